@@ -0,0 +1,226 @@
+//! Sign-in state and the sign-in / sign-up screens
+//!
+//! The signed-in user is shared through a `Signal<Option<UserResponse>>`
+//! context. Its session token is saved with the settings so the user stays
+//! signed in across restarts until the token expires.
+
+use dioxus::prelude::*;
+use playshelf_types::validation::{collect_errors, validate_name, validate_password, validate_username};
+
+use crate::api::{self, LoginRequest, RegisterRequest, SessionResponse, UserResponse};
+use crate::settings::{self, Settings};
+use crate::Route;
+
+/// Saves the session token and makes `session.user` the signed-in user
+pub fn sign_in(session: SessionResponse) {
+    api::set_session_token(Some(session.token.clone()));
+    // The session still works for this run if it cannot be saved
+    let _ = settings::save(&Settings {
+        session_token: Some(session.token),
+        ..settings::load()
+    });
+    consume_context::<Signal<Option<UserResponse>>>().set(Some(session.user));
+}
+
+/// Forgets the session token and the signed-in user
+pub fn sign_out() {
+    api::set_session_token(None);
+    let _ = settings::save(&Settings {
+        session_token: None,
+        ..settings::load()
+    });
+    consume_context::<Signal<Option<UserResponse>>>().set(None);
+}
+
+/// Replaces the signed-in user after the server returned an updated copy
+pub fn update_account(user: UserResponse) {
+    consume_context::<Signal<Option<UserResponse>>>().set(Some(user));
+}
+
+/// Shows who is signed in, or links to the sign-in screen
+#[component]
+pub fn AccountBar() -> Element {
+    let account = use_context::<Signal<Option<UserResponse>>>();
+    let mut route = use_context::<Signal<Route>>();
+
+    rsx! {
+        div {
+            class: "account-bar",
+            match account() {
+                Some(user) => rsx! {
+                    span { class: "account-name", "👤 {user.username}" }
+                    button {
+                        class: "account-button",
+                        onclick: move |_| route.set(Route::Collections),
+                        "📚 Collections"
+                    }
+                    button {
+                        class: "account-button",
+                        onclick: move |_| route.set(Route::Stats),
+                        "📊 Stats"
+                    }
+                    button {
+                        class: "account-button",
+                        onclick: move |_| sign_out(),
+                        "Sign out"
+                    }
+                },
+                None => rsx! {
+                    button {
+                        class: "account-button",
+                        onclick: move |_| route.set(Route::SignIn),
+                        "Sign in"
+                    }
+                    button {
+                        class: "account-button",
+                        onclick: move |_| route.set(Route::SignUp),
+                        "Sign up"
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Sign-in form, or the sign-up form when `sign_up` is set
+#[component]
+pub fn AuthPage(sign_up: bool) -> Element {
+    let mut route = use_context::<Signal<Route>>();
+    let mut username = use_signal(String::new);
+    let mut name = use_signal(String::new);
+    let mut password = use_signal(String::new);
+    let mut submitting = use_signal(|| false);
+    let mut error = use_signal(|| Option::<String>::None);
+
+    // The display name defaults to the username
+    let display_name = move || if name().trim().is_empty() { username() } else { name() };
+    // Sign-up fields are checked with the server's rules before submitting
+    let problems = move || {
+        if !sign_up {
+            return Vec::new();
+        }
+        collect_errors([
+            validate_username(username().trim()),
+            validate_name(&display_name()),
+            validate_password(&password()),
+        ])
+    };
+
+    let submit = move |evt: FormEvent| {
+        evt.prevent_default();
+        if let Some(problem) = problems().into_iter().next() {
+            error.set(Some(problem.message));
+            return;
+        }
+        spawn(async move {
+            submitting.set(true);
+            error.set(None);
+            let result = if sign_up {
+                api::register(&RegisterRequest {
+                    username: username().trim().to_string(),
+                    name: display_name(),
+                    description: String::new(),
+                    password: password(),
+                })
+                .await
+            } else {
+                api::login(&LoginRequest {
+                    username: username().trim().to_string(),
+                    password: password(),
+                })
+                .await
+            };
+            submitting.set(false);
+            match result {
+                Ok(session) => {
+                    sign_in(session);
+                    route.set(Route::Home);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    };
+
+    let submit_label = match (sign_up, submitting()) {
+        (_, true) => "Please wait...",
+        (true, false) => "Sign up",
+        (false, false) => "Sign in",
+    };
+
+    rsx! {
+        div {
+            class: "settings-page",
+
+            button {
+                class: "back-button",
+                onclick: move |_| route.set(Route::Home),
+                "← Back to games"
+            }
+
+            h2 { class: "game-detail-title", if sign_up { "Create an account" } else { "Sign in" } }
+
+            form {
+                class: "auth-form",
+                onsubmit: submit,
+
+                label { class: "settings-label", r#for: "auth-username", "Username" }
+                input {
+                    id: "auth-username",
+                    class: "search-input settings-input",
+                    r#type: "text",
+                    autocomplete: "username",
+                    value: "{username()}",
+                    // Usernames are lowercase, so typing "Link" still finds "link"
+                    oninput: move |evt| username.set(evt.value().to_lowercase()),
+                }
+                if sign_up {
+                    div { class: "settings-hint", "3-32 lowercase letters, digits, '_' or '-', starting with a letter" }
+                }
+
+                if sign_up {
+                    label { class: "settings-label", r#for: "auth-name", "Display name" }
+                    input {
+                        id: "auth-name",
+                        class: "search-input settings-input",
+                        r#type: "text",
+                        placeholder: "Optional",
+                        value: "{name()}",
+                        oninput: move |evt| name.set(evt.value()),
+                    }
+                }
+
+                label { class: "settings-label", r#for: "auth-password", "Password" }
+                input {
+                    id: "auth-password",
+                    class: "search-input settings-input",
+                    r#type: "password",
+                    autocomplete: if sign_up { "new-password" } else { "current-password" },
+                    value: "{password()}",
+                    oninput: move |evt| password.set(evt.value()),
+                }
+
+                div {
+                    class: "settings-actions",
+                    button {
+                        class: "search-button",
+                        r#type: "submit",
+                        disabled: submitting() || username().trim().is_empty() || password().is_empty(),
+                        {submit_label}
+                    }
+                    button {
+                        class: "search-button settings-secondary",
+                        r#type: "button",
+                        onclick: move |_| route.set(if sign_up { Route::SignIn } else { Route::SignUp }),
+                        if sign_up { "I already have an account" } else { "Create an account" }
+                    }
+                }
+            }
+
+            if let Some(message) = error() {
+                for line in message.lines() {
+                    div { class: "settings-status settings-status-error", "⚠️ {line}" }
+                }
+            }
+        }
+    }
+}
@@ -1,72 +1,310 @@
-use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use std::sync::RwLock;
 
-const API_BASE_URL: &str = "http://localhost:8081";
+pub use playshelf_types::{
+    CollectGameRequest, Collection, CollectionNameRequest, ErrorResponse, ExportFormat, GameData, GameDetail,
+    GameReview, GameReviewsResponse, GamesResponse, ImportItem, ImportPreview, ImportPreviewRequest, ImportRequest,
+    ImportResponse, ImportRow, LibraryStats, LogSessionRequest, LoginRequest, MatchConfidence, OrderRequest,
+    PlaySession, PlaytimeTotals, Rating, RegisterRequest, ReviewRequest, SessionResponse, ShelfEntry, ShelfStatus,
+    SimilarGame, StatusChangeRequest, SteamImportResponse, SteamOwnedGame, SteamOwnedGames, UserResponse,
+};
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
-pub struct GameData {
-    pub id: u64,
-    pub name: String,
-    pub platforms: Vec<String>,
-    pub first_release_date: String,
-    pub genres: Vec<String>,
+/// Server used unless another one is chosen in the settings screen;
+/// set `PLAYSHELF_API_URL` when building to change it
+pub const DEFAULT_API_BASE_URL: &str = match option_env!("PLAYSHELF_API_URL") {
+    Some(url) => url,
+    None => "http://localhost:8081",
+};
+
+/// Server chosen at runtime, overriding `DEFAULT_API_BASE_URL`
+static API_BASE_URL: RwLock<Option<String>> = RwLock::new(None);
+
+/// Returns the base URL requests are currently sent to
+pub fn api_base_url() -> String {
+    API_BASE_URL
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| DEFAULT_API_BASE_URL.to_string())
+}
+
+/// Sends further requests to `url`, or back to the default server if `None`
+pub fn set_api_base_url(url: Option<String>) {
+    *API_BASE_URL.write().unwrap() = url;
+}
+
+/// Session token of the signed-in user, sent with every request
+static SESSION_TOKEN: RwLock<Option<String>> = RwLock::new(None);
+
+/// Signs further requests with `token`, or sends them anonymously if `None`
+pub fn set_session_token(token: Option<String>) {
+    *SESSION_TOKEN.write().unwrap() = token;
 }
 
-#[derive(Debug, Deserialize)]
-pub struct GamesResponse {
-    pub count: usize,
-    pub games: Vec<GameData>,
+/// Whether requests are currently signed with a session token
+pub fn has_session_token() -> bool {
+    SESSION_TOKEN.read().unwrap().is_some()
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ErrorResponse {
-    pub error: String,
+/// Checks that `input` is an http(s) URL and strips any trailing slash
+pub fn normalize_base_url(input: &str) -> Result<String, String> {
+    let url = input.trim().trim_end_matches('/');
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("'{}' is not a valid URL: {}", url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("The server URL must start with http:// or https://".to_string());
+    }
+    Ok(url.to_string())
 }
 
-/// Fetch all games from the server
-pub async fn fetch_games() -> Result<Vec<GameData>, String> {
-    let url = format!("{}/games", API_BASE_URL);
-    let response = reqwest::get(&url)
+/// Number of games requested per page
+pub const PAGE_SIZE: u32 = 20;
+
+/// Sends `request`, signed in if there is a session, and parses the JSON
+/// response or the server's error message
+async fn send_json<T: DeserializeOwned>(request: reqwest::RequestBuilder, action: &str) -> Result<T, String> {
+    let request = match SESSION_TOKEN.read().unwrap().as_deref() {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    };
+    let response = request
+        .send()
         .await
-        .map_err(|e| format!("Failed to fetch games: {}", e))?;
+        .map_err(|e| format!("Failed to {}: {}", action, e))?;
 
     if response.status().is_success() {
-        let games_response: GamesResponse = response
+        response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse response: {}", e))?;
-        Ok(games_response.games)
+            .map_err(|e| format!("Failed to parse response: {}", e))
     } else {
         let error_response: ErrorResponse = response
             .json()
             .await
-            .unwrap_or(ErrorResponse {
-                error: "Unknown error".to_string(),
-            });
+            .unwrap_or_else(|_| ErrorResponse::new("Unknown error"));
+        if error_response.fields.len() > 1 {
+            // Validation errors list every rejected field
+            let messages: Vec<String> = error_response.fields.into_iter().map(|field| field.message).collect();
+            return Err(messages.join("\n"));
+        }
         Err(error_response.error)
     }
 }
 
-/// Search for games by query string
-pub async fn search_games(query: String) -> Result<Vec<GameData>, String> {
-    let url = format!("{}/games/search?query={}", API_BASE_URL, urlencoding::encode(&query));
-    let response = reqwest::get(&url)
-        .await
-        .map_err(|e| format!("Failed to search games: {}", e))?;
+/// Sends a GET request and parses the JSON response
+async fn get_json<T: DeserializeOwned>(url: &str, action: &str) -> Result<T, String> {
+    send_json(reqwest::Client::new().get(url), action).await
+}
 
+/// Checks that a PlayShelf server answers at `base_url`
+pub async fn test_connection(base_url: &str) -> Result<(), String> {
+    let response = reqwest::get(format!("{}/", base_url))
+        .await
+        .map_err(|e| format!("Failed to connect: {}", e))?;
     if response.status().is_success() {
-        let games_response: GamesResponse = response
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse response: {}", e))?;
-        Ok(games_response.games)
+        Ok(())
     } else {
-        let error_response: ErrorResponse = response
-            .json()
-            .await
-            .unwrap_or(ErrorResponse {
-                error: "Unknown error".to_string(),
-            });
-        Err(error_response.error)
+        Err(format!("Server responded with {}", response.status()))
     }
 }
 
+/// Fetch a page of games from the server
+pub async fn fetch_games(offset: u32) -> Result<GamesResponse, String> {
+    let url = format!("{}/games?limit={}&offset={}", api_base_url(), PAGE_SIZE, offset);
+    get_json(&url, "fetch games").await
+}
+
+/// Fetch the extended record of a single game
+pub async fn fetch_game_detail(id: u64) -> Result<GameDetail, String> {
+    let url = format!("{}/games/{}", api_base_url(), id);
+    get_json(&url, "fetch game").await
+}
+
+/// Fetch every user's rating and review of a game
+pub async fn fetch_game_reviews(id: u64, offset: u32) -> Result<GameReviewsResponse, String> {
+    let url = format!("{}/games/{}/reviews?offset={}", api_base_url(), id, offset);
+    get_json(&url, "fetch reviews").await
+}
+
+/// Search for games by query string, one page at a time
+pub async fn search_games(query: String, offset: u32) -> Result<GamesResponse, String> {
+    let url = format!(
+        "{}/games/search?query={}&limit={}&offset={}",
+        api_base_url(),
+        urlencoding::encode(&query),
+        PAGE_SIZE,
+        offset
+    );
+    get_json(&url, "search games").await
+}
+
+/// Fetch a page of games, searching when `query` is not empty
+pub async fn fetch_game_page(query: String, offset: u32) -> Result<GamesResponse, String> {
+    if query.is_empty() {
+        fetch_games(offset).await
+    } else {
+        search_games(query, offset).await
+    }
+}
+
+/// Create an account and sign in to it
+pub async fn register(request: &RegisterRequest) -> Result<SessionResponse, String> {
+    let url = format!("{}/auth/register", api_base_url());
+    send_json(reqwest::Client::new().post(url).json(request), "sign up").await
+}
+
+/// Sign in with a username and password
+pub async fn login(request: &LoginRequest) -> Result<SessionResponse, String> {
+    let url = format!("{}/auth/login", api_base_url());
+    send_json(reqwest::Client::new().post(url).json(request), "sign in").await
+}
+
+/// Fetch the signed-in user
+pub async fn fetch_me() -> Result<UserResponse, String> {
+    let url = format!("{}/auth/me", api_base_url());
+    get_json(&url, "fetch account").await
+}
+
+/// Fetch the breakdowns and trends of a user's library
+pub async fn fetch_stats(username: &str) -> Result<LibraryStats, String> {
+    let url = format!("{}/users/{}/stats", api_base_url(), urlencoding::encode(username));
+    get_json(&url, "fetch stats").await
+}
+
+/// URL downloading a user's library as a file in `format`
+pub fn export_url(username: &str, format: ExportFormat) -> String {
+    format!(
+        "{}/users/{}/export?format={}",
+        api_base_url(),
+        urlencoding::encode(username),
+        format.extension()
+    )
+}
+
+/// Match the rows of a CSV of games against IGDB without importing them
+pub async fn preview_import(username: &str, csv: String) -> Result<ImportPreview, String> {
+    let url = format!("{}/users/{}/import/preview", api_base_url(), urlencoding::encode(username));
+    let request = ImportPreviewRequest { csv };
+    send_json(reqwest::Client::new().post(url).json(&request), "match games").await
+}
+
+/// Add the confirmed matches of an import to the signed-in user's library
+pub async fn import_games(username: &str, items: Vec<ImportItem>) -> Result<ImportResponse, String> {
+    let url = format!("{}/users/{}/import", api_base_url(), urlencoding::encode(username));
+    let request = ImportRequest { items };
+    send_json(reqwest::Client::new().post(url).json(&request), "import games").await
+}
+
+/// Add the games of a saved Steam `GetOwnedGames` response to the signed-in
+/// user's library with their playtime
+pub async fn import_steam(username: &str, owned: &SteamOwnedGames) -> Result<SteamImportResponse, String> {
+    let url = format!("{}/users/{}/import/steam", api_base_url(), urlencoding::encode(username));
+    send_json(reqwest::Client::new().post(url).json(owned), "import Steam library").await
+}
+
+/// Add a game to the signed-in user's library, returning the updated user
+pub async fn add_to_library(username: &str, game: &GameData) -> Result<UserResponse, String> {
+    let url = format!("{}/users/{}/games", api_base_url(), urlencoding::encode(username));
+    send_json(reqwest::Client::new().post(url).json(game), "add game").await
+}
+
+/// Move a game on the signed-in user's shelf to `status` as of today, returning the updated user
+pub async fn set_game_status(username: &str, game_id: u64, status: ShelfStatus) -> Result<UserResponse, String> {
+    let url = format!(
+        "{}/users/{}/games/{}/status",
+        api_base_url(),
+        urlencoding::encode(username),
+        game_id
+    );
+    let request = StatusChangeRequest { status, date: None };
+    send_json(reqwest::Client::new().put(url).json(&request), "change status").await
+}
+
+/// Replace the signed-in user's rating and review of a game, returning the updated user
+pub async fn save_review(username: &str, game_id: u64, request: &ReviewRequest) -> Result<UserResponse, String> {
+    let url = format!(
+        "{}/users/{}/games/{}/review",
+        api_base_url(),
+        urlencoding::encode(username),
+        game_id
+    );
+    send_json(reqwest::Client::new().put(url).json(request), "save review").await
+}
+
+/// Log a play session of a game on the signed-in user's shelf, returning the updated user
+pub async fn log_session(username: &str, game_id: u64, request: &LogSessionRequest) -> Result<UserResponse, String> {
+    let url = format!(
+        "{}/users/{}/games/{}/sessions",
+        api_base_url(),
+        urlencoding::encode(username),
+        game_id
+    );
+    send_json(reqwest::Client::new().post(url).json(request), "log session").await
+}
+
+/// Delete one of the signed-in user's play sessions, returning the updated user
+pub async fn delete_session(username: &str, game_id: u64, id: u64) -> Result<UserResponse, String> {
+    let url = format!(
+        "{}/users/{}/games/{}/sessions/{}",
+        api_base_url(),
+        urlencoding::encode(username),
+        game_id,
+        id
+    );
+    send_json(reqwest::Client::new().delete(url), "delete session").await
+}
+
+/// Remove a game from the signed-in user's library, returning the updated user
+pub async fn remove_from_library(username: &str, game_id: u64) -> Result<UserResponse, String> {
+    let url = format!("{}/users/{}/games/{}", api_base_url(), urlencoding::encode(username), game_id);
+    send_json(reqwest::Client::new().delete(url), "remove game").await
+}
+
+/// URL of the signed-in user's collections, or of the part of one at `path`
+fn collections_url(username: &str, path: &str) -> String {
+    format!("{}/users/{}/collections{}", api_base_url(), urlencoding::encode(username), path)
+}
+
+/// Create an empty collection, returning the updated user
+pub async fn create_collection(username: &str, name: &str) -> Result<UserResponse, String> {
+    let request = CollectionNameRequest { name: name.to_string() };
+    let url = collections_url(username, "");
+    send_json(reqwest::Client::new().post(url).json(&request), "create collection").await
+}
+
+/// Rename a collection, returning the updated user
+pub async fn rename_collection(username: &str, id: u64, name: &str) -> Result<UserResponse, String> {
+    let request = CollectionNameRequest { name: name.to_string() };
+    let url = collections_url(username, &format!("/{}", id));
+    send_json(reqwest::Client::new().patch(url).json(&request), "rename collection").await
+}
+
+/// Delete a collection, returning the updated user
+pub async fn delete_collection(username: &str, id: u64) -> Result<UserResponse, String> {
+    let url = collections_url(username, &format!("/{}", id));
+    send_json(reqwest::Client::new().delete(url), "delete collection").await
+}
+
+/// Put the collections in the order of `ids`, returning the updated user
+pub async fn reorder_collections(username: &str, ids: Vec<u64>) -> Result<UserResponse, String> {
+    let url = collections_url(username, "/order");
+    send_json(reqwest::Client::new().put(url).json(&OrderRequest { ids }), "reorder collections").await
+}
+
+/// Add a game from the shelf to the end of a collection, returning the updated user
+pub async fn collect_game(username: &str, id: u64, game_id: u64) -> Result<UserResponse, String> {
+    let request = CollectGameRequest { game_id, position: None };
+    let url = collections_url(username, &format!("/{}/games", id));
+    send_json(reqwest::Client::new().post(url).json(&request), "add to collection").await
+}
+
+/// Take a game out of a collection, returning the updated user
+pub async fn uncollect_game(username: &str, id: u64, game_id: u64) -> Result<UserResponse, String> {
+    let url = collections_url(username, &format!("/{}/games/{}", id, game_id));
+    send_json(reqwest::Client::new().delete(url), "remove from collection").await
+}
+
+/// Put the games of a collection in the order of `game_ids`, returning the updated user
+pub async fn reorder_collection(username: &str, id: u64, game_ids: Vec<u64>) -> Result<UserResponse, String> {
+    let url = collections_url(username, &format!("/{}/order", id));
+    send_json(reqwest::Client::new().put(url).json(&OrderRequest { ids: game_ids }), "reorder collection").await
+}
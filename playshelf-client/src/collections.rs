@@ -0,0 +1,441 @@
+//! Screens for managing the signed-in user's collections
+//!
+//! Collections come with the signed-in user, and every change returns the
+//! updated user, so these screens read straight from the account context
+//! instead of fetching collections separately.
+
+use dioxus::prelude::*;
+use std::future::Future;
+
+use crate::account;
+use crate::api::{self, Collection, UserResponse};
+use crate::{open_game, Route};
+
+/// Sends a change to the server and applies the returned user, showing any error
+async fn apply(
+    change: impl Future<Output = Result<UserResponse, String>>,
+    mut busy: Signal<bool>,
+    mut error: Signal<Option<String>>,
+) {
+    busy.set(true);
+    error.set(None);
+    match change.await {
+        Ok(user) => account::update_account(user),
+        Err(e) => error.set(Some(e)),
+    }
+    busy.set(false);
+}
+
+/// `ids` with the item at `index` swapped with the one before it (`up`) or
+/// after it, or `None` if it is already at that end
+fn moved(ids: &[u64], index: usize, up: bool) -> Option<Vec<u64>> {
+    let other = if up { index.checked_sub(1)? } else { index + 1 };
+    if other >= ids.len() {
+        return None;
+    }
+    let mut ids = ids.to_vec();
+    ids.swap(index, other);
+    Some(ids)
+}
+
+/// Lists the signed-in user's collections with controls to create, rename,
+/// reorder and delete them
+#[component]
+pub fn CollectionsPage() -> Element {
+    let account = use_context::<Signal<Option<UserResponse>>>();
+    let mut route = use_context::<Signal<Route>>();
+    let mut new_name = use_signal(String::new);
+    // Collection being renamed and its new name
+    let mut renaming = use_signal(|| Option::<(u64, String)>::None);
+    let busy = use_signal(|| false);
+    let error = use_signal(|| Option::<String>::None);
+
+    let Some(user) = account() else {
+        return rsx! {
+            div {
+                class: "settings-page",
+                p { class: "library-hint", "Sign in to organise your games into collections." }
+            }
+        };
+    };
+    let username = user.username.clone();
+    let ids: Vec<u64> = user.collections.iter().map(|collection| collection.id).collect();
+
+    let create = {
+        let username = username.clone();
+        move |evt: FormEvent| {
+            evt.prevent_default();
+            let username = username.clone();
+            let name = new_name().trim().to_string();
+            spawn(async move {
+                apply(api::create_collection(&username, &name), busy, error).await;
+                if error().is_none() {
+                    new_name.set(String::new());
+                }
+            });
+        }
+    };
+
+    rsx! {
+        div {
+            class: "settings-page",
+
+            button {
+                class: "back-button",
+                onclick: move |_| route.set(Route::Home),
+                "← Back to games"
+            }
+
+            h2 { class: "game-detail-title", "Collections" }
+
+            form {
+                class: "collection-create",
+                onsubmit: create,
+                input {
+                    class: "search-input",
+                    r#type: "text",
+                    placeholder: "New collection, e.g. Couch co-op",
+                    value: "{new_name()}",
+                    oninput: move |evt| new_name.set(evt.value()),
+                }
+                button {
+                    class: "search-button",
+                    r#type: "submit",
+                    disabled: busy() || new_name().trim().is_empty(),
+                    "Create"
+                }
+            }
+
+            if let Some(message) = error() {
+                div { class: "settings-status settings-status-error", "⚠️ {message}" }
+            }
+
+            if user.collections.is_empty() {
+                p { class: "library-hint", "You have no collections yet." }
+            }
+
+            for (index, collection) in user.collections.iter().enumerate() {
+                div {
+                    key: "{collection.id}",
+                    class: "collection-row",
+
+                    if let Some((_, name)) = renaming().filter(|(id, _)| *id == collection.id) {
+                        form {
+                            class: "collection-rename",
+                            onsubmit: {
+                                let username = username.clone();
+                                let id = collection.id;
+                                move |evt: FormEvent| {
+                                    evt.prevent_default();
+                                    let Some((_, name)) = renaming() else {
+                                        return;
+                                    };
+                                    let username = username.clone();
+                                    let name = name.trim().to_string();
+                                    spawn(async move {
+                                        apply(api::rename_collection(&username, id, &name), busy, error).await;
+                                        if error().is_none() {
+                                            renaming.set(None);
+                                        }
+                                    });
+                                }
+                            },
+                            input {
+                                class: "search-input",
+                                r#type: "text",
+                                value: "{name}",
+                                oninput: {
+                                    let id = collection.id;
+                                    move |evt: FormEvent| renaming.set(Some((id, evt.value())))
+                                },
+                            }
+                            button { class: "search-button", r#type: "submit", disabled: busy(), "Save" }
+                            button {
+                                class: "search-button settings-secondary",
+                                r#type: "button",
+                                onclick: move |_| renaming.set(None),
+                                "Cancel"
+                            }
+                        }
+                    } else {
+                        button {
+                            class: "collection-name",
+                            onclick: {
+                                let id = collection.id;
+                                move |_| route.set(Route::Collection { id })
+                            },
+                            "{collection.name}"
+                        }
+                        span { class: "collection-count", "{collection.game_ids.len()} games" }
+                        div {
+                            class: "collection-controls",
+                            ReorderButtons {
+                                username: username.clone(),
+                                up: moved(&ids, index, true),
+                                down: moved(&ids, index, false),
+                                collection: None,
+                                busy,
+                                error,
+                            }
+                            button {
+                                class: "icon-button",
+                                title: "Rename",
+                                onclick: {
+                                    let id = collection.id;
+                                    let name = collection.name.clone();
+                                    move |_| renaming.set(Some((id, name.clone())))
+                                },
+                                "✏️"
+                            }
+                            button {
+                                class: "icon-button",
+                                title: "Delete",
+                                disabled: busy(),
+                                onclick: {
+                                    let username = username.clone();
+                                    let id = collection.id;
+                                    move |_| {
+                                        let username = username.clone();
+                                        spawn(async move {
+                                            apply(api::delete_collection(&username, id), busy, error).await;
+                                        });
+                                    }
+                                },
+                                "🗑️"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Up and down buttons sending the new order of the collections, or of the
+/// games in `collection`, when clicked
+#[component]
+fn ReorderButtons(
+    username: String,
+    up: Option<Vec<u64>>,
+    down: Option<Vec<u64>>,
+    collection: Option<u64>,
+    busy: Signal<bool>,
+    error: Signal<Option<String>>,
+) -> Element {
+    let send = move |ids: Vec<u64>| {
+        let username = username.clone();
+        spawn(async move {
+            match collection {
+                Some(id) => apply(api::reorder_collection(&username, id, ids), busy, error).await,
+                None => apply(api::reorder_collections(&username, ids), busy, error).await,
+            }
+        });
+    };
+    let send_down = send.clone();
+
+    rsx! {
+        button {
+            class: "icon-button",
+            title: "Move up",
+            disabled: busy() || up.is_none(),
+            onclick: move |_| {
+                if let Some(ids) = up.clone() {
+                    send(ids);
+                }
+            },
+            "▲"
+        }
+        button {
+            class: "icon-button",
+            title: "Move down",
+            disabled: busy() || down.is_none(),
+            onclick: move |_| {
+                if let Some(ids) = down.clone() {
+                    send_down(ids);
+                }
+            },
+            "▼"
+        }
+    }
+}
+
+/// The games of one collection in order, with controls to reorder and remove them
+#[component]
+pub fn CollectionPage(id: u64) -> Element {
+    let account = use_context::<Signal<Option<UserResponse>>>();
+    let mut route = use_context::<Signal<Route>>();
+    let busy = use_signal(|| false);
+    let error = use_signal(|| Option::<String>::None);
+
+    let Some(user) = account() else {
+        return rsx! {};
+    };
+    let Some(collection) = user.collections.iter().find(|collection| collection.id == id).cloned() else {
+        return rsx! {
+            div {
+                class: "settings-page",
+                button {
+                    class: "back-button",
+                    onclick: move |_| route.set(Route::Collections),
+                    "← Back to collections"
+                }
+                p { class: "library-hint", "This collection no longer exists." }
+            }
+        };
+    };
+    let username = user.username.clone();
+    let entries: Vec<_> = collection
+        .game_ids
+        .iter()
+        .filter_map(|game_id| user.games.iter().find(|entry| entry.game.id == *game_id).cloned())
+        .collect();
+
+    rsx! {
+        div {
+            class: "settings-page",
+
+            button {
+                class: "back-button",
+                onclick: move |_| route.set(Route::Collections),
+                "← Back to collections"
+            }
+
+            h2 { class: "game-detail-title", "{collection.name}" }
+
+            if let Some(message) = error() {
+                div { class: "settings-status settings-status-error", "⚠️ {message}" }
+            }
+
+            if entries.is_empty() {
+                p { class: "library-hint", "Add games to this collection from their pages." }
+            }
+
+            for (index, entry) in entries.iter().enumerate() {
+                div {
+                    key: "{entry.game.id}",
+                    class: "collection-row",
+
+                    if let Some(cover) = &entry.game.cover {
+                        img { class: "collection-thumb", src: "{cover.thumb}", alt: "" }
+                    }
+                    button {
+                        class: "collection-name",
+                        onclick: {
+                            let game_id = entry.game.id;
+                            move |_| open_game(game_id)
+                        },
+                        "{entry.game.name}"
+                    }
+                    span { class: "collection-count", "{entry.status.label()}" }
+                    div {
+                        class: "collection-controls",
+                        ReorderButtons {
+                            username: username.clone(),
+                            up: moved(&collection.game_ids, index, true),
+                            down: moved(&collection.game_ids, index, false),
+                            collection: Some(id),
+                            busy,
+                            error,
+                        }
+                        button {
+                            class: "icon-button",
+                            title: "Remove from collection",
+                            disabled: busy(),
+                            onclick: {
+                                let username = username.clone();
+                                let game_id = entry.game.id;
+                                move |_| {
+                                    let username = username.clone();
+                                    spawn(async move {
+                                        apply(api::uncollect_game(&username, id, game_id), busy, error).await;
+                                    });
+                                }
+                            },
+                            "✕"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Shows which collections a shelved game is in and adds it to others
+#[component]
+pub fn CollectionPicker(game_id: u64) -> Element {
+    let account = use_context::<Signal<Option<UserResponse>>>();
+    let mut route = use_context::<Signal<Route>>();
+    let busy = use_signal(|| false);
+    let error = use_signal(|| Option::<String>::None);
+
+    let Some(user) = account() else {
+        return rsx! {};
+    };
+    let username = user.username.clone();
+    let (member, other): (Vec<Collection>, Vec<Collection>) = user
+        .collections
+        .into_iter()
+        .partition(|collection| collection.game_ids.contains(&game_id));
+
+    let add = {
+        let username = username.clone();
+        move |evt: FormEvent| {
+            let Ok(id) = evt.value().parse::<u64>() else {
+                return;
+            };
+            let username = username.clone();
+            spawn(async move {
+                apply(api::collect_game(&username, id, game_id), busy, error).await;
+            });
+        }
+    };
+
+    rsx! {
+        div {
+            class: "collection-picker",
+            for collection in member.iter() {
+                span {
+                    key: "{collection.id}",
+                    class: "collection-chip",
+                    "📚 {collection.name}"
+                    button {
+                        class: "collection-chip-remove",
+                        title: "Remove from {collection.name}",
+                        disabled: busy(),
+                        onclick: {
+                            let username = username.clone();
+                            let id = collection.id;
+                            move |_| {
+                                let username = username.clone();
+                                spawn(async move {
+                                    apply(api::uncollect_game(&username, id, game_id), busy, error).await;
+                                });
+                            }
+                        },
+                        "✕"
+                    }
+                }
+            }
+            if other.is_empty() && member.is_empty() {
+                button {
+                    class: "search-button settings-secondary",
+                    onclick: move |_| route.set(Route::Collections),
+                    "Create a collection"
+                }
+            } else if !other.is_empty() {
+                select {
+                    class: "shelf-status",
+                    disabled: busy(),
+                    onchange: add,
+                    option { value: "", selected: true, "Add to collection…" }
+                    for collection in other.iter() {
+                        option { key: "{collection.id}", value: "{collection.id}", "{collection.name}" }
+                    }
+                }
+            }
+            if let Some(message) = error() {
+                span { class: "library-error", "⚠️ {message}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,355 @@
+//! Importing a CSV of games or a Steam library into the signed-in user's library
+//!
+//! For a CSV the server matches every row against IGDB first; the user then
+//! checks the matches, picks between candidates where the match is unsure,
+//! and imports the rows they kept. Steam games are matched by app ID, so they
+//! are imported in one step.
+
+use dioxus::prelude::*;
+use playshelf_types::MAX_STEAM_GAMES;
+
+use crate::account;
+use crate::api::{self, ImportItem, ImportRow, MatchConfidence, SteamOwnedGame, SteamOwnedGames, UserResponse};
+use crate::Route;
+
+/// Candidate picked for a row when the preview arrives: the best one if the
+/// match is confident and the game is not already on the shelf
+fn default_choice(row: &ImportRow) -> Option<usize> {
+    (row.confidence == MatchConfidence::Confident && row.on_shelf.is_none()).then_some(0)
+}
+
+fn confidence_label(confidence: MatchConfidence) -> &'static str {
+    match confidence {
+        MatchConfidence::Confident => "Matched",
+        MatchConfidence::Ambiguous => "Check match",
+        MatchConfidence::Unmatched => "No match",
+    }
+}
+
+fn confidence_class(confidence: MatchConfidence) -> &'static str {
+    match confidence {
+        MatchConfidence::Confident => "import-confidence import-confident",
+        MatchConfidence::Ambiguous => "import-confidence import-ambiguous",
+        MatchConfidence::Unmatched => "import-confidence import-unmatched",
+    }
+}
+
+/// "Name (Platform, Platform) · 87%"
+fn candidate_label(row: &ImportRow, index: usize) -> String {
+    let candidate = &row.candidates[index];
+    let percent = (candidate.score * 100.0).round();
+    if candidate.game.platforms.is_empty() {
+        format!("{} · {}%", candidate.game.name, percent)
+    } else {
+        format!("{} ({}) · {}%", candidate.game.name, candidate.game.platforms.join(", "), percent)
+    }
+}
+
+/// What the row will add, e.g. "Completed · 4.5★"
+fn row_summary(row: &ImportRow) -> String {
+    match row.rating {
+        Some(rating) => format!("{} · {}", row.status.label(), rating.star_label()),
+        None => row.status.label().to_string(),
+    }
+}
+
+#[component]
+pub fn ImportPage() -> Element {
+    let account = use_context::<Signal<Option<UserResponse>>>();
+    let mut route = use_context::<Signal<Route>>();
+    let mut csv = use_signal(String::new);
+    let mut rows = use_signal(Vec::<ImportRow>::new);
+    let mut choices = use_signal(Vec::<Option<usize>>::new);
+    let mut busy = use_signal(|| false);
+    let mut error = use_signal(|| None::<String>);
+    let mut notice = use_signal(|| None::<String>);
+
+    let Some(user) = account() else {
+        return rsx! {
+            div {
+                class: "settings-page",
+                p { class: "library-hint", "Sign in to import games into your library." }
+            }
+        };
+    };
+    let username = user.username.clone();
+
+    let preview_username = username.clone();
+    let preview = move |_| {
+        let username = preview_username.clone();
+        spawn(async move {
+            busy.set(true);
+            error.set(None);
+            notice.set(None);
+            match api::preview_import(&username, csv()).await {
+                Ok(preview) => {
+                    choices.set(preview.rows.iter().map(default_choice).collect());
+                    rows.set(preview.rows);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+            busy.set(false);
+        });
+    };
+
+    let import = move |_| {
+        let username = username.clone();
+        let items: Vec<ImportItem> = rows
+            .read()
+            .iter()
+            .zip(choices.read().iter())
+            .filter_map(|(row, choice)| {
+                choice.map(|index| ImportItem {
+                    game_id: row.candidates[index].game.id,
+                    status: row.status,
+                    rating: row.rating,
+                })
+            })
+            .collect();
+        spawn(async move {
+            busy.set(true);
+            error.set(None);
+            match api::import_games(&username, items).await {
+                Ok(response) => {
+                    let mut message = format!("Imported {} games.", response.imported.len());
+                    if !response.skipped.is_empty() {
+                        message.push_str(&format!(" {} were already in your library.", response.skipped.len()));
+                    }
+                    if !response.not_found.is_empty() {
+                        message.push_str(&format!(" {} are no longer on IGDB.", response.not_found.len()));
+                    }
+                    account::update_account(response.user);
+                    notice.set(Some(message));
+                    rows.set(Vec::new());
+                    choices.set(Vec::new());
+                    csv.set(String::new());
+                }
+                Err(e) => error.set(Some(e)),
+            }
+            busy.set(false);
+        });
+    };
+
+    let chosen = choices.read().iter().filter(|choice| choice.is_some()).count();
+
+    rsx! {
+        div {
+            class: "settings-page",
+
+            button {
+                class: "back-button",
+                onclick: move |_| route.set(Route::Stats),
+                "← Back to stats"
+            }
+
+            h2 { class: "game-detail-title", "Import games" }
+
+            p {
+                class: "library-hint",
+                "Paste or upload a CSV with a header row. It needs a title column; platform, status, rating and IGDB id columns are optional. A PlayShelf CSV export works as is."
+            }
+
+            input {
+                class: "import-file",
+                r#type: "file",
+                accept: ".csv,text/csv",
+                onchange: move |evt: FormEvent| {
+                    let files = evt.files();
+                    spawn(async move {
+                        if let Some(file) = files.first() {
+                            match file.read_string().await {
+                                Ok(text) => csv.set(text),
+                                Err(e) => error.set(Some(format!("Failed to read {}: {}", file.name(), e))),
+                            }
+                        }
+                    });
+                },
+            }
+
+            textarea {
+                class: "search-input review-input import-csv",
+                rows: "8",
+                placeholder: "title,platform,status,rating\nCeleste,PC,completed,4.5",
+                value: "{csv}",
+                oninput: move |evt| csv.set(evt.value()),
+            }
+
+            div {
+                class: "settings-actions",
+                button {
+                    class: "search-button",
+                    disabled: busy() || csv().trim().is_empty(),
+                    onclick: preview,
+                    if busy() && rows.read().is_empty() { "Matching..." } else { "Match games" }
+                }
+            }
+
+            if let Some(err) = error() {
+                div { class: "settings-status settings-status-error", "⚠️ {err}" }
+            }
+
+            if let Some(message) = notice() {
+                div { class: "settings-status settings-status-ok", "✓ {message}" }
+            }
+
+            if !rows.read().is_empty() {
+                div {
+                    class: "import-rows",
+                    for (index, row) in rows().into_iter().enumerate() {
+                        ImportRowView {
+                            key: "{row.line}",
+                            row: row,
+                            choice: choices.read()[index],
+                            on_choose: move |choice| choices.write()[index] = choice,
+                        }
+                    }
+                }
+
+                div {
+                    class: "settings-actions",
+                    button {
+                        class: "search-button",
+                        disabled: busy() || chosen == 0,
+                        onclick: import,
+                        if busy() { "Importing..." } else { "Import {chosen} games" }
+                    }
+                }
+            }
+
+            SteamImport { username: user.username.clone() }
+        }
+    }
+}
+
+/// Imports a Steam library from a saved `GetOwnedGames` response
+#[component]
+fn SteamImport(username: String) -> Element {
+    let mut busy = use_signal(|| false);
+    let mut error = use_signal(|| None::<String>);
+    let mut notice = use_signal(|| None::<String>);
+    let mut unmatched = use_signal(Vec::<SteamOwnedGame>::new);
+
+    let import = move |evt: FormEvent| {
+        let username = username.clone();
+        let files = evt.files();
+        spawn(async move {
+            let Some(file) = files.first() else {
+                return;
+            };
+            busy.set(true);
+            error.set(None);
+            notice.set(None);
+            unmatched.set(Vec::new());
+            let owned = match file.read_string().await {
+                Ok(text) => serde_json::from_str::<SteamOwnedGames>(&text)
+                    .map_err(|e| format!("{} is not a GetOwnedGames response: {}", file.name(), e)),
+                Err(e) => Err(format!("Failed to read {}: {}", file.name(), e)),
+            };
+            let result = match owned {
+                Ok(owned) => api::import_steam(&username, &owned).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(response) => {
+                    notice.set(Some(format!(
+                        "Imported {} games and added playtime to {}; {} were already in your library.",
+                        response.imported.len(),
+                        response.playtime_added.len(),
+                        response.skipped.len()
+                    )));
+                    unmatched.set(response.unmatched);
+                    account::update_account(response.user);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+            busy.set(false);
+        });
+    };
+
+    rsx! {
+        h3 { class: "settings-label import-steam-title", "From Steam" }
+        p {
+            class: "library-hint",
+            "Upload the JSON of a GetOwnedGames request to the Steam Web API, made with include_appinfo=1 so unmatched games show their names. Games come in as backlog with their Steam playtime."
+            " Up to {MAX_STEAM_GAMES} games are read from one file."
+        }
+        input {
+            class: "import-file",
+            r#type: "file",
+            accept: ".json,application/json",
+            disabled: busy(),
+            onchange: import,
+        }
+        if busy() {
+            div { class: "loading-text", "Importing Steam library..." }
+        }
+        if let Some(err) = error() {
+            div { class: "settings-status settings-status-error", "⚠️ {err}" }
+        }
+        if let Some(message) = notice() {
+            div { class: "settings-status settings-status-ok", "✓ {message}" }
+        }
+        if !unmatched.read().is_empty() {
+            p { class: "import-warning", "IGDB has no record of these games, so they were left out:" }
+            ul {
+                class: "import-unmatched-list",
+                for game in unmatched() {
+                    li { key: "{game.appid}", "{game.label()}" }
+                }
+            }
+        }
+    }
+}
+
+/// One CSV row with its match and a picker for the game to import
+#[component]
+fn ImportRowView(row: ImportRow, choice: Option<usize>, on_choose: EventHandler<Option<usize>>) -> Element {
+    let summary = row_summary(&row);
+    let best = row.candidates.first().map(|candidate| (candidate.score * 100.0).round());
+
+    rsx! {
+        div {
+            class: if choice.is_some() { "import-row import-row-chosen" } else { "import-row" },
+
+            div {
+                class: "import-row-header",
+                span { class: "import-line", "#{row.line}" }
+                span { class: "import-title", "{row.title}" }
+                if let Some(platform) = &row.platform {
+                    span { class: "library-hint", "{platform}" }
+                }
+                span { class: "library-hint", "{summary}" }
+                span {
+                    class: confidence_class(row.confidence),
+                    "{confidence_label(row.confidence)}"
+                    if let Some(best) = best {
+                        " · {best}%"
+                    }
+                }
+            }
+
+            if !row.candidates.is_empty() {
+                select {
+                    class: "shelf-status import-candidates",
+                    "aria-label": "Game to import",
+                    onchange: move |evt| on_choose.call(evt.value().parse().ok()),
+                    option { value: "", selected: choice.is_none(), "Don't import" }
+                    for index in 0..row.candidates.len() {
+                        option {
+                            value: "{index}",
+                            selected: choice == Some(index),
+                            "{candidate_label(&row, index)}"
+                        }
+                    }
+                }
+            }
+
+            if row.on_shelf.is_some() {
+                p { class: "import-warning", "Already in your library" }
+            }
+            for warning in row.warnings.iter() {
+                p { class: "import-warning", "⚠️ {warning}" }
+            }
+        }
+    }
+}
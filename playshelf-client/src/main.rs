@@ -1,30 +1,90 @@
+mod account;
 mod api;
+mod collections;
+mod import;
+mod reviews;
+mod sessions;
+mod settings;
+mod stats;
 
+use chrono::NaiveDate;
 use dioxus::prelude::*;
-use api::{fetch_games, search_games, GameData};
+use account::{AccountBar, AuthPage};
+use collections::{CollectionPage, CollectionPicker, CollectionsPage};
+use import::ImportPage;
+use reviews::GameReviews;
+use sessions::PlaySessions;
+use api::{fetch_game_detail, fetch_game_page, GameData, GameDetail, ShelfEntry, ShelfStatus, SimilarGame, UserResponse};
+use settings::Settings;
+use stats::StatsPage;
 
 const FAVICON: Asset = asset!("/assets/favicon.ico");
 const MAIN_CSS: Asset = asset!("/assets/main.css");
 
 fn main() {
+    let settings = settings::load();
+    api::set_api_base_url(settings.api_base_url);
+    api::set_session_token(settings.session_token);
     dioxus::launch(App);
 }
 
+/// Page currently shown by the app
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Route {
+    Home,
+    GameDetail { id: u64 },
+    Settings,
+    SignIn,
+    SignUp,
+    Collections,
+    Collection { id: u64 },
+    Stats,
+    Import,
+}
+
+/// Switches to the detail page of game `id`, scrolled to the top
+fn open_game(id: u64) {
+    let mut route = consume_context::<Signal<Route>>();
+    route.set(Route::GameDetail { id });
+    document::eval("window.scrollTo(0, 0)");
+}
+
 #[component]
 fn App() -> Element {
-    let mut games = use_signal(|| Vec::<GameData>::new());
+    let mut games = use_signal(Vec::<GameData>::new);
     let mut loading = use_signal(|| false);
+    let mut loading_more = use_signal(|| false);
     let mut error = use_signal(|| Option::<String>::None);
-    let search_query = use_signal(|| String::new());
+    let search_query = use_signal(String::new);
+    // Query of the results currently shown, used to fetch further pages
+    let mut active_query = use_signal(String::new);
+    let mut next_offset = use_signal(|| Option::<u32>::None);
+    let mut route = use_context_provider(|| Signal::new(Route::Home));
+    let mut account = use_context_provider(|| Signal::new(Option::<UserResponse>::None));
 
-    // Load games on mount
+    // Restore the session saved by a previous run; an expired one just leaves
+    // the user signed out
     use_effect(move || {
+        if !api::has_session_token() {
+            return;
+        }
+        spawn(async move {
+            if let Ok(user) = api::fetch_me().await {
+                account.set(Some(user));
+            }
+        });
+    });
+
+    // Replaces the shown games with the first page of results for `query`
+    let load_first_page = move |query: String| {
         spawn(async move {
             loading.set(true);
             error.set(None);
-            match fetch_games().await {
-                Ok(fetched_games) => {
-                    games.set(fetched_games);
+            active_query.set(query.clone());
+            match fetch_game_page(query, 0).await {
+                Ok(page) => {
+                    games.set(page.games);
+                    next_offset.set(page.next_offset);
                     loading.set(false);
                 }
                 Err(e) => {
@@ -33,7 +93,10 @@ fn App() -> Element {
                 }
             }
         });
-    });
+    };
+
+    // Load games on mount
+    use_effect(move || load_first_page(String::new()));
 
     rsx! {
         document::Link { rel: "icon", href: FAVICON }
@@ -48,7 +111,14 @@ fn App() -> Element {
                 
                 // Header Section
                 div {
-                    style: "text-align: center; margin-bottom: 3rem;",
+                    style: "text-align: center; margin-bottom: 3rem; position: relative;",
+                    AccountBar {}
+                    button {
+                        class: "settings-button",
+                        title: "Settings",
+                        onclick: move |_| route.set(Route::Settings),
+                        "⚙️"
+                    }
                     h1 {
                         style: "color: white; font-size: 3.5rem; margin: 0 0 0.5rem 0; font-weight: 800; text-shadow: 0 4px 12px rgba(0,0,0,0.3); letter-spacing: -0.02em;",
                         "🎮 PlayShelf"
@@ -59,57 +129,94 @@ fn App() -> Element {
                     }
                 }
                 
-                SearchBar {
-                    search_query: search_query,
-                    on_search: move |query: String| {
-                        spawn(async move {
-                            loading.set(true);
-                            error.set(None);
-                            if query.is_empty() {
-                                match fetch_games().await {
-                                    Ok(fetched_games) => {
-                                        games.set(fetched_games);
-                                        loading.set(false);
-                                    }
-                                    Err(e) => {
-                                        error.set(Some(e));
-                                        loading.set(false);
-                                    }
-                                }
-                            } else {
-                                match search_games(query).await {
-                                    Ok(fetched_games) => {
-                                        games.set(fetched_games);
-                                        loading.set(false);
-                                    }
-                                    Err(e) => {
-                                        error.set(Some(e));
-                                        loading.set(false);
-                                    }
-                                }
-                            }
-                        });
+                if let Route::GameDetail { id } = route() {
+                    GameDetailPage { key: "{id}", id: id }
+                }
+
+                if route() == Route::Collections {
+                    CollectionsPage {}
+                }
+
+                if let Route::Collection { id } = route() {
+                    CollectionPage { key: "{id}", id: id }
+                }
+
+                if route() == Route::Stats {
+                    StatsPage {}
+                }
+
+                if route() == Route::Import {
+                    ImportPage {}
+                }
+
+                if route() == Route::SignIn {
+                    AuthPage { sign_up: false }
+                }
+
+                if route() == Route::SignUp {
+                    AuthPage { sign_up: true }
+                }
+
+                if route() == Route::Settings {
+                    SettingsPage {
+                        // Results from the previous server no longer apply
+                        on_saved: move |_| load_first_page(String::new()),
                     }
                 }
+
+                // The home view stays mounted while a game is open so the
+                // search results and loaded pages survive going back
+                div {
+                    style: if route() != Route::Home { "display: none;" } else { "" },
+
+                    SearchBar {
+                        search_query: search_query,
+                        on_search: move |query: String| load_first_page(query),
+                    }
                 
-                if loading() {
-                    div {
-                        class: "loading-container",
-                        div { class: "loading-spinner" }
-                        div { class: "loading-text", "Loading games..." }
+                    if loading() {
+                        div {
+                            class: "loading-container",
+                            div { class: "loading-spinner" }
+                            div { class: "loading-text", "Loading games..." }
+                        }
                     }
-                }
                 
-                if let Some(err) = error() {
-                    div {
-                        class: "error-message",
-                        span { class: "error-icon", "⚠️" }
-                        span { "Error: {err}" }
+                    if let Some(err) = error() {
+                        div {
+                            class: "error-message",
+                            span { class: "error-icon", "⚠️" }
+                            span { "Error: {err}" }
+                        }
                     }
-                }
                 
-                if !loading() && error().is_none() {
-                    GameList { games: games }
+                    if !loading() && error().is_none() {
+                        GameList { games: games }
+
+                        if let Some(offset) = next_offset() {
+                            div {
+                                class: "load-more-container",
+                                button {
+                                    class: "search-button",
+                                    disabled: loading_more(),
+                                    onclick: move |_| {
+                                        spawn(async move {
+                                            loading_more.set(true);
+                                            match fetch_game_page(active_query(), offset).await {
+                                                Ok(page) => {
+                                                    games.write().extend(page.games);
+                                                    next_offset.set(page.next_offset);
+                                                }
+                                                Err(e) => error.set(Some(e)),
+                                            }
+                                            loading_more.set(false);
+                                        });
+                                    },
+                                    if loading_more() { "Loading..." } else { "Load more" }
+                                }
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -181,24 +288,27 @@ fn GameList(games: Signal<Vec<GameData>>) -> Element {
 
 #[component]
 fn GameCard(game: GameData) -> Element {
-    let release_date = if !game.first_release_date.is_empty() && game.first_release_date != "0" {
-        match game.first_release_date.parse::<i64>() {
-            Ok(timestamp) => {
-                if let Some(dt) = chrono::DateTime::<chrono::Utc>::from_timestamp(timestamp, 0) {
-                    Some(dt.format("%B %Y").to_string())
-                } else {
-                    None
-                }
-            }
-            Err(_) => None,
-        }
-    } else {
-        None
-    };
+    let release_date = game.first_release_date.map(|date| date.label());
+    let id = game.id;
     
     rsx! {
         div {
-            class: "game-card",
+            class: "game-card game-card-clickable",
+            onclick: move |_| open_game(id),
+
+            if let Some(cover) = &game.cover {
+                img {
+                    class: "game-cover",
+                    src: "{cover.cover_big}",
+                    alt: "Cover art for {game.name}",
+                    loading: "lazy",
+                }
+            } else {
+                div {
+                    class: "game-cover game-cover-placeholder",
+                    "🎮"
+                }
+            }
             
             h2 {
                 class: "game-title",
@@ -247,3 +357,451 @@ fn GameCard(game: GameData) -> Element {
         }
     }
 }
+
+#[component]
+fn GameDetailPage(id: u64) -> Element {
+    let mut route = use_context::<Signal<Route>>();
+    let detail = use_resource(move || async move { fetch_game_detail(id).await });
+
+    rsx! {
+        div {
+            class: "game-detail",
+
+            button {
+                class: "back-button",
+                onclick: move |_| route.set(Route::Home),
+                "← Back to games"
+            }
+
+            match &*detail.read() {
+                None => rsx! {
+                    div {
+                        class: "loading-container",
+                        div { class: "loading-spinner" }
+                        div { class: "loading-text", "Loading game..." }
+                    }
+                },
+                Some(Err(err)) => rsx! {
+                    div {
+                        class: "error-message",
+                        span { class: "error-icon", "⚠️" }
+                        span { "Error: {err}" }
+                    }
+                },
+                Some(Ok(detail)) => rsx! {
+                    GameDetailView { detail: detail.clone() }
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn GameDetailView(detail: GameDetail) -> Element {
+    let game = &detail.game;
+    let release_date = game.first_release_date.map(|date| date.label());
+    let developers = detail
+        .involved_companies
+        .iter()
+        .filter(|company| company.developer)
+        .map(|company| company.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let publishers = detail
+        .involved_companies
+        .iter()
+        .filter(|company| company.publisher)
+        .map(|company| company.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let tags: Vec<&String> = detail
+        .game_modes
+        .iter()
+        .chain(detail.themes.iter())
+        .chain(detail.franchises.iter())
+        .collect();
+
+    rsx! {
+        div {
+            class: "game-detail-header",
+
+            if let Some(cover) = &game.cover {
+                img {
+                    class: "game-detail-cover",
+                    src: "{cover.cover_big}",
+                    alt: "Cover art for {game.name}",
+                }
+            } else {
+                div {
+                    class: "game-detail-cover game-cover-placeholder",
+                    "🎮"
+                }
+            }
+
+            div {
+                class: "game-detail-info",
+
+                h2 { class: "game-detail-title", "{game.name}" }
+
+                div {
+                    class: "game-detail-meta",
+                    if let Some(date) = release_date {
+                        span { class: "badge badge-release", "📅 {date}" }
+                    }
+                    if let Some(rating) = detail.rating {
+                        span {
+                            class: "badge badge-rating",
+                            "⭐ {rating:.0}/100"
+                            if let Some(count) = detail.rating_count {
+                                " ({count} ratings)"
+                            }
+                        }
+                    }
+                }
+
+                LibraryButton { game: game.clone() }
+
+                if !developers.is_empty() {
+                    p { class: "game-detail-credit", strong { "Developer: " } "{developers}" }
+                }
+                if !publishers.is_empty() {
+                    p { class: "game-detail-credit", strong { "Publisher: " } "{publishers}" }
+                }
+
+                if !game.platforms.is_empty() {
+                    div {
+                        class: "platform-tags",
+                        for platform in game.platforms.iter() {
+                            span { class: "platform-tag", "{platform}" }
+                        }
+                    }
+                }
+
+                if !game.genres.is_empty() || !tags.is_empty() {
+                    div {
+                        class: "genre-tags",
+                        style: "margin-top: 0.75rem;",
+                        for genre in game.genres.iter().chain(tags.iter().copied()) {
+                            span { class: "genre-tag", "{genre}" }
+                        }
+                    }
+                }
+
+                if !detail.websites.is_empty() {
+                    div {
+                        class: "game-detail-links",
+                        for website in detail.websites.iter() {
+                            a {
+                                class: "game-detail-link",
+                                href: "{website.url}",
+                                target: "_blank",
+                                rel: "noopener noreferrer",
+                                "{website.category}"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(summary) = &detail.summary {
+            div {
+                class: "game-detail-section",
+                h3 { "Summary" }
+                p { "{summary}" }
+            }
+        }
+
+        if let Some(storyline) = &detail.storyline {
+            div {
+                class: "game-detail-section",
+                h3 { "Storyline" }
+                p { "{storyline}" }
+            }
+        }
+
+        PlaySessions { game_id: game.id }
+
+        GameReviews { game_id: game.id }
+
+        if !game.release_dates.is_empty() {
+            div {
+                class: "game-detail-section",
+                h3 { "Release dates" }
+                table {
+                    class: "release-dates",
+                    for release in game.release_dates.iter() {
+                        tr {
+                            td { "{release.platform}" }
+                            td { {release.region.map(|region| region.label()).unwrap_or("Unknown region")} }
+                            td { {release.date.map(|date| date.label()).unwrap_or_else(|| "TBA".to_string())} }
+                        }
+                    }
+                }
+            }
+        }
+
+        if !game.screenshots.is_empty() {
+            div {
+                class: "game-detail-section",
+                h3 { "Screenshots" }
+                div {
+                    class: "screenshot-grid",
+                    for screenshot in game.screenshots.iter() {
+                        img {
+                            class: "screenshot",
+                            src: "{screenshot.screenshot_med}",
+                            alt: "Screenshot of {game.name}",
+                            loading: "lazy",
+                        }
+                    }
+                }
+            }
+        }
+
+        if !detail.similar_games.is_empty() {
+            div {
+                class: "game-detail-section",
+                h3 { "Similar games" }
+                div {
+                    class: "similar-games",
+                    for similar in detail.similar_games.iter() {
+                        SimilarGameCard { game: similar.clone() }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Adds the game to, or removes it from, the signed-in user's library, and
+/// shows its status there
+#[component]
+fn LibraryButton(game: GameData) -> Element {
+    let account = use_context::<Signal<Option<UserResponse>>>();
+    let mut saving = use_signal(|| false);
+    let mut error = use_signal(|| Option::<String>::None);
+
+    let Some(user) = account() else {
+        return rsx! {
+            p { class: "library-hint", "Sign in to add this game to your library." }
+        };
+    };
+    let entry = user.games.iter().find(|entry| entry.game.id == game.id).cloned();
+    let in_library = entry.is_some();
+    let game_id = game.id;
+    let username = user.username.clone();
+
+    let toggle = move |_| {
+        let username = user.username.clone();
+        let game = game.clone();
+        spawn(async move {
+            saving.set(true);
+            error.set(None);
+            let result = if in_library {
+                api::remove_from_library(&username, game.id).await
+            } else {
+                api::add_to_library(&username, &game).await
+            };
+            match result {
+                Ok(user) => account::update_account(user),
+                Err(e) => error.set(Some(e)),
+            }
+            saving.set(false);
+        });
+    };
+
+    let change_status = move |evt: FormEvent| {
+        let Some(status) = evt.value().parse::<usize>().ok().and_then(|index| ShelfStatus::ALL.get(index).copied())
+        else {
+            return;
+        };
+        let username = username.clone();
+        spawn(async move {
+            saving.set(true);
+            error.set(None);
+            match api::set_game_status(&username, game_id, status).await {
+                Ok(user) => account::update_account(user),
+                Err(e) => error.set(Some(e)),
+            }
+            saving.set(false);
+        });
+    };
+
+    rsx! {
+        div {
+            class: "library-actions",
+            button {
+                class: if in_library { "search-button settings-secondary" } else { "search-button" },
+                disabled: saving(),
+                onclick: toggle,
+                if in_library { "✓ In your library (remove)" } else { "＋ Add to library" }
+            }
+            if let Some(entry) = entry {
+                select {
+                    class: "shelf-status",
+                    disabled: saving(),
+                    onchange: change_status,
+                    for (index, status) in ShelfStatus::ALL.iter().enumerate() {
+                        option {
+                            value: "{index}",
+                            selected: *status == entry.status,
+                            "{status.label()}"
+                        }
+                    }
+                }
+                span { class: "shelf-dates", "{shelf_dates(&entry)}" }
+            }
+            if in_library {
+                CollectionPicker { game_id: game_id }
+            }
+            if let Some(message) = error() {
+                span { class: "library-error", "⚠️ {message}" }
+            }
+        }
+    }
+}
+
+/// Describes when a shelved game was started and finished, e.g. "Started May 2, 2024"
+fn shelf_dates(entry: &ShelfEntry) -> String {
+    let format = |date: NaiveDate| date.format("%b %-d, %Y").to_string();
+    match (entry.started_on, entry.finished_on) {
+        (Some(started), Some(finished)) => format!("{} – {}", format(started), format(finished)),
+        (Some(started), None) => format!("Started {}", format(started)),
+        (None, Some(finished)) => format!("Finished {}", format(finished)),
+        (None, None) => String::new(),
+    }
+}
+
+#[component]
+fn SimilarGameCard(game: SimilarGame) -> Element {
+    let id = game.id;
+
+    rsx! {
+        div {
+            class: "similar-game",
+            onclick: move |_| open_game(id),
+
+            if let Some(cover) = &game.cover {
+                img {
+                    class: "similar-game-cover",
+                    src: "{cover.cover_small}",
+                    alt: "Cover art for {game.name}",
+                    loading: "lazy",
+                }
+            } else {
+                div {
+                    class: "similar-game-cover game-cover-placeholder",
+                    "🎮"
+                }
+            }
+            span { class: "similar-game-name", "{game.name}" }
+        }
+    }
+}
+
+#[component]
+fn SettingsPage(on_saved: EventHandler<()>) -> Element {
+    let mut route = use_context::<Signal<Route>>();
+    let mut url_input = use_signal(api::api_base_url);
+    let mut testing = use_signal(|| false);
+    // Outcome of the last test or save: Ok(message) or Err(message)
+    let mut status = use_signal(|| Option::<Result<String, String>>::None);
+
+    let test = move |_| {
+        let url = match api::normalize_base_url(&url_input()) {
+            Ok(url) => url,
+            Err(e) => return status.set(Some(Err(e))),
+        };
+        spawn(async move {
+            testing.set(true);
+            status.set(None);
+            let result = api::test_connection(&url)
+                .await
+                .map(|_| format!("Connected to {}", url));
+            status.set(Some(result));
+            testing.set(false);
+        });
+    };
+
+    let mut save = move |api_base_url: Option<String>| {
+        let settings = Settings {
+            api_base_url: api_base_url.clone(),
+            ..settings::load()
+        };
+        match settings::save(&settings) {
+            Ok(()) => {
+                api::set_api_base_url(api_base_url);
+                url_input.set(api::api_base_url());
+                status.set(Some(Ok("Settings saved".to_string())));
+                on_saved.call(());
+            }
+            Err(e) => status.set(Some(Err(e))),
+        }
+    };
+
+    rsx! {
+        div {
+            class: "settings-page",
+
+            button {
+                class: "back-button",
+                onclick: move |_| route.set(Route::Home),
+                "← Back to games"
+            }
+
+            h2 { class: "game-detail-title", "Settings" }
+
+            label {
+                class: "settings-label",
+                r#for: "api-base-url",
+                "Server URL"
+            }
+            input {
+                id: "api-base-url",
+                class: "search-input settings-input",
+                r#type: "url",
+                placeholder: api::DEFAULT_API_BASE_URL,
+                value: "{url_input()}",
+                oninput: move |evt| url_input.set(evt.value()),
+            }
+            p {
+                class: "settings-hint",
+                "The PlayShelf server this app talks to. Default: {api::DEFAULT_API_BASE_URL}"
+            }
+
+            div {
+                class: "settings-actions",
+                button {
+                    class: "search-button",
+                    disabled: testing(),
+                    onclick: test,
+                    if testing() { "Testing..." } else { "Test connection" }
+                }
+                button {
+                    class: "search-button",
+                    onclick: move |_| match api::normalize_base_url(&url_input()) {
+                        Ok(url) => save(Some(url)),
+                        Err(e) => status.set(Some(Err(e))),
+                    },
+                    "Save"
+                }
+                button {
+                    class: "search-button settings-secondary",
+                    onclick: move |_| save(None),
+                    "Reset to default"
+                }
+            }
+
+            match status() {
+                Some(Ok(message)) => rsx! {
+                    div { class: "settings-status settings-status-ok", "✅ {message}" }
+                },
+                Some(Err(message)) => rsx! {
+                    div { class: "settings-status settings-status-error", "⚠️ {message}" }
+                },
+                None => rsx! {},
+            }
+        }
+    }
+}
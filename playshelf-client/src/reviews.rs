@@ -0,0 +1,296 @@
+//! Ratings and reviews on the game detail page
+//!
+//! Reviews are written in Markdown and rendered to HTML here. One user's
+//! review is shown on other users' pages, so raw HTML is shown as text, links
+//! are kept only for http, https and mailto URLs, and images become links to
+//! the image rather than being loaded.
+
+use dioxus::prelude::*;
+use pulldown_cmark::{html, Event, Parser, Tag, TagEnd};
+
+use crate::account;
+use crate::api::{self, GameReview, GameReviewsResponse, Rating, ReviewRequest, ShelfEntry, UserResponse};
+
+/// URL prefixes a link in a review may start with
+const SAFE_LINK_PREFIXES: [&str; 3] = ["http://", "https://", "mailto:"];
+
+fn is_safe_link(url: &str) -> bool {
+    let url = url.to_ascii_lowercase();
+    SAFE_LINK_PREFIXES.iter().any(|prefix| url.starts_with(prefix))
+}
+
+/// Renders Markdown to HTML, escaping any HTML written in the source
+///
+/// Links to other kinds of URLs, such as `javascript:`, keep only their text.
+/// Images are rendered as links, or as their alt text inside another link.
+fn markdown_to_html(markdown: &str) -> String {
+    // Whether each open link or image was kept, so its end tag can match
+    let mut kept: Vec<bool> = Vec::new();
+    let events = Parser::new(markdown).filter_map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Some(Event::Text(raw)),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        })
+        | Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => {
+            let keep = is_safe_link(&dest_url) && !kept.contains(&true);
+            kept.push(keep);
+            keep.then_some(Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }))
+        }
+        Event::End(TagEnd::Link | TagEnd::Image) => kept.pop().unwrap_or(false).then_some(Event::End(TagEnd::Link)),
+        event => Some(event),
+    });
+    let mut output = String::new();
+    html::push_html(&mut output, events);
+    output
+}
+
+/// Every user's reviews of a game, with a form for the signed-in user's own
+#[component]
+pub fn GameReviews(game_id: u64) -> Element {
+    let account = use_context::<Signal<Option<UserResponse>>>();
+    // Pages fetched with "Load more", dropped whenever the first page is reloaded
+    let mut more = use_signal(Vec::<GameReview>::new);
+    let mut next_offset = use_signal(|| Option::<u32>::None);
+    let mut loading_more = use_signal(|| false);
+    let mut error = use_signal(|| Option::<String>::None);
+    let mut reviews = use_resource(move || async move {
+        let page = api::fetch_game_reviews(game_id, 0).await;
+        more.write().clear();
+        next_offset.set(page.as_ref().ok().and_then(|page| page.next_offset));
+        page
+    });
+
+    let entry = account().and_then(|user| user.games.into_iter().find(|entry| entry.game.id == game_id));
+
+    rsx! {
+        div {
+            class: "game-detail-section",
+            h3 { "Reviews" }
+
+            if let Some(entry) = entry {
+                ReviewForm {
+                    key: "{game_id}",
+                    entry,
+                    on_saved: move |_| reviews.restart(),
+                }
+            }
+
+            match &*reviews.read() {
+                None => rsx! {
+                    div { class: "loading-text", "Loading reviews..." }
+                },
+                Some(Err(err)) => rsx! {
+                    div { class: "library-error", "⚠️ {err}" }
+                },
+                Some(Ok(reviews)) => {
+                    let mut reviews = reviews.clone();
+                    reviews.reviews.extend(more());
+                    rsx! {
+                        ReviewList { reviews }
+                    }
+                }
+            }
+
+            if let Some(err) = error() {
+                div { class: "library-error", "⚠️ {err}" }
+            }
+
+            if let Some(offset) = next_offset() {
+                div {
+                    class: "load-more-container",
+                    button {
+                        class: "search-button",
+                        disabled: loading_more(),
+                        onclick: move |_| {
+                            spawn(async move {
+                                loading_more.set(true);
+                                match api::fetch_game_reviews(game_id, offset).await {
+                                    Ok(page) => {
+                                        more.write().extend(page.reviews);
+                                        next_offset.set(page.next_offset);
+                                        error.set(None);
+                                    }
+                                    Err(e) => error.set(Some(e)),
+                                }
+                                loading_more.set(false);
+                            });
+                        },
+                        if loading_more() { "Loading..." } else { "Load more reviews" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn ReviewList(reviews: GameReviewsResponse) -> Element {
+    if reviews.reviews.is_empty() {
+        return rsx! {
+            p { class: "library-hint", "No reviews yet." }
+        };
+    }
+
+    rsx! {
+        if let Some(average) = reviews.average_rating {
+            p {
+                class: "review-average",
+                "⭐ {average:.1} / 5 from {reviews.rating_count} "
+                if reviews.rating_count == 1 { "rating" } else { "ratings" }
+            }
+        }
+        for review in reviews.reviews.iter() {
+            div {
+                class: "review",
+                key: "{review.username}",
+                div {
+                    class: "review-header",
+                    strong { "{review.name}" }
+                    span { class: "review-username", "@{review.username} · {review.status.label()}" }
+                    if let Some(rating) = review.rating {
+                        span { class: "review-stars", title: "{rating} stars", "{rating.star_label()}" }
+                    }
+                    if let Some(date) = review.reviewed_on {
+                        span { class: "review-date", "{date}" }
+                    }
+                }
+                if let Some(text) = &review.review {
+                    div { class: "review-body", dangerous_inner_html: markdown_to_html(text) }
+                }
+            }
+        }
+    }
+}
+
+/// Edits the signed-in user's rating and review of a game on their shelf
+#[component]
+fn ReviewForm(entry: ShelfEntry, on_saved: EventHandler<()>) -> Element {
+    let account = use_context::<Signal<Option<UserResponse>>>();
+    // Half stars, 0 for no rating
+    let mut half_stars = use_signal(|| entry.rating.map(|rating| rating.half_stars()).unwrap_or(0));
+    let mut text = use_signal(|| entry.review.clone().unwrap_or_default());
+    let mut saving = use_signal(|| false);
+    let mut error = use_signal(|| Option::<String>::None);
+    let game_id = entry.game.id;
+
+    let save = move |evt: FormEvent| {
+        evt.prevent_default();
+        let Some(username) = account().map(|user| user.username) else {
+            return;
+        };
+        let request = ReviewRequest {
+            rating: Rating::from_half_stars(half_stars()),
+            review: Some(text()).filter(|review| !review.trim().is_empty()),
+        };
+        spawn(async move {
+            saving.set(true);
+            error.set(None);
+            match api::save_review(&username, game_id, &request).await {
+                Ok(user) => {
+                    account::update_account(user);
+                    on_saved.call(());
+                }
+                Err(e) => error.set(Some(e)),
+            }
+            saving.set(false);
+        });
+    };
+
+    rsx! {
+        form {
+            class: "review-form",
+            onsubmit: save,
+
+            label { class: "settings-label", r#for: "review-rating", "Your rating" }
+            select {
+                id: "review-rating",
+                class: "shelf-status",
+                onchange: move |evt| half_stars.set(evt.value().parse().unwrap_or(0)),
+                option { value: "0", selected: half_stars() == 0, "No rating" }
+                for value in (Rating::MIN_HALF_STARS..=Rating::MAX_HALF_STARS).rev() {
+                    if let Some(rating) = Rating::from_half_stars(value) {
+                        option {
+                            value: "{value}",
+                            selected: half_stars() == value,
+                            "{rating.star_label()} ({rating})"
+                        }
+                    }
+                }
+            }
+
+            label { class: "settings-label", r#for: "review-text", "Your review" }
+            textarea {
+                id: "review-text",
+                class: "search-input review-input",
+                rows: "5",
+                placeholder: "Markdown is supported",
+                value: "{text()}",
+                oninput: move |evt| text.set(evt.value()),
+            }
+
+            div {
+                class: "settings-actions",
+                button {
+                    class: "search-button",
+                    r#type: "submit",
+                    disabled: saving(),
+                    if saving() { "Saving..." } else { "Save review" }
+                }
+                if let Some(message) = error() {
+                    span { class: "library-error", "⚠️ {message}" }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_to_html_escapes_html() {
+        let html = markdown_to_html("**Great** <script>alert(1)</script>");
+        assert!(html.contains("<strong>Great</strong>"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn test_markdown_to_html_drops_unsafe_links() {
+        let html = markdown_to_html("[click me](javascript:alert(1)) and [docs](https://example.com)");
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("click me"));
+        assert!(html.contains(r#"<a href="https://example.com">docs</a>"#));
+
+        let html = markdown_to_html("[mail](MAILTO:link@example.com) [x](JavaScript:alert(1)) [y](data:text/html,hi)");
+        assert!(html.contains(r#"href="MAILTO:link@example.com""#));
+        assert_eq!(html.matches("<a ").count(), 1);
+    }
+
+    #[test]
+    fn test_markdown_to_html_renders_images_as_links() {
+        let html = markdown_to_html("![boss fight](https://example.com/boss.png)");
+        assert!(!html.contains("<img"));
+        assert!(html.contains(r#"<a href="https://example.com/boss.png">boss fight</a>"#));
+
+        let html =
+            markdown_to_html("![x](javascript:alert(1)) [![cover](https://example.com/c.png)](https://example.com)");
+        assert!(!html.contains("javascript:"));
+        assert_eq!(html.matches("<a ").count(), 1);
+        assert!(html.contains(r#"<a href="https://example.com">cover</a>"#));
+    }
+}
@@ -0,0 +1,352 @@
+//! Play session timer, log form and time played on the game detail page
+//!
+//! Sessions come with the signed-in user's shelf entries, so totals are
+//! worked out here from the account context rather than fetched. A running
+//! timer is saved in the settings so it keeps counting while the app is closed.
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use dioxus::prelude::*;
+use playshelf_types::{format_minutes, ImportedPlaytime};
+use playshelf_types::validation::{
+    collect_errors, validate_session_length, validate_session_note, validate_session_platform,
+};
+
+use crate::account;
+use crate::api::{self, LogSessionRequest, PlaySession, PlaytimeTotals, ShelfEntry, UserResponse};
+use crate::settings::{self, RunningSession, Settings};
+
+/// Saves the running timer, or clears it if `None`
+fn save_timer(running_session: Option<RunningSession>) -> Result<(), String> {
+    settings::save(&Settings {
+        running_session,
+        ..settings::load()
+    })
+}
+
+/// Local date and time of day in the form fields' formats, e.g. "2024-05-01" and "20:15"
+fn local_fields(time: DateTime<Utc>) -> (String, String) {
+    let local = time.with_timezone(&Local);
+    (local.format("%Y-%m-%d").to_string(), local.format("%H:%M").to_string())
+}
+
+/// Formats `time` in the local time zone, e.g. "May 1, 2024 20:15"
+fn local_label(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local).format("%b %-d, %Y %H:%M").to_string()
+}
+
+/// Reads a local date and time of day from the form fields
+fn parse_local(date: &str, time: &str) -> Option<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let time = NaiveTime::parse_from_str(time, "%H:%M").ok()?;
+    let local = Local.from_local_datetime(&date.and_time(time)).earliest()?;
+    Some(local.with_timezone(&Utc))
+}
+
+/// Checks a session the way the server will, joining the messages of every rejected field
+fn check(request: &LogSessionRequest) -> Result<(), String> {
+    let errors = collect_errors([
+        validate_session_length(request),
+        request.platform.as_deref().map_or(Ok(()), validate_session_platform),
+        request.note.as_deref().map_or(Ok(()), validate_session_note),
+    ]);
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let messages: Vec<String> = errors.into_iter().map(|error| error.message).collect();
+    Err(messages.join("\n"))
+}
+
+/// Time played on a game on the signed-in user's shelf, with a timer, a form
+/// to log sessions afterwards and the sessions logged so far
+#[component]
+pub fn PlaySessions(game_id: u64) -> Element {
+    let account = use_context::<Signal<Option<UserResponse>>>();
+    let Some(entry) = account().and_then(|user| user.games.into_iter().find(|entry| entry.game.id == game_id))
+    else {
+        return rsx! {};
+    };
+    let totals = entry.playtime_totals();
+
+    rsx! {
+        div {
+            class: "game-detail-section",
+            h3 { "Play sessions" }
+
+            if totals.minutes > 0 {
+                PlaytimeSummary { totals, imported: entry.imported_playtime }
+            }
+            SessionForm { key: "{game_id}", entry: entry.clone() }
+            SessionList { game_id, sessions: entry.sessions.iter().rev().cloned().collect::<Vec<_>>() }
+        }
+    }
+}
+
+#[component]
+fn PlaytimeSummary(totals: PlaytimeTotals, imported: Option<ImportedPlaytime>) -> Element {
+    rsx! {
+        p {
+            class: "playtime-total",
+            "⏱ {format_minutes(totals.minutes)} over {totals.sessions} "
+            if totals.sessions == 1 { "session" } else { "sessions" }
+            if let Some(imported) = imported {
+                ", including {format_minutes(imported.minutes)} from {imported.source.label()}"
+            }
+        }
+        div {
+            class: "platform-tags",
+            for platform in totals.by_platform.iter() {
+                span {
+                    class: "platform-tag",
+                    {platform.platform.as_deref().unwrap_or("Unknown platform")}
+                    " · {format_minutes(platform.minutes)}"
+                }
+            }
+        }
+        table {
+            class: "release-dates playtime-months",
+            for month in totals.by_month.iter() {
+                tr {
+                    key: "{month.month}",
+                    td { "{month.month}" }
+                    td { "{format_minutes(month.minutes)}" }
+                }
+            }
+        }
+    }
+}
+
+/// Starts and stops the timer, and logs sessions by start and duration
+#[component]
+fn SessionForm(entry: ShelfEntry) -> Element {
+    let account = use_context::<Signal<Option<UserResponse>>>();
+    let game_id = entry.game.id;
+    let mut running = use_signal(|| settings::load().running_session);
+    let (today, now) = local_fields(Utc::now());
+    let mut date = use_signal(|| today);
+    let mut time = use_signal(|| now);
+    let mut minutes = use_signal(|| "60".to_string());
+    let mut platform = use_signal(|| entry.game.platforms.first().cloned().unwrap_or_default());
+    let mut note = use_signal(String::new);
+    let mut saving = use_signal(|| false);
+    let mut error = use_signal(|| Option::<String>::None);
+
+    let mut send = move |request: LogSessionRequest, stops_timer: bool| {
+        let Some(username) = account().map(|user| user.username) else {
+            return;
+        };
+        if let Err(message) = check(&request) {
+            error.set(Some(message));
+            return;
+        }
+        spawn(async move {
+            saving.set(true);
+            error.set(None);
+            match api::log_session(&username, game_id, &request).await {
+                Ok(user) => {
+                    account::update_account(user);
+                    note.set(String::new());
+                    if stops_timer {
+                        running.set(None);
+                        if let Err(e) = save_timer(None) {
+                            error.set(Some(e));
+                        }
+                    }
+                }
+                Err(e) => error.set(Some(e)),
+            }
+            saving.set(false);
+        });
+    };
+    let request = move |started_at: DateTime<Utc>, ended_at: Option<DateTime<Utc>>, duration_minutes| {
+        LogSessionRequest {
+            started_at,
+            ended_at,
+            duration_minutes,
+            platform: Some(platform()).filter(|platform| !platform.trim().is_empty()),
+            note: Some(note()).filter(|note| !note.trim().is_empty()),
+        }
+    };
+
+    let game_name = entry.game.name.clone();
+    let start = move |_| {
+        let timer = RunningSession {
+            game_id,
+            game_name: game_name.clone(),
+            started_at: Utc::now(),
+        };
+        match save_timer(Some(timer.clone())) {
+            Ok(()) => running.set(Some(timer)),
+            Err(e) => error.set(Some(e)),
+        }
+    };
+    let stop = move |_: MouseEvent| {
+        if let Some(timer) = running() {
+            send(request(timer.started_at, Some(Utc::now()), None), true);
+        }
+    };
+    let discard = move |_| {
+        running.set(None);
+        if let Err(e) = save_timer(None) {
+            error.set(Some(e));
+        }
+    };
+    let log = move |evt: FormEvent| {
+        evt.prevent_default();
+        let Some(started_at) = parse_local(&date(), &time()) else {
+            error.set(Some("Enter the date and time the session started".to_string()));
+            return;
+        };
+        send(request(started_at, None, minutes().trim().parse().ok()), false);
+    };
+
+    rsx! {
+        div {
+            class: "session-timer",
+            match running() {
+                Some(timer) if timer.game_id == game_id => rsx! {
+                    span {
+                        class: "session-running",
+                        "▶ Playing since {local_label(timer.started_at)}"
+                    }
+                    button {
+                        class: "search-button",
+                        disabled: saving(),
+                        onclick: stop,
+                        "■ Stop and log"
+                    }
+                    button {
+                        class: "search-button settings-secondary",
+                        disabled: saving(),
+                        onclick: discard,
+                        "Discard"
+                    }
+                },
+                Some(timer) => rsx! {
+                    span { class: "library-hint", "A timer is running for {timer.game_name}." }
+                },
+                None => rsx! {
+                    button { class: "search-button", onclick: start, "▶ Start playing" }
+                },
+            }
+        }
+
+        form {
+            class: "session-form",
+            onsubmit: log,
+
+            div {
+                class: "session-fields",
+                input {
+                    class: "search-input",
+                    r#type: "date",
+                    "aria-label": "Date",
+                    value: "{date()}",
+                    oninput: move |evt| date.set(evt.value()),
+                }
+                input {
+                    class: "search-input",
+                    r#type: "time",
+                    "aria-label": "Start time",
+                    value: "{time()}",
+                    oninput: move |evt| time.set(evt.value()),
+                }
+                input {
+                    class: "search-input session-minutes",
+                    r#type: "number",
+                    min: "1",
+                    "aria-label": "Minutes played",
+                    value: "{minutes()}",
+                    oninput: move |evt| minutes.set(evt.value()),
+                }
+                span { class: "library-hint", "minutes" }
+            }
+            div {
+                class: "session-fields",
+                select {
+                    class: "shelf-status",
+                    "aria-label": "Platform",
+                    onchange: move |evt| platform.set(evt.value()),
+                    option { value: "", selected: platform().is_empty(), "No platform" }
+                    for name in entry.game.platforms.iter() {
+                        option { value: "{name}", selected: platform() == *name, "{name}" }
+                    }
+                }
+                input {
+                    class: "search-input session-note",
+                    r#type: "text",
+                    placeholder: "Note, e.g. Beat the Water Temple",
+                    value: "{note()}",
+                    oninput: move |evt| note.set(evt.value()),
+                }
+                button {
+                    class: "search-button",
+                    r#type: "submit",
+                    disabled: saving(),
+                    if saving() { "Saving..." } else { "Log session" }
+                }
+            }
+            if let Some(message) = error() {
+                for line in message.lines() {
+                    span { class: "library-error", "⚠️ {line}" }
+                }
+            }
+        }
+    }
+}
+
+/// The sessions of a game, newest first, each with a delete button
+#[component]
+fn SessionList(game_id: u64, sessions: Vec<PlaySession>) -> Element {
+    let account = use_context::<Signal<Option<UserResponse>>>();
+    let mut busy = use_signal(|| false);
+    let mut error = use_signal(|| Option::<String>::None);
+
+    rsx! {
+        if let Some(message) = error() {
+            div { class: "settings-status settings-status-error", "⚠️ {message}" }
+        }
+        for session in sessions.iter() {
+            div {
+                key: "{session.id}",
+                class: "collection-row",
+                span {
+                    class: "session-when",
+                    "{local_label(session.started_at)}"
+                }
+                span { class: "collection-count", "{format_minutes(session.duration_minutes.into())}" }
+                if let Some(platform) = &session.platform {
+                    span { class: "platform-tag", "{platform}" }
+                }
+                if let Some(note) = &session.note {
+                    span { class: "session-note-text", "{note}" }
+                }
+                div {
+                    class: "collection-controls",
+                    button {
+                        class: "icon-button",
+                        title: "Delete session",
+                        disabled: busy(),
+                        onclick: {
+                            let id = session.id;
+                            move |_| {
+                                let Some(username) = account().map(|user| user.username) else {
+                                    return;
+                                };
+                                spawn(async move {
+                                    busy.set(true);
+                                    error.set(None);
+                                    match api::delete_session(&username, game_id, id).await {
+                                        Ok(user) => account::update_account(user),
+                                        Err(e) => error.set(Some(e)),
+                                    }
+                                    busy.set(false);
+                                });
+                            }
+                        },
+                        "🗑️"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Settings persisted between runs of the client
+//!
+//! The web build keeps them in the browser's local storage; desktop and
+//! mobile builds keep them in `settings.json` under the platform config
+//! directory (e.g. `~/.config/playshelf` on Linux).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Settings {
+    /// Base URL of the PlayShelf server chosen in the settings screen
+    #[serde(default)]
+    pub api_base_url: Option<String>,
+    /// Session token of the signed-in user
+    #[serde(default)]
+    pub session_token: Option<String>,
+    /// Play session timer started and not yet stopped
+    #[serde(default)]
+    pub running_session: Option<RunningSession>,
+}
+
+/// A play session timer, kept here so it survives closing the app
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RunningSession {
+    pub game_id: u64,
+    pub game_name: String,
+    pub started_at: DateTime<Utc>,
+}
+
+#[cfg(target_arch = "wasm32")]
+const STORAGE_KEY: &str = "playshelf.settings";
+
+/// Loads the saved settings, falling back to the defaults if there are none
+pub fn load() -> Settings {
+    read()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Persists `settings`
+pub fn save(settings: &Settings) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    write(&content)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read() -> Option<String> {
+    local_storage()?.get_item(STORAGE_KEY).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(content: &str) -> Result<(), String> {
+    local_storage()
+        .ok_or_else(|| "Local storage is not available".to_string())?
+        .set_item(STORAGE_KEY, content)
+        .map_err(|_| "Failed to write to local storage".to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn settings_path() -> Option<std::path::PathBuf> {
+    Some(dirs::config_dir()?.join("playshelf").join("settings.json"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read() -> Option<String> {
+    std::fs::read_to_string(settings_path()?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(content: &str) -> Result<(), String> {
+    let path = settings_path().ok_or_else(|| "No config directory on this platform".to_string())?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    }
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
@@ -0,0 +1,258 @@
+//! Dashboard charting the signed-in user's library statistics
+//!
+//! Charts are plain bars sized with CSS, so the dashboard needs no charting
+//! library and renders the same on every platform.
+
+use dioxus::prelude::*;
+use playshelf_types::format_minutes;
+
+use crate::api::{self, ExportFormat, LibraryStats, UserResponse};
+use crate::Route;
+
+/// Most platforms and genres charted; the rest are summed into "Other"
+const MAX_BARS: usize = 10;
+
+/// One bar of a chart, with the text shown next to it
+#[derive(Debug, Clone, PartialEq)]
+struct Bar {
+    label: String,
+    value: u64,
+    caption: String,
+}
+
+impl Bar {
+    fn count(label: impl Into<String>, count: usize) -> Self {
+        Self {
+            label: label.into(),
+            value: count as u64,
+            caption: count.to_string(),
+        }
+    }
+}
+
+/// The first `MAX_BARS` counts as bars, with the rest summed into one
+fn top_bars(counts: impl Iterator<Item = (String, usize)>) -> Vec<Bar> {
+    let mut bars = Vec::new();
+    let mut other = 0;
+    for (label, count) in counts {
+        if bars.len() < MAX_BARS {
+            bars.push(Bar::count(label, count));
+        } else {
+            other += count;
+        }
+    }
+    if other > 0 {
+        bars.push(Bar::count("Other", other));
+    }
+    bars
+}
+
+#[component]
+pub fn StatsPage() -> Element {
+    let account = use_context::<Signal<Option<UserResponse>>>();
+    let mut route = use_context::<Signal<Route>>();
+    let stats = use_resource(move || async move {
+        match account() {
+            Some(user) => api::fetch_stats(&user.username).await.map(Some),
+            None => Ok(None),
+        }
+    });
+
+    rsx! {
+        div {
+            class: "settings-page",
+
+            button {
+                class: "back-button",
+                onclick: move |_| route.set(Route::Home),
+                "← Back to games"
+            }
+
+            h2 { class: "game-detail-title", "Library stats" }
+
+            if let Some(user) = account() {
+                ExportLinks { username: user.username }
+            }
+
+            match &*stats.read() {
+                None => rsx! {
+                    div { class: "loading-text", "Loading stats..." }
+                },
+                Some(Err(err)) => rsx! {
+                    div { class: "library-error", "⚠️ {err}" }
+                },
+                Some(Ok(None)) => rsx! {
+                    p { class: "library-hint", "Sign in to see statistics about your library." }
+                },
+                Some(Ok(Some(stats))) if stats.total_games == 0 => rsx! {
+                    p { class: "library-hint", "Add games to your library to see statistics about it." }
+                },
+                Some(Ok(Some(stats))) => rsx! {
+                    StatsDashboard { stats: stats.clone() }
+                },
+            }
+        }
+    }
+}
+
+/// Downloads the user's library as CSV or JSON, next to the way back in
+#[component]
+fn ExportLinks(username: String) -> Element {
+    let mut route = use_context::<Signal<Route>>();
+
+    rsx! {
+        div {
+            class: "export-links",
+            span { class: "library-hint", "Export your library:" }
+            for (format, label) in [(ExportFormat::Csv, "⬇ CSV"), (ExportFormat::Json, "⬇ JSON")] {
+                a {
+                    key: "{format.extension()}",
+                    class: "search-button settings-secondary",
+                    href: api::export_url(&username, format),
+                    download: "",
+                    target: "_blank",
+                    rel: "noopener noreferrer",
+                    "{label}"
+                }
+            }
+            button {
+                class: "search-button settings-secondary",
+                onclick: move |_| route.set(Route::Import),
+                "⬆ Import games"
+            }
+        }
+    }
+}
+
+#[component]
+fn StatsDashboard(stats: LibraryStats) -> Element {
+    let completion = stats.completion_rate.map(|rate| (rate * 100.0).round() as u32);
+    let by_status: Vec<Bar> =
+        stats.by_status.iter().map(|status| Bar::count(status.status.label(), status.count)).collect();
+    let by_platform = top_bars(stats.by_platform.iter().map(|platform| (platform.label.clone(), platform.count)));
+    let by_genre = top_bars(stats.by_genre.iter().map(|genre| (genre.label.clone(), genre.count)));
+    let mut by_release_year: Vec<Bar> =
+        stats.by_release_year.iter().map(|year| Bar::count(year.year.to_string(), year.count)).collect();
+    if stats.unknown_release_year > 0 {
+        by_release_year.push(Bar::count("Unknown", stats.unknown_release_year));
+    }
+    let finished_per_year: Vec<Bar> = stats
+        .yearly_trends
+        .iter()
+        .map(|trend| Bar::count(trend.year.to_string(), trend.completed))
+        .collect();
+    let played_per_year: Vec<Bar> = stats
+        .yearly_trends
+        .iter()
+        .filter(|trend| trend.minutes_played > 0)
+        .map(|trend| Bar {
+            label: trend.year.to_string(),
+            value: trend.minutes_played,
+            caption: format_minutes(trend.minutes_played),
+        })
+        .collect();
+
+    rsx! {
+        div {
+            class: "stats-summary",
+            div {
+                class: "stats-tile",
+                span { class: "stats-number", "{stats.total_games}" }
+                span { class: "stats-caption", "games on the shelf" }
+            }
+            div {
+                class: "stats-tile",
+                span { class: "stats-number", "{stats.backlog_size}" }
+                span { class: "stats-caption", "in the backlog" }
+            }
+            div {
+                class: "stats-tile",
+                span {
+                    class: "stats-number",
+                    {completion.map(|percent| format!("{}%", percent)).unwrap_or_else(|| "–".to_string())}
+                }
+                span { class: "stats-caption", "of owned games completed" }
+                div {
+                    class: "stats-progress",
+                    div { class: "stats-progress-fill", style: "width: {completion.unwrap_or(0)}%;" }
+                }
+            }
+        }
+
+        BarChart { title: "By status", bars: by_status }
+        BarChart { title: "By platform", bars: by_platform }
+        BarChart { title: "By genre", bars: by_genre }
+        BarChart { title: "By release year", bars: by_release_year }
+
+        div {
+            class: "game-detail-section",
+            h3 { "Year by year" }
+            table {
+                class: "release-dates stats-trends",
+                tr {
+                    th { "Year" }
+                    th { "Added" }
+                    th { "Started" }
+                    th { "Completed" }
+                    th { "Abandoned" }
+                    th { "Played" }
+                }
+                for trend in stats.yearly_trends.iter() {
+                    tr {
+                        key: "{trend.year}",
+                        td { "{trend.year}" }
+                        td { "{trend.added}" }
+                        td { "{trend.started}" }
+                        td { "{trend.completed}" }
+                        td { "{trend.abandoned}" }
+                        td { "{format_minutes(trend.minutes_played)}" }
+                    }
+                }
+            }
+        }
+        BarChart { title: "Completed per year", bars: finished_per_year }
+        BarChart { title: "Time played per year", bars: played_per_year }
+        if stats.unattributed_minutes_played > 0 {
+            p {
+                class: "library-hint",
+                "Plus {format_minutes(stats.unattributed_minutes_played)} of imported playtime, which has no dates"
+            }
+        }
+    }
+}
+
+/// CSS width of a bar of `value` in a chart whose largest bar is `max`
+fn bar_width(value: u64, max: u64) -> String {
+    format!("{:.1}%", value as f64 * 100.0 / max as f64)
+}
+
+/// A titled horizontal bar chart, with bars sized relative to the largest
+#[component]
+fn BarChart(title: String, bars: Vec<Bar>) -> Element {
+    let max = bars.iter().map(|bar| bar.value).max().unwrap_or(0).max(1);
+    if bars.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div {
+            class: "game-detail-section",
+            h3 { "{title}" }
+            div {
+                class: "bar-chart",
+                for bar in bars.iter() {
+                    div {
+                        key: "{bar.label}",
+                        class: "bar-row",
+                        span { class: "bar-label", title: "{bar.label}", "{bar.label}" }
+                        div {
+                            class: "bar-track",
+                            div { class: "bar-fill", style: "width: {bar_width(bar.value, max)}" }
+                        }
+                        span { class: "bar-value", "{bar.caption}" }
+                    }
+                }
+            }
+        }
+    }
+}
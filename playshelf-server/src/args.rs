@@ -1,10 +1,178 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use playshelf_server::config::{Config, ConfigError};
+use std::net::SocketAddr;
+use std::path::PathBuf;
 
-/// Simple program to greet a person
+/// Where the Twitch credentials and the session signing key are read from
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SecretSource {
+    /// Environment variables (`TWITCH_CLIENT_ID`, `TWITCH_CLIENT_SECRET`)
+    Env,
+    /// A local TOML or .env file given by `--secrets-file`
+    File,
+    /// AWS SSM Parameter Store
+    Ssm,
+}
+
+/// Where users and their libraries are stored
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StoreKind {
+    /// The DynamoDB tables named by `tables` in the config
+    Dynamodb,
+    /// A local SQLite database given by `--sqlite-path`
+    Sqlite,
+    /// In memory, lost when the server stops
+    Memory,
+}
+
+/// Command line flags of the PlayShelf API server
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Args {
     /// Run the server in development mode
     #[arg(long, default_value_t = false)]
     pub dev: bool,
-}
\ No newline at end of file
+
+    /// Where to read the Twitch credentials from
+    #[arg(long, value_enum, default_value_t = SecretSource::Ssm)]
+    pub secrets: SecretSource,
+
+    /// Secrets file used with `--secrets file` (TOML if it ends in .toml, .env format otherwise)
+    #[arg(long, default_value = ".env")]
+    pub secrets_file: PathBuf,
+
+    /// SSM parameter path prefix used with `--secrets ssm`
+    #[arg(long, default_value = "/playshelf/prod")]
+    pub ssm_prefix: String,
+
+    /// Where to store users and their libraries
+    #[arg(long, value_enum, default_value_t = StoreKind::Dynamodb)]
+    pub store: StoreKind,
+
+    /// SQLite database file used with `--store sqlite`
+    #[arg(long, default_value = "playshelf.db")]
+    pub sqlite_path: PathBuf,
+
+    /// TOML config file; settings given as flags or environment variables take precedence
+    #[arg(long, env = "PLAYSHELF_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Address to listen on, e.g. 0.0.0.0:8081
+    #[arg(long, env = "PLAYSHELF_BIND_ADDRESS")]
+    pub bind_address: Option<SocketAddr>,
+
+    /// Comma separated origins allowed to call the API, or * for any origin
+    #[arg(long, env = "PLAYSHELF_CORS_ORIGINS", value_delimiter = ',')]
+    pub cors_origins: Option<Vec<String>>,
+
+    /// AWS region of the DynamoDB tables and SSM parameters
+    #[arg(long, env = "PLAYSHELF_AWS_REGION")]
+    pub aws_region: Option<String>,
+
+    /// DynamoDB table holding users
+    #[arg(long, env = "PLAYSHELF_USERS_TABLE")]
+    pub users_table: Option<String>,
+
+    /// DynamoDB table reserving usernames
+    #[arg(long, env = "PLAYSHELF_USERNAMES_TABLE")]
+    pub usernames_table: Option<String>,
+
+    /// Base URL of the IGDB API, e.g. a local mock server for testing
+    #[arg(long, env = "PLAYSHELF_IGDB_URL")]
+    pub igdb_url: Option<String>,
+
+    /// URL of the Twitch OAuth2 token endpoint
+    #[arg(long, env = "PLAYSHELF_TWITCH_TOKEN_URL")]
+    pub twitch_token_url: Option<String>,
+
+    /// Maximum number of cached platform or genre names
+    #[arg(long, env = "PLAYSHELF_NAME_CACHE_CAPACITY")]
+    pub name_cache_capacity: Option<usize>,
+
+    /// Maximum number of cached game results
+    #[arg(long, env = "PLAYSHELF_GAME_CACHE_CAPACITY")]
+    pub game_cache_capacity: Option<usize>,
+}
+
+impl Args {
+    /// Loads the config file (if any), applies the flag and environment overrides and validates the result
+    pub fn load_config(&self) -> Result<Config, ConfigError> {
+        let mut config = match &self.config {
+            Some(path) => Config::load(path)?,
+            None => Config::default(),
+        };
+
+        if let Some(bind_address) = self.bind_address {
+            config.bind_address = bind_address;
+        }
+        if let Some(cors_origins) = &self.cors_origins {
+            config.cors_origins = cors_origins.clone();
+        }
+        if let Some(aws_region) = &self.aws_region {
+            config.aws_region = aws_region.clone();
+        }
+        if let Some(users_table) = &self.users_table {
+            config.tables.users = users_table.clone();
+        }
+        if let Some(usernames_table) = &self.usernames_table {
+            config.tables.usernames = usernames_table.clone();
+        }
+        if let Some(igdb_url) = &self.igdb_url {
+            config.igdb.igdb_url = igdb_url.clone();
+        }
+        if let Some(token_url) = &self.twitch_token_url {
+            config.igdb.token_url = token_url.clone();
+        }
+        if let Some(capacity) = self.name_cache_capacity {
+            config.igdb.name_cache_capacity = capacity;
+        }
+        if let Some(capacity) = self.game_cache_capacity {
+            config.igdb.game_cache_capacity = capacity;
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    /// Parses `flags` without reading the `PLAYSHELF_*` environment variables,
+    /// so the shell the tests run in cannot change the result
+    fn parse(flags: &[&str]) -> Result<Args, clap::Error> {
+        let matches = Args::command().mut_args(|arg| arg.env(None)).try_get_matches_from(flags)?;
+        Args::from_arg_matches(&matches)
+    }
+
+    #[test]
+    fn test_flags_override_config() {
+        let flags = parse(&[
+            "playshelf-server",
+            "--bind-address",
+            "127.0.0.1:9000",
+            "--cors-origins",
+            "https://playshelf.example.com,http://localhost:8080",
+            "--users-table",
+            "playshelf_user_test",
+            "--game-cache-capacity",
+            "0",
+        ])
+        .expect("Failed to parse flags");
+        let config = flags.load_config().expect("Failed to load config");
+
+        assert_eq!(config.bind_address, SocketAddr::from(([127, 0, 0, 1], 9000)));
+        assert_eq!(config.cors_origins, vec!["https://playshelf.example.com", "http://localhost:8080"]);
+        assert_eq!(config.tables.users, "playshelf_user_test");
+        assert_eq!(config.igdb.game_cache_capacity, 0);
+        assert_eq!(config.aws_region, Config::default().aws_region);
+    }
+
+    #[test]
+    fn test_invalid_flags_fail_validation() {
+        let flags = parse(&["playshelf-server", "--igdb-url", "not a url"]).expect("Failed to parse flags");
+        assert!(matches!(flags.load_config(), Err(ConfigError::Invalid { setting: "igdb.igdb_url", .. })));
+    }
+}
@@ -0,0 +1,163 @@
+//! Password hashing and signed session tokens
+//!
+//! Passwords are hashed with Argon2id and stored in PHC string format, so the
+//! parameters travel with each hash. Sessions are stateless HS256 JWTs whose
+//! subject is the user ID; any server holding the same signing key accepts
+//! them, whichever user store it uses.
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a session token stays valid after login
+pub const SESSION_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Errors returned while hashing passwords or issuing and checking tokens
+#[derive(Debug)]
+pub enum AuthError {
+    /// The password could not be hashed
+    Hash(String),
+    /// A session token could not be created
+    Token(String),
+    /// The session token is malformed, forged or expired
+    InvalidToken(String),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::Hash(message) => write!(f, "failed to hash password: {}", message),
+            AuthError::Token(message) => write!(f, "failed to create session token: {}", message),
+            AuthError::InvalidToken(message) => write!(f, "invalid session token: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// Hashes `password` with a fresh random salt
+pub fn hash_password(password: &str) -> Result<String, AuthError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AuthError::Hash(e.to_string()))
+}
+
+/// Checks `password` against a hash produced by `hash_password`
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+        .unwrap_or(false)
+}
+
+/// Claims carried by a session token
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    /// User ID as a decimal string, since JWT numbers cannot hold 128 bits
+    sub: String,
+    iat: u64,
+    exp: u64,
+}
+
+/// A newly issued session token
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub token: String,
+    /// Unix timestamp in seconds after which the token is rejected
+    pub expires_at: u64,
+}
+
+/// Signs and verifies session tokens with a shared secret
+pub struct SessionKeys {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    validation: Validation,
+}
+
+impl SessionKeys {
+    pub fn new(secret: &[u8]) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.leeway = 0;
+        Self {
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+            validation,
+        }
+    }
+
+    /// Keys for a random secret; tokens stop working when the process exits
+    pub fn random() -> Self {
+        let secret: [u8; 32] = rand::random();
+        Self::new(&secret)
+    }
+
+    /// Issues a token identifying `user_id`
+    pub fn issue(&self, user_id: u128) -> Result<Session, AuthError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| AuthError::Token(e.to_string()))?
+            .as_secs();
+        let claims = Claims {
+            sub: user_id.to_string(),
+            iat: now,
+            exp: now + SESSION_TTL.as_secs(),
+        };
+        let token = jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &self.encoding)
+            .map_err(|e| AuthError::Token(e.to_string()))?;
+        Ok(Session {
+            token,
+            expires_at: claims.exp,
+        })
+    }
+
+    /// Returns the user ID of a valid, unexpired token
+    pub fn verify(&self, token: &str) -> Result<u128, AuthError> {
+        let data = jsonwebtoken::decode::<Claims>(token, &self.decoding, &self.validation)
+            .map_err(|e| AuthError::InvalidToken(e.to_string()))?;
+        data.claims
+            .sub
+            .parse()
+            .map_err(|_| AuthError::InvalidToken("subject is not a user ID".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_hash_round_trip() {
+        let hash = hash_password("correct horse").expect("Failed to hash password");
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password("correct horse", &hash));
+        assert!(!verify_password("battery staple", &hash));
+        assert!(!verify_password("correct horse", "not a hash"));
+        assert_ne!(hash, hash_password("correct horse").unwrap(), "Salts should differ");
+    }
+
+    #[test]
+    fn test_session_tokens() {
+        let keys = SessionKeys::new(b"test-key");
+        let user_id = u128::MAX - 1;
+        let session = keys.issue(user_id).expect("Failed to issue token");
+        assert_eq!(keys.verify(&session.token).unwrap(), user_id);
+
+        // Tokens signed with another key or tampered with are rejected
+        assert!(SessionKeys::new(b"other-key").verify(&session.token).is_err());
+        let tampered = format!("{}x", session.token);
+        assert!(matches!(keys.verify(&tampered), Err(AuthError::InvalidToken(_))));
+
+        let expired = Claims {
+            sub: user_id.to_string(),
+            iat: 1,
+            exp: 2,
+        };
+        let token = jsonwebtoken::encode(&Header::default(), &expired, &keys.encoding).unwrap();
+        assert!(keys.verify(&token).is_err());
+    }
+}
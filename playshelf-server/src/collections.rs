@@ -0,0 +1,236 @@
+//! Managing a user's named collections
+//!
+//! Collections live on the `User` and are saved with it, so every change here
+//! is followed by a `UserStore::update` in the handlers.
+
+use playshelf_types::{Collection, CollectionDetail};
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::user::User;
+
+/// Why a change to a user's collections was refused
+#[derive(Debug, PartialEq, Eq)]
+pub enum CollectionError {
+    /// The user has no collection with this ID
+    NotFound(u64),
+    /// Another collection already has this name, ignoring case
+    NameTaken(String),
+    /// The game is not on the user's shelf
+    NotOnShelf(u64),
+    /// The game is already in the collection
+    AlreadyCollected(u64),
+    /// The game is not in the collection
+    NotCollected(u64),
+    /// A new order did not list every item exactly once
+    InvalidOrder,
+}
+
+impl fmt::Display for CollectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollectionError::NotFound(id) => write!(f, "Collection {} not found", id),
+            CollectionError::NameTaken(name) => write!(f, "A collection named '{}' already exists", name),
+            CollectionError::NotOnShelf(game_id) => write!(f, "Game {} is not in the library", game_id),
+            CollectionError::AlreadyCollected(game_id) => {
+                write!(f, "Game {} is already in the collection", game_id)
+            }
+            CollectionError::NotCollected(game_id) => write!(f, "Game {} is not in the collection", game_id),
+            CollectionError::InvalidOrder => write!(f, "The new order must list every item exactly once"),
+        }
+    }
+}
+
+impl std::error::Error for CollectionError {}
+
+pub type CollectionResult<T> = Result<T, CollectionError>;
+
+/// Whether `order` lists exactly the items of `current`, each once
+fn is_reordering(current: &[u64], order: &[u64]) -> bool {
+    let unique: HashSet<u64> = order.iter().copied().collect();
+    order.len() == current.len() && unique.len() == order.len() && current.iter().all(|id| unique.contains(id))
+}
+
+impl User {
+    pub fn collection(&self, id: u64) -> CollectionResult<&Collection> {
+        self.collections
+            .iter()
+            .find(|collection| collection.id == id)
+            .ok_or(CollectionError::NotFound(id))
+    }
+
+    fn collection_mut(&mut self, id: u64) -> CollectionResult<&mut Collection> {
+        self.collections
+            .iter_mut()
+            .find(|collection| collection.id == id)
+            .ok_or(CollectionError::NotFound(id))
+    }
+
+    /// Fails if a collection other than `except` is already called `name`
+    fn check_collection_name(&self, name: &str, except: Option<u64>) -> CollectionResult<()> {
+        let taken = self
+            .collections
+            .iter()
+            .any(|collection| Some(collection.id) != except && collection.name.eq_ignore_ascii_case(name));
+        if taken {
+            return Err(CollectionError::NameTaken(name.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Adds an empty collection after the existing ones and returns its ID,
+    /// which is never one a deleted collection had
+    pub fn create_collection(&mut self, name: String) -> CollectionResult<u64> {
+        self.check_collection_name(&name, None)?;
+        // Collections saved before the counter existed may hold higher IDs
+        let highest = self.collections.iter().map(|collection| collection.id).max().unwrap_or(0);
+        let id = self.last_collection_id.max(highest) + 1;
+        self.last_collection_id = id;
+        self.collections.push(Collection {
+            id,
+            name,
+            game_ids: Vec::new(),
+        });
+        Ok(id)
+    }
+
+    pub fn rename_collection(&mut self, id: u64, name: String) -> CollectionResult<()> {
+        self.check_collection_name(&name, Some(id))?;
+        self.collection_mut(id)?.name = name;
+        Ok(())
+    }
+
+    pub fn delete_collection(&mut self, id: u64) -> CollectionResult<()> {
+        self.collection(id)?;
+        self.collections.retain(|collection| collection.id != id);
+        Ok(())
+    }
+
+    /// Puts the collections in the order of `ids`, which must list each of them once
+    pub fn reorder_collections(&mut self, ids: &[u64]) -> CollectionResult<()> {
+        let current: Vec<u64> = self.collections.iter().map(|collection| collection.id).collect();
+        if !is_reordering(&current, ids) {
+            return Err(CollectionError::InvalidOrder);
+        }
+        self.collections
+            .sort_by_key(|collection| ids.iter().position(|&id| id == collection.id));
+        Ok(())
+    }
+
+    /// Adds a game from the shelf to a collection at `position`, or at the end
+    pub fn collect_game(&mut self, id: u64, game_id: u64, position: Option<usize>) -> CollectionResult<()> {
+        if !self.games.iter().any(|entry| entry.game.id == game_id) {
+            return Err(CollectionError::NotOnShelf(game_id));
+        }
+        let collection = self.collection_mut(id)?;
+        if collection.game_ids.contains(&game_id) {
+            return Err(CollectionError::AlreadyCollected(game_id));
+        }
+        let position = position.unwrap_or(usize::MAX).min(collection.game_ids.len());
+        collection.game_ids.insert(position, game_id);
+        Ok(())
+    }
+
+    /// Takes a game out of a collection, leaving it on the shelf
+    pub fn uncollect_game(&mut self, id: u64, game_id: u64) -> CollectionResult<()> {
+        let collection = self.collection_mut(id)?;
+        let len = collection.game_ids.len();
+        collection.game_ids.retain(|&collected| collected != game_id);
+        if collection.game_ids.len() == len {
+            return Err(CollectionError::NotCollected(game_id));
+        }
+        Ok(())
+    }
+
+    /// Puts the games of a collection in the order of `game_ids`, which must list each of them once
+    pub fn reorder_collection(&mut self, id: u64, game_ids: &[u64]) -> CollectionResult<()> {
+        let collection = self.collection_mut(id)?;
+        if !is_reordering(&collection.game_ids, game_ids) {
+            return Err(CollectionError::InvalidOrder);
+        }
+        collection.game_ids = game_ids.to_vec();
+        Ok(())
+    }
+
+    /// A collection with the shelf entries of its games, in collection order
+    pub fn collection_detail(&self, id: u64) -> CollectionResult<CollectionDetail> {
+        let collection = self.collection(id)?;
+        let games = collection
+            .game_ids
+            .iter()
+            .filter_map(|game_id| self.games.iter().find(|entry| entry.game.id == *game_id))
+            .cloned()
+            .collect();
+        Ok(CollectionDetail {
+            id: collection.id,
+            name: collection.name.clone(),
+            games,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::shelf_of;
+
+    fn order(user: &User, id: u64) -> Vec<u64> {
+        user.collection(id).unwrap().game_ids.clone()
+    }
+
+    #[test]
+    fn test_collection_lifecycle() {
+        let mut user = shelf_of(&[]);
+        let couch = user.create_collection("Couch co-op".to_string()).unwrap();
+        let retro = user.create_collection("Retro 2024".to_string()).unwrap();
+        assert_eq!(
+            user.create_collection("couch CO-OP".to_string()),
+            Err(CollectionError::NameTaken("couch CO-OP".to_string()))
+        );
+        assert_eq!(
+            user.rename_collection(retro, "Couch co-op".to_string()),
+            Err(CollectionError::NameTaken("Couch co-op".to_string()))
+        );
+        user.rename_collection(couch, "Couch Co-op".to_string()).unwrap();
+
+        assert_eq!(user.reorder_collections(&[retro]), Err(CollectionError::InvalidOrder));
+        assert_eq!(user.reorder_collections(&[retro, retro]), Err(CollectionError::InvalidOrder));
+        user.reorder_collections(&[retro, couch]).unwrap();
+        let names: Vec<&str> = user.collections.iter().map(|collection| collection.name.as_str()).collect();
+        assert_eq!(names, vec!["Retro 2024", "Couch Co-op"]);
+
+        user.delete_collection(retro).unwrap();
+        assert_eq!(user.delete_collection(retro), Err(CollectionError::NotFound(retro)));
+        let newest = user.create_collection("Retro 2025".to_string()).unwrap();
+        assert_ne!(newest, couch);
+        assert_ne!(newest, retro, "IDs of deleted collections are not reused");
+    }
+
+    #[test]
+    fn test_collection_games() {
+        let mut user = shelf_of(&[1, 2, 3]);
+        let couch = user.create_collection("Couch co-op".to_string()).unwrap();
+        let retro = user.create_collection("Retro".to_string()).unwrap();
+
+        user.collect_game(couch, 1, None).unwrap();
+        user.collect_game(couch, 2, None).unwrap();
+        user.collect_game(couch, 3, Some(0)).unwrap();
+        assert_eq!(order(&user, couch), vec![3, 1, 2]);
+        assert_eq!(user.collect_game(couch, 1, None), Err(CollectionError::AlreadyCollected(1)));
+        assert_eq!(user.collect_game(couch, 9, None), Err(CollectionError::NotOnShelf(9)));
+
+        // A game can be in several collections
+        user.collect_game(retro, 1, Some(99)).unwrap();
+        user.reorder_collection(couch, &[1, 2, 3]).unwrap();
+        assert_eq!(user.reorder_collection(couch, &[1, 2]), Err(CollectionError::InvalidOrder));
+        let names: Vec<String> =
+            user.collection_detail(couch).unwrap().games.into_iter().map(|entry| entry.game.name).collect();
+        assert_eq!(names, vec!["Game 1", "Game 2", "Game 3"]);
+
+        user.uncollect_game(couch, 2).unwrap();
+        assert_eq!(user.uncollect_game(couch, 2), Err(CollectionError::NotCollected(2)));
+        assert!(user.remove_game(1), "Removing from the shelf empties the collections too");
+        assert_eq!(order(&user, couch), vec![3]);
+        assert!(order(&user, retro).is_empty());
+    }
+}
@@ -0,0 +1,269 @@
+//! Server settings
+//!
+//! Settings are layered: built-in defaults, then an optional TOML file, then
+//! environment variables and command line flags (see `args.rs`). A complete
+//! file looks like this:
+//!
+//! ```toml
+//! bind_address = "0.0.0.0:8081"
+//! cors_origins = ["https://playshelf.example.com"]
+//! aws_region = "us-east-2"
+//!
+//! [tables]
+//! users = "playshelf_user"
+//! usernames = "playshelf_username"
+//!
+//! [igdb]
+//! igdb_url = "https://api.igdb.com"
+//! token_url = "https://id.twitch.tv/oauth2/token"
+//! name_cache_capacity = 2000
+//! game_cache_capacity = 500
+//! ```
+
+use axum::http::HeaderValue;
+use serde::Deserialize;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+use crate::igdb::manager::IGDBConfig;
+
+/// `cors_origins` entry that allows requests from any origin
+pub const ANY_ORIGIN: &str = "*";
+
+/// Settings of the PlayShelf server
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Address the HTTP server listens on
+    pub bind_address: SocketAddr,
+    /// Origins allowed to call the API from a browser, or `["*"]` for any origin
+    pub cors_origins: Vec<String>,
+    /// AWS region of the DynamoDB tables and SSM parameters
+    pub aws_region: String,
+    /// DynamoDB table names
+    pub tables: TableConfig,
+    /// IGDB endpoints and cache sizes
+    pub igdb: IGDBConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            bind_address: SocketAddr::from(([0, 0, 0, 0], 8081)),
+            cors_origins: vec![ANY_ORIGIN.to_string()],
+            aws_region: "us-east-2".to_string(),
+            tables: TableConfig::default(),
+            igdb: IGDBConfig::default(),
+        }
+    }
+}
+
+/// Names of the DynamoDB tables
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TableConfig {
+    /// Table holding users and their libraries, keyed by `user_id`, with a
+    /// `username-index` global secondary index on `username`
+    pub users: String,
+    /// Table claiming each username for one user, keyed by `username`
+    pub usernames: String,
+}
+
+impl Default for TableConfig {
+    fn default() -> Self {
+        Self {
+            users: "playshelf_user".to_string(),
+            usernames: "playshelf_username".to_string(),
+        }
+    }
+}
+
+/// Errors returned while loading or validating the configuration
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The configuration file could not be read or parsed
+    File { path: PathBuf, message: String },
+    /// A setting has an invalid value
+    Invalid { setting: &'static str, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::File { path, message } => {
+                write!(f, "failed to load config file '{}': {}", path.display(), message)
+            }
+            ConfigError::Invalid { setting, message } => {
+                write!(f, "invalid setting '{}': {}", setting, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn invalid(setting: &'static str, message: impl Into<String>) -> ConfigError {
+    ConfigError::Invalid {
+        setting,
+        message: message.into(),
+    }
+}
+
+impl Config {
+    /// Loads the TOML file at `path`; settings missing from the file keep their defaults
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let file_error = |message: String| ConfigError::File {
+            path: path.to_path_buf(),
+            message,
+        };
+        let content = std::fs::read_to_string(path).map_err(|e| file_error(e.to_string()))?;
+        toml::from_str(&content).map_err(|e: toml::de::Error| file_error(e.to_string()))
+    }
+
+    /// Checks every setting, returning the first invalid one
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.cors_origins.iter().any(|origin| origin == ANY_ORIGIN) {
+            if self.cors_origins.len() > 1 {
+                return Err(invalid("cors_origins", "\"*\" cannot be combined with other origins"));
+            }
+        } else {
+            for origin in &self.cors_origins {
+                validate_origin(origin).map_err(|message| invalid("cors_origins", message))?;
+            }
+        }
+
+        let region_chars = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-';
+        if self.aws_region.is_empty() || !self.aws_region.chars().all(region_chars) {
+            return Err(invalid("aws_region", format!("'{}' is not an AWS region", self.aws_region)));
+        }
+
+        validate_table_name(&self.tables.users).map_err(|message| invalid("tables.users", message))?;
+        validate_table_name(&self.tables.usernames)
+            .map_err(|message| invalid("tables.usernames", message))?;
+
+        validate_http_url(&self.igdb.igdb_url).map_err(|message| invalid("igdb.igdb_url", message))?;
+        validate_http_url(&self.igdb.token_url).map_err(|message| invalid("igdb.token_url", message))?;
+        Ok(())
+    }
+
+    /// Builds the CORS layer allowing the configured origins
+    pub fn cors_layer(&self) -> CorsLayer {
+        if self.cors_origins.iter().any(|origin| origin == ANY_ORIGIN) {
+            return CorsLayer::permissive();
+        }
+        let origins = self
+            .cors_origins
+            .iter()
+            .filter_map(|origin| HeaderValue::from_str(origin).ok())
+            .collect::<Vec<_>>();
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::list(origins))
+            .allow_methods(Any)
+            .allow_headers(Any)
+    }
+}
+
+/// Checks that `url` is an absolute http(s) URL
+fn validate_http_url(url: &str) -> Result<reqwest::Url, String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("'{}' is not a valid URL: {}", url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("'{}' must use http or https", url));
+    }
+    Ok(parsed)
+}
+
+/// Checks that `origin` is a bare origin such as `https://example.com:8080`
+fn validate_origin(origin: &str) -> Result<(), String> {
+    let parsed = validate_http_url(origin)?;
+    if parsed.path() != "/" || origin.ends_with('/') || parsed.query().is_some() {
+        return Err(format!("'{}' must be an origin without a path", origin));
+    }
+    Ok(())
+}
+
+/// Checks `name` against the DynamoDB table naming rules
+fn validate_table_name(name: &str) -> Result<(), String> {
+    let valid_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.');
+    if !(3..=255).contains(&name.len()) || !name.chars().all(valid_char) {
+        return Err(format!(
+            "'{}' must be 3 to 255 letters, digits, underscores, dashes or dots",
+            name
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_is_valid() {
+        Config::default().validate().expect("Default config should be valid");
+    }
+
+    #[test]
+    fn test_load_partial_file() {
+        let path = std::env::temp_dir().join(format!("playshelf-{}-config.toml", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "bind_address = \"127.0.0.1:9000\"\n\n[igdb]\ngame_cache_capacity = 0\n",
+        )
+        .expect("Failed to write temp file");
+        let config = Config::load(&path).expect("Failed to load config");
+        std::fs::remove_file(path).ok();
+
+        assert_eq!(config.bind_address, SocketAddr::from(([127, 0, 0, 1], 9000)));
+        assert_eq!(config.igdb.game_cache_capacity, 0);
+        assert_eq!(config.igdb.igdb_url, IGDBConfig::default().igdb_url);
+        assert_eq!(config.tables, TableConfig::default());
+    }
+
+    #[test]
+    fn test_load_rejects_unknown_settings() {
+        let path = std::env::temp_dir().join(format!("playshelf-{}-config.toml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "bind_adress = \"127.0.0.1:9000\"\n").expect("Failed to write temp file");
+        let result = Config::load(&path);
+        std::fs::remove_file(path).ok();
+        assert!(matches!(result, Err(ConfigError::File { .. })));
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_settings() {
+        let setting = |config: Config| match config.validate() {
+            Err(ConfigError::Invalid { setting, .. }) => setting,
+            other => panic!("expected an invalid setting, got {:?}", other),
+        };
+
+        let cors = |origins: &[&str]| Config {
+            cors_origins: origins.iter().map(|origin| origin.to_string()).collect(),
+            ..Config::default()
+        };
+        assert_eq!(setting(cors(&["*", "https://example.com"])), "cors_origins");
+        assert_eq!(setting(cors(&["https://example.com/app"])), "cors_origins");
+        assert_eq!(setting(cors(&["example.com"])), "cors_origins");
+        cors(&["https://example.com", "http://localhost:8080"])
+            .validate()
+            .expect("Origins should be valid");
+
+        let mut config = Config::default();
+        config.tables.users = "users table".to_string();
+        assert_eq!(setting(config), "tables.users");
+
+        let mut config = Config::default();
+        config.tables.usernames = "u".to_string();
+        assert_eq!(setting(config), "tables.usernames");
+
+        let mut config = Config::default();
+        config.igdb.token_url = "ftp://id.twitch.tv".to_string();
+        assert_eq!(setting(config), "igdb.token_url");
+
+        let config = Config {
+            aws_region: String::new(),
+            ..Config::default()
+        };
+        assert_eq!(setting(config), "aws_region");
+    }
+}
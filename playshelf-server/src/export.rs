@@ -0,0 +1,168 @@
+//! Exporting a user's library as CSV or versioned JSON
+
+use chrono::{DateTime, Utc};
+use playshelf_types::{LibraryExport, ShelfEntry, UserResponse, CSV_COLUMNS, EXPORT_VERSION};
+
+use crate::user::User;
+
+/// First characters that make a spreadsheet read a cell as a formula
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// Formats an optional value for a CSV cell, leaving it empty if `None`
+fn cell<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Formats user or IGDB text for a CSV cell, prefixing `'` to text a
+/// spreadsheet would otherwise run as a formula
+fn text_cell(text: &str) -> String {
+    if text.starts_with(FORMULA_PREFIXES) {
+        format!("'{}", text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Undoes `text_cell`, so an exported CSV imports with its original titles
+pub(crate) fn strip_formula_guard(text: &str) -> &str {
+    match text.strip_prefix('\'') {
+        Some(rest) if rest.starts_with(FORMULA_PREFIXES) => rest,
+        _ => text,
+    }
+}
+
+impl User {
+    /// The user in the `sample_users.json` shape, stamped with the export version
+    pub fn export(&self, exported_at: DateTime<Utc>) -> LibraryExport {
+        LibraryExport {
+            version: EXPORT_VERSION,
+            exported_at: Some(exported_at),
+            users: vec![UserResponse::from(self)],
+        }
+    }
+
+    /// The shelf as CSV with the `CSV_COLUMNS` header, one row per entry
+    pub fn export_csv(&self) -> Result<Vec<u8>, csv::Error> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(CSV_COLUMNS)?;
+        for entry in &self.games {
+            writer.write_record(self.csv_row(entry))?;
+        }
+        writer.into_inner().map_err(|e| e.into_error().into())
+    }
+
+    fn csv_row(&self, entry: &ShelfEntry) -> [String; CSV_COLUMNS.len()] {
+        let game = &entry.game;
+        let collections: Vec<&str> = self
+            .collections
+            .iter()
+            .filter(|collection| collection.game_ids.contains(&game.id))
+            .map(|collection| collection.name.as_str())
+            .collect();
+        [
+            game.id.to_string(),
+            text_cell(&game.name),
+            text_cell(&game.platforms.join("; ")),
+            text_cell(&game.genres.join("; ")),
+            cell(game.first_release_date.map(|date| date.date)),
+            // Same spelling as the JSON export
+            serde_json::to_value(entry.status)
+                .ok()
+                .and_then(|status| status.as_str().map(str::to_string))
+                .unwrap_or_default(),
+            cell(entry.added_on),
+            cell(entry.started_on),
+            cell(entry.finished_on),
+            cell(entry.rating),
+            text_cell(entry.review.as_deref().unwrap_or_default()),
+            cell(entry.reviewed_on),
+            entry.sessions.len().to_string(),
+            entry.minutes_played().to_string(),
+            cell(entry.sessions.first().map(|session| session.started_at.to_rfc3339())),
+            cell(entry.sessions.last().map(|session| session.ended_at().to_rfc3339())),
+            text_cell(&collections.join("; ")),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use playshelf_types::{LogSessionRequest, Rating};
+    use std::fs;
+
+    fn sample_user() -> User {
+        let sample = fs::read_to_string("../sample_users.json").expect("Failed to read sample_users.json");
+        let export: LibraryExport = serde_json::from_str(&sample).expect("Failed to parse sample_users.json");
+        serde_json::from_value(serde_json::to_value(&export.users[0]).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_json_export_round_trips() {
+        let mut user = sample_user();
+        let collection = user.create_collection("Favourites".to_string()).unwrap();
+        user.collect_game(collection, 0, None).unwrap();
+        let exported_at: DateTime<Utc> = "2024-05-01T12:00:00Z".parse().unwrap();
+
+        let value = serde_json::to_value(user.export(exported_at)).unwrap();
+        assert_eq!(value["version"], EXPORT_VERSION);
+        assert_eq!(value["exported_at"], "2024-05-01T12:00:00Z");
+        let export: LibraryExport = serde_json::from_value(value).unwrap();
+        let restored: User = serde_json::from_value(serde_json::to_value(&export.users[0]).unwrap()).unwrap();
+        assert_eq!(restored.games, user.games);
+        assert_eq!(restored.collections, user.collections);
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let mut user = sample_user();
+        let date = chrono::NaiveDate::from_ymd_opt(2018, 1, 2).unwrap();
+        user.entry_mut(0)
+            .unwrap()
+            .set_review(Rating::from_half_stars(9), Some("Climb \"everything\",\nthen glide".to_string()), date);
+        let session = LogSessionRequest {
+            started_at: "2017-03-03T20:00:00Z".parse().unwrap(),
+            ended_at: None,
+            duration_minutes: Some(90),
+            platform: None,
+            note: None,
+        };
+        user.log_session(0, session).unwrap();
+
+        let csv = user.export_csv().unwrap();
+        let mut reader = csv::Reader::from_reader(csv.as_slice());
+        assert_eq!(reader.headers().unwrap(), CSV_COLUMNS.as_slice());
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 2);
+        let zelda = &rows[0];
+        assert_eq!(&zelda[1], "The Legend of Zelda: Breath of the Wild");
+        assert_eq!(&zelda[2], "Nintendo Wii U; Nintendo Switch");
+        assert_eq!(&zelda[4], "2017-03-03");
+        assert_eq!(&zelda[5], "completed");
+        assert_eq!(&zelda[9], "4.5");
+        assert_eq!(&zelda[10], "Climb \"everything\",\nthen glide", "Quotes and line breaks survive");
+        assert_eq!((&zelda[12], &zelda[13]), ("1", "90"));
+        assert_eq!(&zelda[15], "2017-03-03T21:30:00+00:00");
+        assert_eq!(&rows[1][5], "playing");
+    }
+
+    #[test]
+    fn test_csv_export_guards_formulas() {
+        let mut user = sample_user();
+        let date = chrono::NaiveDate::from_ymd_opt(2018, 1, 2).unwrap();
+        user.entry_mut(0).unwrap().set_review(None, Some("=HYPERLINK(\"http://evil\")".to_string()), date);
+        let collection = user.create_collection("@home".to_string()).unwrap();
+        user.collect_game(collection, 0, None).unwrap();
+        user.entry_mut(1).unwrap().game.name = "-1+1".to_string();
+
+        let csv = user.export_csv().unwrap();
+        let mut reader = csv::Reader::from_reader(csv.as_slice());
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(&rows[0][10], "'=HYPERLINK(\"http://evil\")");
+        assert_eq!(&rows[0][16], "'@home");
+        assert_eq!(&rows[1][1], "'-1+1");
+        assert_eq!(&rows[0][1], "The Legend of Zelda: Breath of the Wild");
+        assert_eq!(strip_formula_guard(&rows[1][1]), "-1+1");
+        assert_eq!(strip_formula_guard("'Tis the season"), "'Tis the season");
+    }
+}
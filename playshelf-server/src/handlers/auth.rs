@@ -0,0 +1,153 @@
+use axum::{
+    extract::{FromRef, FromRequestParts, State},
+    http::{header::AUTHORIZATION, request::Parts, StatusCode},
+    response::Json,
+};
+use playshelf_types::validation::{
+    collect_errors, validate_description, validate_name, validate_password, validate_username,
+};
+use playshelf_types::{LoginRequest, RegisterRequest, SessionResponse, UserResponse};
+use std::sync::Arc;
+
+use super::{error_response, json_response, store_error_response, validation_error_response, JsonResponse};
+use crate::auth::{hash_password, verify_password, SessionKeys};
+use crate::store::{StoreError, UserStore};
+use crate::user::User;
+
+/// Argon2 hash checked when the user is unknown or has no password, so those
+/// logins take as long as a wrong password
+const DUMMY_PASSWORD_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$HlIwfQM2uJK9iXSkRwbUDQ$+3Bh19Vpclidvzw3eDbAq9Lnm+3jSztA3iHTTLV+HPQ";
+
+/// The user making the request, identified by the `Authorization: Bearer` session token
+///
+/// Handlers taking this extractor answer 401 to anonymous requests.
+pub struct AuthUser(pub User);
+
+impl<S> FromRequestParts<S> for AuthUser
+where
+    Arc<SessionKeys>: FromRef<S>,
+    Arc<dyn UserStore>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = JsonResponse;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| error_response(StatusCode::UNAUTHORIZED, "Sign in to do this"))?;
+        let user_id = Arc::<SessionKeys>::from_ref(state)
+            .verify(token.trim())
+            .map_err(|e| error_response(StatusCode::UNAUTHORIZED, e.to_string()))?;
+        match Arc::<dyn UserStore>::from_ref(state).get_by_id(user_id).await {
+            Ok(Some(user)) => Ok(AuthUser(user)),
+            Ok(None) => Err(error_response(StatusCode::UNAUTHORIZED, "This account no longer exists")),
+            Err(e) => Err(store_error_response(e)),
+        }
+    }
+}
+
+/// Issues a session token for `user` and wraps it with the user in the response body
+fn session_response(status: StatusCode, sessions: &SessionKeys, user: &User) -> JsonResponse {
+    match sessions.issue(user.id) {
+        Ok(session) => json_response(
+            status,
+            &SessionResponse {
+                token: session.token,
+                expires_at: session.expires_at,
+                user: UserResponse::from(user),
+            },
+        ),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// Handler for POST /auth/register endpoint
+/// Creates a user with an empty library and signs them in
+pub async fn register_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    State(sessions): State<Arc<SessionKeys>>,
+    Json(request): Json<RegisterRequest>,
+) -> JsonResponse {
+    let errors = collect_errors([
+        validate_username(&request.username),
+        validate_name(&request.name),
+        validate_description(&request.description),
+        validate_password(&request.password),
+    ]);
+    if !errors.is_empty() {
+        return validation_error_response(errors);
+    }
+    // Checked up front to skip hashing; the store still rejects a username
+    // claimed by a concurrent registration
+    match store.get_by_username(&request.username).await {
+        Ok(Some(_)) => return store_error_response(StoreError::UsernameTaken),
+        Ok(None) => {}
+        Err(e) => return store_error_response(e),
+    }
+
+    // Argon2 is deliberately slow, so keep it off the async workers
+    let password = request.password;
+    let password_hash = match tokio::task::spawn_blocking(move || hash_password(&password)).await {
+        Ok(Ok(hash)) => hash,
+        Ok(Err(e)) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+
+    let mut user = User::new(request.username, request.name, request.description);
+    user.password_hash = Some(password_hash);
+    match store.create(&user).await {
+        Ok(()) => session_response(StatusCode::CREATED, &sessions, &user),
+        Err(e) => store_error_response(e),
+    }
+}
+
+/// Handler for POST /auth/login endpoint
+/// Exchanges a username and password for a session token
+pub async fn login_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    State(sessions): State<Arc<SessionKeys>>,
+    Json(request): Json<LoginRequest>,
+) -> JsonResponse {
+    let user = match store.get_by_username(&request.username).await {
+        Ok(user) => user,
+        Err(e) => return store_error_response(e),
+    };
+    // The same answer, after the same work, for unknown users and wrong
+    // passwords, so logins cannot be used to find out which usernames exist
+    let invalid = || error_response(StatusCode::UNAUTHORIZED, "Invalid username or password");
+    let password_hash = user
+        .as_ref()
+        .and_then(|user| user.password_hash.clone())
+        .unwrap_or_else(|| DUMMY_PASSWORD_HASH.to_string());
+    let password = request.password;
+    let valid = tokio::task::spawn_blocking(move || verify_password(&password, &password_hash))
+        .await
+        .unwrap_or(false);
+    let Some(user) = user.filter(|user| valid && user.password_hash.is_some()) else {
+        return invalid();
+    };
+    session_response(StatusCode::OK, &sessions, &user)
+}
+
+/// Handler for GET /auth/me endpoint
+/// Returns the signed-in user
+pub async fn me_handler(AuthUser(user): AuthUser) -> JsonResponse {
+    json_response(StatusCode::OK, &UserResponse::from(&user))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dummy_hash_costs_as_much_as_real_hashes() {
+        let hash = hash_password("triforce").unwrap();
+        let params = |hash: &str| hash.split('$').take(4).collect::<Vec<_>>().join("$");
+        assert_eq!(params(DUMMY_PASSWORD_HASH), params(&hash));
+        assert!(!verify_password("triforce", DUMMY_PASSWORD_HASH));
+    }
+}
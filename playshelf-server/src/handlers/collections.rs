@@ -0,0 +1,193 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use playshelf_types::validation::validate_collection_name;
+use playshelf_types::{
+    CollectGameRequest, CollectionNameRequest, CollectionsResponse, ErrorResponse, FieldError, OrderRequest,
+    ValidationCode,
+};
+use std::sync::Arc;
+
+use super::auth::AuthUser;
+use super::users::{find_user, owned_library, user_json};
+use super::{error_response, json_response, store_error_response, validation_error_response, JsonResponse};
+use crate::collections::{CollectionError, CollectionResult};
+use crate::store::UserStore;
+use crate::user::User;
+
+/// Maps a refused collection change to the matching HTTP error response
+fn collection_error_response(error: CollectionError) -> JsonResponse {
+    let status = match error {
+        CollectionError::NotFound(_) | CollectionError::NotOnShelf(_) | CollectionError::NotCollected(_) => {
+            StatusCode::NOT_FOUND
+        }
+        CollectionError::NameTaken(_) => {
+            let body = ErrorResponse {
+                error: error.to_string(),
+                fields: vec![FieldError::new("name", ValidationCode::Taken, error.to_string())],
+            };
+            return json_response(StatusCode::CONFLICT, &body);
+        }
+        CollectionError::AlreadyCollected(_) => StatusCode::CONFLICT,
+        CollectionError::InvalidOrder => StatusCode::UNPROCESSABLE_ENTITY,
+    };
+    error_response(status, error.to_string())
+}
+
+/// Applies `change` to the signed-in owner's collections, saves them and
+/// answers with the updated user
+async fn change_collections(
+    store: &dyn UserStore,
+    auth: AuthUser,
+    username: &str,
+    status: StatusCode,
+    change: impl FnOnce(&mut User) -> CollectionResult<()>,
+) -> JsonResponse {
+    let mut user = match owned_library(auth, username) {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    if let Err(e) = change(&mut user) {
+        return collection_error_response(e);
+    }
+    match store.update(&mut user).await {
+        Ok(()) => user_json(status, &user),
+        Err(e) => store_error_response(e),
+    }
+}
+
+/// Handler for GET /users/{username}/collections endpoint
+/// Returns a user's collections in their chosen order
+pub async fn list_collections_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    Path(username): Path<String>,
+) -> JsonResponse {
+    match find_user(store.as_ref(), &username).await {
+        Ok(user) => json_response(
+            StatusCode::OK,
+            &CollectionsResponse {
+                collections: user.collections,
+            },
+        ),
+        Err(response) => response,
+    }
+}
+
+/// Handler for GET /users/{username}/collections/{id} endpoint
+/// Returns a collection with the shelf entries of its games, in order
+pub async fn get_collection_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    Path((username, id)): Path<(String, u64)>,
+) -> JsonResponse {
+    match find_user(store.as_ref(), &username).await {
+        Ok(user) => match user.collection_detail(id) {
+            Ok(detail) => json_response(StatusCode::OK, &detail),
+            Err(e) => collection_error_response(e),
+        },
+        Err(response) => response,
+    }
+}
+
+/// Handler for POST /users/{username}/collections endpoint
+/// Adds an empty collection after the signed-in user's others
+pub async fn create_collection_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    auth: AuthUser,
+    Path(username): Path<String>,
+    Json(request): Json<CollectionNameRequest>,
+) -> JsonResponse {
+    if let Err(error) = validate_collection_name(&request.name) {
+        return validation_error_response(vec![error]);
+    }
+    change_collections(store.as_ref(), auth, &username, StatusCode::CREATED, |user| {
+        user.create_collection(request.name).map(|_| ())
+    })
+    .await
+}
+
+/// Handler for PATCH /users/{username}/collections/{id} endpoint
+/// Renames one of the signed-in user's collections
+pub async fn rename_collection_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    auth: AuthUser,
+    Path((username, id)): Path<(String, u64)>,
+    Json(request): Json<CollectionNameRequest>,
+) -> JsonResponse {
+    if let Err(error) = validate_collection_name(&request.name) {
+        return validation_error_response(vec![error]);
+    }
+    change_collections(store.as_ref(), auth, &username, StatusCode::OK, |user| {
+        user.rename_collection(id, request.name)
+    })
+    .await
+}
+
+/// Handler for DELETE /users/{username}/collections/{id} endpoint
+/// Deletes a collection; its games stay on the shelf
+pub async fn delete_collection_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    auth: AuthUser,
+    Path((username, id)): Path<(String, u64)>,
+) -> JsonResponse {
+    change_collections(store.as_ref(), auth, &username, StatusCode::OK, |user| {
+        user.delete_collection(id)
+    })
+    .await
+}
+
+/// Handler for PUT /users/{username}/collections/order endpoint
+/// Rearranges the signed-in user's collections
+pub async fn reorder_collections_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    auth: AuthUser,
+    Path(username): Path<String>,
+    Json(request): Json<OrderRequest>,
+) -> JsonResponse {
+    change_collections(store.as_ref(), auth, &username, StatusCode::OK, |user| {
+        user.reorder_collections(&request.ids)
+    })
+    .await
+}
+
+/// Handler for POST /users/{username}/collections/{id}/games endpoint
+/// Adds a game from the signed-in user's shelf to a collection
+pub async fn collect_game_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    auth: AuthUser,
+    Path((username, id)): Path<(String, u64)>,
+    Json(request): Json<CollectGameRequest>,
+) -> JsonResponse {
+    change_collections(store.as_ref(), auth, &username, StatusCode::CREATED, |user| {
+        user.collect_game(id, request.game_id, request.position)
+    })
+    .await
+}
+
+/// Handler for DELETE /users/{username}/collections/{id}/games/{game_id} endpoint
+/// Takes a game out of a collection, leaving it on the shelf
+pub async fn uncollect_game_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    auth: AuthUser,
+    Path((username, id, game_id)): Path<(String, u64, u64)>,
+) -> JsonResponse {
+    change_collections(store.as_ref(), auth, &username, StatusCode::OK, |user| {
+        user.uncollect_game(id, game_id)
+    })
+    .await
+}
+
+/// Handler for PUT /users/{username}/collections/{id}/order endpoint
+/// Rearranges the games in one of the signed-in user's collections
+pub async fn reorder_collection_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    auth: AuthUser,
+    Path((username, id)): Path<(String, u64)>,
+    Json(request): Json<OrderRequest>,
+) -> JsonResponse {
+    change_collections(store.as_ref(), auth, &username, StatusCode::OK, |user| {
+        user.reorder_collection(id, &request.ids)
+    })
+    .await
+}
@@ -0,0 +1,61 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use playshelf_types::{ExportFormat, ExportQuery};
+use std::sync::Arc;
+
+use super::users::find_user;
+use super::{error_response, JsonResponse};
+use crate::store::UserStore;
+
+/// Handler for GET /users/{username}/export endpoint
+/// Downloads a user's library as versioned JSON (`?format=json`, the default)
+/// or as CSV (`?format=csv`)
+///
+/// Exports hold nothing that `GET /users/{username}` does not already show,
+/// so like it they need no sign-in.
+pub async fn export_library_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    Path(username): Path<String>,
+    Query(query): Query<ExportQuery>,
+) -> Response {
+    let user = match find_user(store.as_ref(), &username).await {
+        Ok(user) => user,
+        Err(response) => return response.into_response(),
+    };
+    let now = Utc::now();
+    let body = match query.format {
+        ExportFormat::Json => serde_json::to_vec_pretty(&user.export(now)).map_err(|e| e.to_string()),
+        ExportFormat::Csv => user.export_csv().map_err(|e| e.to_string()),
+    };
+    let body = match body {
+        Ok(body) => body,
+        Err(e) => {
+            let response: JsonResponse = error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to export library: {}", e),
+            );
+            return response.into_response();
+        }
+    };
+
+    let content_type = match query.format {
+        ExportFormat::Json => "application/json",
+        ExportFormat::Csv => "text/csv; charset=utf-8",
+    };
+    let disposition = format!(
+        "attachment; filename=\"playshelf-{}-{}.{}\"",
+        user.username,
+        now.date_naive(),
+        query.format.extension()
+    );
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, content_type.to_string()), (header::CONTENT_DISPOSITION, disposition)],
+        body,
+    )
+        .into_response()
+}
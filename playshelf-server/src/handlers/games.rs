@@ -0,0 +1,89 @@
+use axum::{extract::{Path, Query, State}, http::StatusCode, response::Json};
+use playshelf_types::{GamesResponse, PageQuery, SearchQuery};
+use std::sync::Arc;
+
+use super::{error_response, igdb_error_response, json_response, JsonResponse};
+use crate::igdb::manager::{GamePage, IGDBManager, Page, MAX_GAME_PAGE_SIZE};
+
+/// Converts pagination parameters to a `Page`, rejecting out of range limits
+fn to_page(limit: Option<u32>, offset: Option<u32>) -> Result<Page, JsonResponse> {
+    let default = Page::default();
+    let limit = limit.unwrap_or(default.limit);
+    if limit == 0 || limit > MAX_GAME_PAGE_SIZE {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            format!("limit must be between 1 and {}", MAX_GAME_PAGE_SIZE),
+        ));
+    }
+    Ok(Page {
+        limit,
+        offset: offset.unwrap_or(default.offset),
+    })
+}
+
+/// Builds the `{count, games, ...}` response body with paging metadata
+fn game_page_response(page: Page, result: GamePage) -> JsonResponse {
+    let next_offset = result.has_more.then(|| page.offset + result.games.len() as u32);
+    let body = GamesResponse {
+        count: result.games.len(),
+        games: result.games,
+        limit: page.limit,
+        offset: page.offset,
+        has_more: result.has_more,
+        next_offset,
+    };
+    json_response(StatusCode::OK, &body)
+}
+
+/// Handler for GET /games endpoint
+/// Returns a page of games from IGDB
+pub async fn get_games_handler(
+    State(manager): State<Arc<IGDBManager>>,
+    Query(params): Query<PageQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let page = match to_page(params.limit, params.offset) {
+        Ok(page) => page,
+        Err(response) => return response,
+    };
+    match manager.get_games(page).await {
+        Ok(result) => game_page_response(page, result),
+        Err(e) => igdb_error_response("Error fetching games", e),
+    }
+}
+
+/// Handler for GET /games/search endpoint
+/// Searches for games by query string, returning one page of results
+pub async fn search_games_handler(
+    State(manager): State<Arc<IGDBManager>>,
+    Query(params): Query<SearchQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let page = match to_page(params.limit, params.offset) {
+        Ok(page) => page,
+        Err(response) => return response,
+    };
+    match manager.search_games(params.query, page).await {
+        Ok(result) => game_page_response(page, result),
+        Err(e) => igdb_error_response("Error searching games", e),
+    }
+}
+
+/// Handler for GET /games/{id} endpoint
+/// Returns the extended record for a single game
+pub async fn get_game_detail_handler(
+    State(manager): State<Arc<IGDBManager>>,
+    Path(id): Path<u64>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    match manager.get_game_detail(id).await {
+        Ok(Some(detail)) => json_response(StatusCode::OK, &detail),
+        Ok(None) => error_response(StatusCode::NOT_FOUND, format!("Game {} not found", id)),
+        Err(e) => igdb_error_response("Error fetching game", e),
+    }
+}
+
+/// Handler for GET /cache/stats endpoint
+/// Returns the hit/miss counters of the IGDB response caches
+pub async fn cache_stats_handler(
+    State(manager): State<Arc<IGDBManager>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    json_response(StatusCode::OK, &manager.cache_stats())
+}
@@ -0,0 +1,160 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::Utc;
+use playshelf_types::{
+    GameData, ImportPreview, ImportPreviewRequest, ImportRequest, ImportResponse, UserResponse, MAX_IMPORT_ROWS,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use super::auth::AuthUser;
+use super::users::owned_library;
+use super::{error_response, igdb_error_response, json_response, store_error_response, JsonResponse};
+use crate::igdb::error::IGDBError;
+use crate::igdb::manager::{IGDBManager, Page};
+use crate::import::{match_row, parse_csv};
+use crate::reviews::ReviewIndex;
+use crate::store::UserStore;
+
+/// Games searched per row; more than are listed, since the best may not be
+/// IGDB's first result
+const SEARCH_LIMIT: u32 = 10;
+/// Searches run at once, matching the requests IGDB allows per second
+const CONCURRENT_SEARCHES: usize = 4;
+
+/// Searches IGDB for each title, a few at a time, keeping each search's error
+/// rather than failing the whole preview
+async fn search_titles(
+    manager: &Arc<IGDBManager>,
+    titles: HashSet<String>,
+) -> HashMap<String, Result<Vec<GameData>, IGDBError>> {
+    let permits = Arc::new(Semaphore::new(CONCURRENT_SEARCHES));
+    let mut searches = JoinSet::new();
+    for title in titles {
+        let (manager, permits) = (manager.clone(), permits.clone());
+        searches.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let page = Page { limit: SEARCH_LIMIT, offset: 0 };
+            let result = manager.search_games(title.clone(), page).await;
+            (title, result.map(|page| page.games))
+        });
+    }
+    let mut results = HashMap::new();
+    while let Some(joined) = searches.join_next().await {
+        if let Ok((title, result)) = joined {
+            results.insert(title, result);
+        }
+    }
+    results
+}
+
+/// Handler for POST /users/{username}/import/preview endpoint
+/// Reads a CSV of games and matches each row against an IGDB search, without
+/// changing the signed-in user's shelf
+///
+/// Rows with an IGDB ID are looked up by it. Each distinct title is searched
+/// once; a failed lookup or search leaves its rows unmatched with a warning.
+pub async fn import_preview_handler(
+    State(manager): State<Arc<IGDBManager>>,
+    auth: AuthUser,
+    Path(username): Path<String>,
+    Json(request): Json<ImportPreviewRequest>,
+) -> JsonResponse {
+    let user = match owned_library(auth, &username) {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    let mut parsed = match parse_csv(&request.csv) {
+        Ok(rows) => rows,
+        Err(e) => return error_response(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()),
+    };
+
+    let ids: Vec<u64> = parsed.iter().filter_map(|row| row.game_id).collect();
+    let by_id = manager.get_games_by_ids(&ids).await;
+    for row in &mut parsed {
+        let Some(id) = row.game_id else {
+            continue;
+        };
+        match &by_id {
+            Ok(games) if games.contains_key(&id) => {}
+            Ok(_) => row.warnings.push(format!("IGDB has no game {}, matching by title", id)),
+            Err(e) => row.warnings.push(format!("Could not look up game {}: {}", id, e)),
+        }
+    }
+    let by_id = by_id.unwrap_or_default();
+
+    let titles: HashSet<String> = parsed
+        .iter()
+        .filter(|row| !row.title.is_empty() && row.game_id.is_none_or(|id| !by_id.contains_key(&id)))
+        .map(|row| row.title.clone())
+        .collect();
+    let searches = search_titles(&manager, titles).await;
+
+    let mut rows = Vec::with_capacity(parsed.len());
+    for mut row in parsed {
+        let games = match row.game_id.and_then(|id| by_id.get(&id)) {
+            Some(game) => vec![game.clone()],
+            None => match searches.get(&row.title) {
+                Some(Ok(games)) => games.clone(),
+                Some(Err(e)) => {
+                    row.warnings.push(format!("Search failed: {}", e));
+                    Vec::new()
+                }
+                None if row.title.is_empty() => Vec::new(),
+                None => {
+                    row.warnings.push("Search failed".to_string());
+                    Vec::new()
+                }
+            },
+        };
+        rows.push(match_row(row, games, &user.games));
+    }
+    json_response(StatusCode::OK, &ImportPreview { rows })
+}
+
+/// Handler for POST /users/{username}/import endpoint
+/// Adds the confirmed matches of an import preview to the signed-in user's
+/// shelf, leaving out games already on it
+///
+/// Games are looked up on IGDB by ID rather than taken from the request.
+pub async fn import_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    State(manager): State<Arc<IGDBManager>>,
+    State(reviews): State<Arc<ReviewIndex>>,
+    auth: AuthUser,
+    Path(username): Path<String>,
+    Json(request): Json<ImportRequest>,
+) -> JsonResponse {
+    let mut user = match owned_library(auth, &username) {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    if request.items.len() > MAX_IMPORT_ROWS {
+        return error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Import at most {} games at a time", MAX_IMPORT_ROWS),
+        );
+    }
+    let ids: Vec<u64> = request.items.iter().map(|item| item.game_id).collect();
+    let games = match manager.get_games_by_ids(&ids).await {
+        Ok(games) => games,
+        Err(e) => return igdb_error_response("Error looking up imported games", e),
+    };
+    let import = user.import_games(request.items, &games, Utc::now().date_naive());
+    if let Err(e) = store.update(&mut user).await {
+        return store_error_response(e);
+    }
+    reviews.refresh_user(&user).await;
+    let response = ImportResponse {
+        imported: import.imported,
+        skipped: import.skipped,
+        not_found: import.not_found,
+        user: UserResponse::from(&user),
+    };
+    json_response(StatusCode::OK, &response)
+}
@@ -0,0 +1,119 @@
+mod auth;
+mod collections;
+mod export;
+mod games;
+mod import;
+mod reviews;
+mod sessions;
+mod stats;
+mod steam;
+mod users;
+
+use axum::{http::StatusCode, response::Json};
+use playshelf_types::{ErrorResponse, FieldError, ValidationCode};
+use serde::Serialize;
+
+use crate::igdb::error::IGDBError;
+use crate::store::StoreError;
+
+pub use auth::{login_handler, me_handler, register_handler, AuthUser};
+pub use collections::{
+    collect_game_handler, create_collection_handler, delete_collection_handler, get_collection_handler,
+    list_collections_handler, rename_collection_handler, reorder_collection_handler, reorder_collections_handler,
+    uncollect_game_handler,
+};
+pub use export::export_library_handler;
+pub use games::{cache_stats_handler, get_game_detail_handler, get_games_handler, search_games_handler};
+pub use import::{import_handler, import_preview_handler};
+pub use reviews::game_reviews_handler;
+pub use sessions::{delete_session_handler, list_game_sessions_handler, log_session_handler, playtime_handler};
+pub use stats::user_stats_handler;
+pub use steam::import_steam_handler;
+pub use users::{
+    add_user_game_handler, get_user_handler, list_user_games_handler, list_users_handler,
+    remove_user_game_handler, set_game_review_handler, set_game_status_handler, update_profile_handler,
+};
+
+/// JSON response returned by every handler
+pub type JsonResponse = (StatusCode, Json<serde_json::Value>);
+
+/// Builds a response whose body is `body` serialized to JSON
+pub fn json_response<T: Serialize>(status: StatusCode, body: &T) -> JsonResponse {
+    match serde_json::to_value(body) {
+        Ok(value) => (status, Json(value)),
+        Err(e) => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to serialize response: {}", e),
+        ),
+    }
+}
+
+/// Builds an error response with the `{"error": ...}` body the client expects
+pub fn error_response(status: StatusCode, message: impl Into<String>) -> JsonResponse {
+    (status, Json(serde_json::json!(ErrorResponse::new(message))))
+}
+
+/// Builds a 422 response listing every rejected field
+///
+/// The top-level `error` repeats the first field's message for clients that
+/// only show one line.
+pub fn validation_error_response(fields: Vec<FieldError>) -> JsonResponse {
+    let error = fields
+        .first()
+        .map(|field| field.message.clone())
+        .unwrap_or_else(|| "Invalid request".to_string());
+    json_response(StatusCode::UNPROCESSABLE_ENTITY, &ErrorResponse { error, fields })
+}
+
+/// Maps a storage error to the matching HTTP error response
+pub fn store_error_response(error: StoreError) -> JsonResponse {
+    let status = match error {
+        StoreError::NotFound => StatusCode::NOT_FOUND,
+        StoreError::AlreadyExists | StoreError::Conflict => StatusCode::CONFLICT,
+        StoreError::UsernameTaken => {
+            let body = ErrorResponse {
+                error: "Username is already taken".to_string(),
+                fields: vec![FieldError::new(
+                    "username",
+                    ValidationCode::Taken,
+                    "Username is already taken",
+                )],
+            };
+            return json_response(StatusCode::CONFLICT, &body);
+        }
+        StoreError::Backend(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    error_response(status, error.to_string())
+}
+
+/// Maps an IGDB error to the matching HTTP error response
+pub fn igdb_error_response(context: &str, error: IGDBError) -> JsonResponse {
+    let status = match error {
+        IGDBError::RateLimited => StatusCode::SERVICE_UNAVAILABLE,
+        IGDBError::Auth(_) | IGDBError::Upstream { .. } | IGDBError::Http(_) => StatusCode::BAD_GATEWAY,
+    };
+    error_response(status, format!("{}: {}", context, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::User;
+    use playshelf_types::{UserResponse, UsersResponse};
+
+    #[test]
+    fn test_json_response_serializes_ids_above_u64() {
+        let mut user = User::new("link".to_string(), "Link".to_string(), String::new());
+        user.id = u128::MAX;
+
+        let (status, Json(body)) = users::user_json(StatusCode::OK, &user);
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["id"], u128::MAX.to_string());
+
+        let users = UsersResponse { users: vec![UserResponse::from(&user)] };
+        let (status, Json(body)) = json_response(StatusCode::OK, &users);
+        assert_eq!(status, StatusCode::OK);
+        let parsed: UsersResponse = serde_json::from_value(body).unwrap();
+        assert_eq!(parsed.users[0].id, u128::MAX);
+    }
+}
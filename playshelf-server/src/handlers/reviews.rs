@@ -0,0 +1,64 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use playshelf_types::{GameReviewsResponse, PageQuery};
+use std::sync::Arc;
+
+use super::{error_response, json_response, store_error_response, JsonResponse};
+use crate::reviews::ReviewIndex;
+use crate::store::UserStore;
+
+/// Reviews per page when the request does not set `limit`
+const DEFAULT_REVIEW_PAGE_SIZE: u32 = 20;
+
+/// Largest `limit` accepted by `GET /games/{id}/reviews`
+const MAX_REVIEW_PAGE_SIZE: u32 = 100;
+
+/// Handler for GET /games/{id}/reviews endpoint
+/// Returns a page of every user's ratings and reviews of a game, with the
+/// average over all ratings
+pub async fn game_reviews_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    State(index): State<Arc<ReviewIndex>>,
+    Path(game_id): Path<u64>,
+    Query(page): Query<PageQuery>,
+) -> JsonResponse {
+    let limit = page.limit.unwrap_or(DEFAULT_REVIEW_PAGE_SIZE);
+    if limit == 0 || limit > MAX_REVIEW_PAGE_SIZE {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            format!("limit must be between 1 and {}", MAX_REVIEW_PAGE_SIZE),
+        );
+    }
+    let offset = page.offset.unwrap_or(0);
+    let reviews = match index.reviews(store.as_ref(), game_id).await {
+        Ok(reviews) => reviews,
+        Err(e) => return store_error_response(e),
+    };
+
+    let ratings: Vec<f64> = reviews
+        .iter()
+        .filter_map(|review| review.rating.map(|rating| rating.stars()))
+        .collect();
+    let average_rating = (!ratings.is_empty()).then(|| ratings.iter().sum::<f64>() / ratings.len() as f64);
+    let review_count = reviews.len();
+    let page: Vec<_> = reviews
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect();
+    let next_offset = offset as usize + page.len();
+
+    json_response(
+        StatusCode::OK,
+        &GameReviewsResponse {
+            game_id,
+            average_rating,
+            rating_count: ratings.len(),
+            review_count,
+            reviews: page,
+            next_offset: (next_offset < review_count).then_some(next_offset as u32),
+        },
+    )
+}
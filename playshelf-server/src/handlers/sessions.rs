@@ -0,0 +1,102 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use playshelf_types::validation::{
+    collect_errors, validate_session_length, validate_session_note, validate_session_platform,
+};
+use playshelf_types::LogSessionRequest;
+use std::sync::Arc;
+
+use super::auth::AuthUser;
+use super::users::{find_user, owned_library, user_json};
+use super::{error_response, json_response, store_error_response, validation_error_response, JsonResponse};
+use crate::sessions::SessionError;
+use crate::store::UserStore;
+
+/// Maps a refused session change to the matching HTTP error response
+fn session_error_response(error: SessionError) -> JsonResponse {
+    let status = match error {
+        SessionError::NotOnShelf(_) | SessionError::NotFound(_) => StatusCode::NOT_FOUND,
+        SessionError::InvalidLength => StatusCode::UNPROCESSABLE_ENTITY,
+    };
+    error_response(status, error.to_string())
+}
+
+/// Handler for GET /users/{username}/games/{game_id}/sessions endpoint
+/// Returns a shelved game's play sessions, newest first, with their totals
+pub async fn list_game_sessions_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    Path((username, game_id)): Path<(String, u64)>,
+) -> JsonResponse {
+    match find_user(store.as_ref(), &username).await {
+        Ok(user) => match user.game_sessions(game_id) {
+            Ok(sessions) => json_response(StatusCode::OK, &sessions),
+            Err(e) => session_error_response(e),
+        },
+        Err(response) => response,
+    }
+}
+
+/// Handler for POST /users/{username}/games/{game_id}/sessions endpoint
+/// Logs a play session of a game on the signed-in user's shelf and returns
+/// the updated user
+pub async fn log_session_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    auth: AuthUser,
+    Path((username, game_id)): Path<(String, u64)>,
+    Json(request): Json<LogSessionRequest>,
+) -> JsonResponse {
+    let mut user = match owned_library(auth, &username) {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    let errors = collect_errors([
+        validate_session_length(&request),
+        request.platform.as_deref().map_or(Ok(()), validate_session_platform),
+        request.note.as_deref().map_or(Ok(()), validate_session_note),
+    ]);
+    if !errors.is_empty() {
+        return validation_error_response(errors);
+    }
+    if let Err(e) = user.log_session(game_id, request) {
+        return session_error_response(e);
+    }
+    match store.update(&mut user).await {
+        Ok(()) => user_json(StatusCode::CREATED, &user),
+        Err(e) => store_error_response(e),
+    }
+}
+
+/// Handler for DELETE /users/{username}/games/{game_id}/sessions/{id} endpoint
+/// Deletes one of the signed-in user's play sessions and returns the updated user
+pub async fn delete_session_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    auth: AuthUser,
+    Path((username, game_id, id)): Path<(String, u64, u64)>,
+) -> JsonResponse {
+    let mut user = match owned_library(auth, &username) {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    if let Err(e) = user.delete_session(game_id, id) {
+        return session_error_response(e);
+    }
+    match store.update(&mut user).await {
+        Ok(()) => user_json(StatusCode::OK, &user),
+        Err(e) => store_error_response(e),
+    }
+}
+
+/// Handler for GET /users/{username}/playtime endpoint
+/// Returns a user's time played in total and per game, platform and month
+pub async fn playtime_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    Path(username): Path<String>,
+) -> JsonResponse {
+    match find_user(store.as_ref(), &username).await {
+        Ok(user) => json_response(StatusCode::OK, &user.playtime()),
+        Err(response) => response,
+    }
+}
@@ -0,0 +1,22 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+};
+use std::sync::Arc;
+
+use super::users::find_user;
+use super::{json_response, JsonResponse};
+use crate::store::UserStore;
+
+/// Handler for GET /users/{username}/stats endpoint
+/// Returns breakdowns of a user's shelf by status, platform, genre and release
+/// year, with the completion rate, backlog size and yearly trends
+pub async fn user_stats_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    Path(username): Path<String>,
+) -> JsonResponse {
+    match find_user(store.as_ref(), &username).await {
+        Ok(user) => json_response(StatusCode::OK, &user.stats()),
+        Err(response) => response,
+    }
+}
@@ -0,0 +1,65 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::Utc;
+use playshelf_types::{SteamImportResponse, SteamOwnedGames, UserResponse, MAX_STEAM_GAMES};
+use std::sync::Arc;
+
+use super::auth::AuthUser;
+use super::users::owned_library;
+use super::{error_response, igdb_error_response, json_response, store_error_response, JsonResponse};
+use crate::igdb::manager::IGDBManager;
+use crate::store::UserStore;
+
+/// Handler for POST /users/{username}/import/steam endpoint
+/// Adds the games of a saved Steam `GetOwnedGames` response to the signed-in
+/// user's shelf with their playtime, listing the ones IGDB has no record of
+pub async fn import_steam_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    State(manager): State<Arc<IGDBManager>>,
+    auth: AuthUser,
+    Path(username): Path<String>,
+    Json(owned): Json<SteamOwnedGames>,
+) -> JsonResponse {
+    let mut user = match owned_library(auth, &username) {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    let owned = owned.response.games;
+    if owned.is_empty() {
+        return error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "The file lists no games; Steam leaves them out when the profile's game details are private",
+        );
+    }
+    if owned.len() > MAX_STEAM_GAMES {
+        return error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!(
+                "The file lists {} games; import at most {} at a time",
+                owned.len(),
+                MAX_STEAM_GAMES
+            ),
+        );
+    }
+
+    let app_ids: Vec<u64> = owned.iter().map(|game| game.appid).collect();
+    let games = match manager.games_by_steam_app_ids(&app_ids).await {
+        Ok(games) => games,
+        Err(e) => return igdb_error_response("Error matching Steam games", e),
+    };
+    let import = user.import_steam(owned, &games, Utc::now());
+    if let Err(e) = store.update(&mut user).await {
+        return store_error_response(e);
+    }
+    let response = SteamImportResponse {
+        imported: import.imported,
+        playtime_added: import.playtime_added,
+        skipped: import.skipped,
+        unmatched: import.unmatched,
+        user: UserResponse::from(&user),
+    };
+    json_response(StatusCode::OK, &response)
+}
@@ -0,0 +1,270 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::Utc;
+use playshelf_types::validation::{
+    collect_errors, validate_description, validate_name, validate_review, validate_status_date, validate_username,
+};
+use playshelf_types::{
+    AddGameRequest, ReviewRequest, ShelfEntry, ShelfQuery, StatusChangeRequest, UpdateProfileRequest, UserGamesResponse,
+    UserResponse, UsersResponse,
+};
+use std::sync::Arc;
+
+use super::auth::AuthUser;
+use super::{error_response, json_response, store_error_response, validation_error_response, JsonResponse};
+use crate::reviews::ReviewIndex;
+use crate::store::UserStore;
+use crate::user::User;
+
+/// Loads a user by username, or returns a 404 response
+pub(super) async fn find_user(store: &dyn UserStore, username: &str) -> Result<User, JsonResponse> {
+    match store.get_by_username(username).await {
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => Err(error_response(
+            StatusCode::NOT_FOUND,
+            format!("User '{}' not found", username),
+        )),
+        Err(e) => Err(store_error_response(e)),
+    }
+}
+
+/// Returns the signed-in user if they own the library of `username`, or a 403 response
+pub(super) fn owned_library(AuthUser(user): AuthUser, username: &str) -> Result<User, JsonResponse> {
+    if user.username != username {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            format!("Only {} can change their library", username),
+        ));
+    }
+    Ok(user)
+}
+
+pub(super) fn user_json(status: StatusCode, user: &User) -> JsonResponse {
+    json_response(status, &UserResponse::from(user))
+}
+
+/// Handler for GET /users endpoint
+/// Returns all users in the same `{"users": [...]}` shape as `sample_users.json`
+pub async fn list_users_handler(State(store): State<Arc<dyn UserStore>>) -> JsonResponse {
+    match store.list().await {
+        Ok(users) => json_response(
+            StatusCode::OK,
+            &UsersResponse {
+                users: users.iter().map(UserResponse::from).collect(),
+            },
+        ),
+        Err(e) => store_error_response(e),
+    }
+}
+
+/// Handler for GET /users/{username} endpoint
+pub async fn get_user_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    Path(username): Path<String>,
+) -> JsonResponse {
+    match find_user(store.as_ref(), &username).await {
+        Ok(user) => user_json(StatusCode::OK, &user),
+        Err(response) => response,
+    }
+}
+
+/// Handler for PATCH /users/{username} endpoint
+/// Changes the signed-in user's username, name or description and returns the updated user
+pub async fn update_profile_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    State(reviews): State<Arc<ReviewIndex>>,
+    AuthUser(mut user): AuthUser,
+    Path(username): Path<String>,
+    Json(request): Json<UpdateProfileRequest>,
+) -> JsonResponse {
+    if user.username != username {
+        return error_response(
+            StatusCode::FORBIDDEN,
+            format!("Only {} can change their profile", username),
+        );
+    }
+    let errors = collect_errors(
+        [
+            request.username.as_deref().map(validate_username),
+            request.name.as_deref().map(validate_name),
+            request.description.as_deref().map(validate_description),
+        ]
+        .into_iter()
+        .flatten(),
+    );
+    if !errors.is_empty() {
+        return validation_error_response(errors);
+    }
+
+    if let Some(username) = request.username {
+        user.username = username;
+    }
+    if let Some(name) = request.name {
+        user.name = name;
+    }
+    if let Some(description) = request.description {
+        user.description = description;
+    }
+    match store.update(&mut user).await {
+        Ok(()) => {
+            reviews.refresh_user(&user).await;
+            user_json(StatusCode::OK, &user)
+        }
+        Err(e) => store_error_response(e),
+    }
+}
+
+/// Handler for GET /users/{username}/games endpoint
+/// Returns the entries on a user's shelf, optionally only those with `?status=`
+pub async fn list_user_games_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    Path(username): Path<String>,
+    Query(query): Query<ShelfQuery>,
+) -> JsonResponse {
+    match find_user(store.as_ref(), &username).await {
+        Ok(user) => {
+            let games: Vec<ShelfEntry> = user
+                .get_games()
+                .iter()
+                .filter(|entry| query.status.is_none_or(|status| entry.status == status))
+                .cloned()
+                .collect();
+            json_response(
+                StatusCode::OK,
+                &UserGamesResponse {
+                    count: games.len(),
+                    games,
+                },
+            )
+        }
+        Err(response) => response,
+    }
+}
+
+/// Handler for POST /users/{username}/games endpoint
+/// Adds a game to the signed-in user's shelf and returns the updated user
+///
+/// The body is either a game with a status or a bare game, which goes to the
+/// backlog. The entry is added today; any other entry fields are ignored.
+pub async fn add_user_game_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    auth: AuthUser,
+    Path(username): Path<String>,
+    Json(request): Json<AddGameRequest>,
+) -> JsonResponse {
+    let mut user = match owned_library(auth, &username) {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    let today = Utc::now().date_naive();
+    let mut entry = ShelfEntry::new(request.game, today);
+    entry.set_status(request.status, today);
+    let game_id = entry.game.id;
+    if !user.add_game(entry) {
+        return error_response(
+            StatusCode::CONFLICT,
+            format!("Game {} is already in the library", game_id),
+        );
+    }
+    match store.update(&mut user).await {
+        Ok(()) => user_json(StatusCode::CREATED, &user),
+        Err(e) => store_error_response(e),
+    }
+}
+
+/// Handler for PUT /users/{username}/games/{game_id}/status endpoint
+/// Moves a game on the signed-in user's shelf to another status and returns the updated user
+pub async fn set_game_status_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    State(reviews): State<Arc<ReviewIndex>>,
+    auth: AuthUser,
+    Path((username, game_id)): Path<(String, u64)>,
+    Json(request): Json<StatusChangeRequest>,
+) -> JsonResponse {
+    let mut user = match owned_library(auth, &username) {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    let Some(entry) = user.entry_mut(game_id) else {
+        return error_response(
+            StatusCode::NOT_FOUND,
+            format!("Game {} is not in the library", game_id),
+        );
+    };
+    let today = Utc::now().date_naive();
+    let date = request.date.unwrap_or(today);
+    if let Err(error) = validate_status_date(entry, request.status, date, today) {
+        return validation_error_response(vec![error]);
+    }
+    entry.set_status(request.status, date);
+    match store.update(&mut user).await {
+        Ok(()) => {
+            reviews.refresh_user(&user).await;
+            user_json(StatusCode::OK, &user)
+        }
+        Err(e) => store_error_response(e),
+    }
+}
+
+/// Handler for PUT /users/{username}/games/{game_id}/review endpoint
+/// Replaces the signed-in user's rating and review of a game on their shelf
+/// and returns the updated user
+pub async fn set_game_review_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    State(reviews): State<Arc<ReviewIndex>>,
+    auth: AuthUser,
+    Path((username, game_id)): Path<(String, u64)>,
+    Json(request): Json<ReviewRequest>,
+) -> JsonResponse {
+    let mut user = match owned_library(auth, &username) {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    if let Some(Err(error)) = request.review.as_deref().map(validate_review) {
+        return validation_error_response(vec![error]);
+    }
+    let Some(entry) = user.entry_mut(game_id) else {
+        return error_response(
+            StatusCode::NOT_FOUND,
+            format!("Game {} is not in the library", game_id),
+        );
+    };
+    entry.set_review(request.rating, request.review, Utc::now().date_naive());
+    match store.update(&mut user).await {
+        Ok(()) => {
+            reviews.refresh_user(&user).await;
+            user_json(StatusCode::OK, &user)
+        }
+        Err(e) => store_error_response(e),
+    }
+}
+
+/// Handler for DELETE /users/{username}/games/{game_id} endpoint
+/// Removes a game from the signed-in user's library and returns the updated user
+pub async fn remove_user_game_handler(
+    State(store): State<Arc<dyn UserStore>>,
+    State(reviews): State<Arc<ReviewIndex>>,
+    auth: AuthUser,
+    Path((username, game_id)): Path<(String, u64)>,
+) -> JsonResponse {
+    let mut user = match owned_library(auth, &username) {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    if !user.remove_game(game_id) {
+        return error_response(
+            StatusCode::NOT_FOUND,
+            format!("Game {} is not in the library", game_id),
+        );
+    }
+    match store.update(&mut user).await {
+        Ok(()) => {
+            reviews.refresh_user(&user).await;
+            user_json(StatusCode::OK, &user)
+        }
+        Err(e) => store_error_response(e),
+    }
+}
@@ -0,0 +1,130 @@
+use playshelf_types::CacheStats;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// In-process cache whose entries expire after a fixed time-to-live
+///
+/// When the cache is full, expired entries are dropped first and then the
+/// oldest entry is evicted to make room.
+pub struct TtlCache<K, V> {
+    entries: Mutex<HashMap<K, (Instant, V)>>,
+    ttl: Duration,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    /// Creates an empty cache holding at most `capacity` entries for `ttl` each
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the cached value for `key` if it has not expired
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap();
+        let value = match entries.get(key) {
+            Some((inserted_at, value)) if inserted_at.elapsed() < self.ttl => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        };
+        let counter = if value.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    /// Looks up every key, returning the cached values and the keys that missed
+    pub fn get_many(&self, keys: &[K]) -> (HashMap<K, V>, Vec<K>) {
+        let mut found = HashMap::new();
+        let mut missing = Vec::new();
+        for key in keys {
+            match self.get(key) {
+                Some(value) => {
+                    found.insert(key.clone(), value);
+                }
+                None => missing.push(key.clone()),
+            }
+        }
+        (found, missing)
+    }
+
+    /// Stores `value` under `key`, evicting old entries if the cache is full
+    pub fn insert(&self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, (inserted_at, _)| inserted_at.elapsed() < self.ttl);
+            if entries.len() >= self.capacity {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (inserted_at, _))| *inserted_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, (Instant::now(), value));
+    }
+
+    /// Returns the hit/miss counters and number of entries
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.lock().unwrap().len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_counts_hits_and_misses() {
+        let cache = TtlCache::new(Duration::from_secs(60), 10);
+        assert_eq!(cache.get(&1), None);
+        cache.insert(1, "PC".to_string());
+        assert_eq!(cache.get(&1), Some("PC".to_string()));
+
+        let (found, missing) = cache.get_many(&[1, 2]);
+        assert_eq!(found.get(&1), Some(&"PC".to_string()));
+        assert_eq!(missing, vec![2]);
+
+        assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 2, entries: 1 });
+    }
+
+    #[test]
+    fn test_cache_entries_expire() {
+        let cache = TtlCache::new(Duration::ZERO, 10);
+        cache.insert(1, "PC".to_string());
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn test_cache_evicts_oldest_entry_when_full() {
+        let cache = TtlCache::new(Duration::from_secs(60), 2);
+        cache.insert(1, "one");
+        cache.insert(2, "two");
+        cache.insert(3, "three");
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&2), Some("two"));
+        assert_eq!(cache.get(&3), Some("three"));
+    }
+}
@@ -2,6 +2,11 @@ use serde::Deserialize;
 use std::time::SystemTime;
 use tokio::time::Duration;
 
+use super::secrets::{Secret, SecretProvider};
+
+/// Twitch OAuth2 token endpoint
+pub const TWITCH_TOKEN_URL: &str = "https://id.twitch.tv/oauth2/token";
+
 #[derive(Debug, Clone)]
 pub struct TwitchCredentials {
     client_id: String,
@@ -17,56 +22,84 @@ impl TwitchCredentials {
     pub fn get_expires_at(&self) -> SystemTime {
         self.expires_at
     }
+
+    /// Returns true if the access token expires within `margin` from now
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        SystemTime::now() + margin >= self.expires_at
+    }
+
+    /// Returns true if both credentials carry the same access token
+    pub fn has_same_token(&self, other: &TwitchCredentials) -> bool {
+        self.access_token == other.access_token
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct TwitchTokenResponse {
     access_token: String,
     expires_in: u64,
-    token_type: String,
 }
 
-async fn get_aws_secret(name: &str) -> String {
-    // Authenticate with AWS
-    let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-        .region("us-east-2") // Ensure this matches your SSM region
-        .load()
-        .await;
-    let client = aws_sdk_ssm::Client::new(&config);
-
-    let resp = client
-        .get_parameter()
-        .name(name)
-        .with_decryption(true) // Crucial for SecureString
-        .send()
-        .await
-        .expect("Failed to fetch parameter");
-
-    resp.parameter()
-        .and_then(|p| p.value())
-        .unwrap_or("")
-        .to_string()
-}
+/// Requests an app access token from the Twitch token endpoint at `token_url`
+/// using the client credentials flow
+pub async fn authenticate_twitch(
+    secrets: &dyn SecretProvider,
+    client: &reqwest::Client,
+    token_url: &str,
+) -> Result<TwitchCredentials, Box<dyn std::error::Error + Send + Sync>> {
+    let client_id = secrets.get_secret(Secret::TwitchClientId).await?;
+    let client_secret = secrets.get_secret(Secret::TwitchClientSecret).await?;
 
-pub async fn authenticate_twitch() -> Result<TwitchCredentials, Box<dyn std::error::Error + Send + Sync>> {
-    let client_id = get_aws_secret("/playshelf/prod/twitch-client-id").await;
-    let client_secret = get_aws_secret("/playshelf/prod/twitch-client-secret").await;
-
-    let url = format!(
-        "https://id.twitch.tv/oauth2/token?client_id={}&client_secret={}&grant_type=client_credentials",
-        client_id, client_secret
-    );
-
-    let response = reqwest::Client::new()
-        .post(&url)
+    let response = client
+        .post(token_url)
+        .query(&[
+            ("client_id", client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+            ("grant_type", "client_credentials"),
+        ])
         .send()
         .await?;
 
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Twitch authentication failed ({}): {}", status, error_text).into());
+    }
+
     let token_response: TwitchTokenResponse = response.json().await?;
-    
+
     Ok(TwitchCredentials {
         client_id,
         access_token: token_response.access_token,
         expires_at: SystemTime::now() + Duration::from_secs(token_response.expires_in),
-        })
-}
\ No newline at end of file
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials_expiring_in(secs: u64) -> TwitchCredentials {
+        TwitchCredentials {
+            client_id: "client".to_string(),
+            access_token: "token".to_string(),
+            expires_at: SystemTime::now() + Duration::from_secs(secs),
+        }
+    }
+
+    #[test]
+    fn test_expires_within() {
+        let credentials = credentials_expiring_in(120);
+        assert!(!credentials.expires_within(Duration::from_secs(60)));
+        assert!(credentials.expires_within(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn test_expired_token_expires_within_zero_margin() {
+        let credentials = TwitchCredentials {
+            expires_at: SystemTime::now() - Duration::from_secs(1),
+            ..credentials_expiring_in(0)
+        };
+        assert!(credentials.expires_within(Duration::ZERO));
+    }
+}
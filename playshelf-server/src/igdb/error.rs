@@ -0,0 +1,40 @@
+use std::fmt;
+
+/// Errors returned by `IGDBManager`
+#[derive(Debug)]
+pub enum IGDBError {
+    /// IGDB kept answering 429 Too Many Requests after all retries
+    RateLimited,
+    /// Twitch authentication failed or IGDB rejected the credentials
+    Auth(String),
+    /// IGDB answered with an error status
+    Upstream { status: u16, message: String },
+    /// The request could not be sent or the response could not be decoded
+    Http(reqwest::Error),
+}
+
+impl fmt::Display for IGDBError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IGDBError::RateLimited => write!(f, "IGDB rate limit exceeded"),
+            IGDBError::Auth(message) => write!(f, "IGDB authentication failed: {}", message),
+            IGDBError::Upstream { status, message } => write!(f, "IGDB API error ({}): {}", status, message),
+            IGDBError::Http(e) => write!(f, "IGDB request failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for IGDBError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IGDBError::Http(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for IGDBError {
+    fn from(e: reqwest::Error) -> Self {
+        IGDBError::Http(e)
+    }
+}
@@ -1,18 +1,131 @@
-use std::time::SystemTime;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
-use super::credentials::authenticate_twitch;
+use super::cache::TtlCache;
+use super::credentials::{authenticate_twitch, TwitchCredentials, TWITCH_TOKEN_URL};
+use super::error::IGDBError;
+use super::query::{ApicalypseQuery, SortOrder};
+use super::rate_limit::{backoff_delay, parse_retry_after, TokenBucket};
+use super::secrets::SecretProvider;
+use playshelf_types::{
+    CompanyCredit, DatePrecision, GameData, GameDetail, GameImage, GameWebsite, IGDBCacheStats,
+    PlatformReleaseDate, ReleaseDate, ReleaseRegion, SimilarGame,
+};
 use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, RwLock};
 
 /// Base URL for the IGDB API
-const IGDB_URL: &str = "https://api.igdb.com";
+pub const IGDB_URL: &str = "https://api.igdb.com";
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
-pub struct GameData {
-    pub id: u64,
-    pub name: String,
-    pub platforms: Vec<String>,
-    pub first_release_date: String,
-    pub genres: Vec<String>,
+/// Refresh the Twitch token when it is this close to expiring
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+/// Fields requested for every game
+const GAME_FIELDS: &[&str] = &[
+    "name",
+    "platforms",
+    "first_release_date",
+    "genres",
+    "cover.image_id",
+    "screenshots.image_id",
+    "release_dates.date",
+    "release_dates.category",
+    "release_dates.platform",
+    "release_dates.region",
+];
+
+/// Extra fields requested for the game detail endpoint
+const GAME_DETAIL_FIELDS: &[&str] = &[
+    "summary",
+    "storyline",
+    "involved_companies.company.name",
+    "involved_companies.developer",
+    "involved_companies.publisher",
+    "franchises.name",
+    "game_modes.name",
+    "themes.name",
+    "total_rating",
+    "total_rating_count",
+    "websites.url",
+    "websites.category",
+    "similar_games.name",
+    "similar_games.cover.image_id",
+];
+
+/// IGDB allows 4 requests per second per client
+const RATE_LIMIT_PER_SECOND: u32 = 4;
+/// Maximum number of attempts for a request that hits a 429, a 5xx or a network error
+const MAX_ATTEMPTS: u32 = 4;
+/// Backoff before the first retry; doubled for every further retry
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+
+/// How long platform and genre names are cached; they almost never change
+const NAME_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Default maximum number of cached platform or genre names
+const NAME_CACHE_CAPACITY: usize = 2_000;
+/// How long game list and search results are cached
+const GAME_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+/// Default maximum number of cached game list and search results
+const GAME_CACHE_CAPACITY: usize = 500;
+
+/// Maximum number of results IGDB returns for a single query
+pub const MAX_PAGE_SIZE: u32 = 500;
+
+/// Largest page of games a caller can ask for; one below `MAX_PAGE_SIZE`
+/// since one extra game is requested to find out whether another page follows
+pub const MAX_GAME_PAGE_SIZE: u32 = MAX_PAGE_SIZE - 1;
+
+/// IGDB external game source of Steam app IDs
+const STEAM_SOURCE: u64 = 1;
+
+/// Endpoints and cache sizes used by the IGDB manager
+///
+/// The defaults point at the real services; tests point them at a local mock.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IGDBConfig {
+    /// Base URL of the IGDB API
+    pub igdb_url: String,
+    /// Full URL of the Twitch OAuth2 token endpoint
+    pub token_url: String,
+    /// Maximum number of cached platform or genre names; 0 disables the cache
+    pub name_cache_capacity: usize,
+    /// Maximum number of cached game results and game details; 0 disables the cache
+    pub game_cache_capacity: usize,
+}
+
+impl Default for IGDBConfig {
+    fn default() -> Self {
+        Self {
+            igdb_url: IGDB_URL.to_string(),
+            token_url: TWITCH_TOKEN_URL.to_string(),
+            name_cache_capacity: NAME_CACHE_CAPACITY,
+            game_cache_capacity: GAME_CACHE_CAPACITY,
+        }
+    }
+}
+
+/// Which slice of the results to return
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    /// Maximum number of games to return, at most `MAX_GAME_PAGE_SIZE`
+    pub limit: u32,
+    /// Number of games to skip
+    pub offset: u32,
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Self { limit: 20, offset: 0 }
+    }
+}
+
+/// One page of games along with whether more results follow it
+#[derive(Debug, Clone, PartialEq)]
+pub struct GamePage {
+    pub games: Vec<GameData>,
+    pub has_more: bool,
 }
 
 /// Represents a game from the IGDB API
@@ -26,12 +139,167 @@ struct Game {
     /// List of platform IDs this game is available on
     #[serde(skip_serializing_if = "Option::is_none")]
     platforms: Option<Vec<u64>>,
-    /// Unix timestamp of the first release date 
+    /// Unix timestamp of the first release date
     #[serde(skip_serializing_if = "Option::is_none")]
-    first_release_date: Option<u64>,
+    first_release_date: Option<i64>,
     /// List of genre IDs associated with this game
     #[serde(skip_serializing_if = "Option::is_none")]
     genres: Option<Vec<u64>>,
+    /// Cover art, expanded to its image ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cover: Option<Image>,
+    /// Screenshots, expanded to their image IDs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    screenshots: Option<Vec<Image>>,
+    /// Release dates per platform and region, expanded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    release_dates: Option<Vec<ReleaseDateEntry>>,
+}
+
+/// Represents a release date of a game on one platform from the IGDB API
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct ReleaseDateEntry {
+    id: u64,
+    /// Unix timestamp of the release, the start of the period for inexact dates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<i64>,
+    /// IGDB date category enum value, i.e. how precise `date` is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category: Option<u64>,
+    /// Platform ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    platform: Option<u64>,
+    /// IGDB region enum value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<u64>,
+}
+
+impl ReleaseDateEntry {
+    /// The release date, or `None` if it is unknown or still to be announced
+    fn release_date(&self) -> Option<ReleaseDate> {
+        let precision = DatePrecision::from_igdb_category(self.category.unwrap_or_default())?;
+        ReleaseDate::from_timestamp(self.date?, precision)
+    }
+}
+
+/// Represents a cover or screenshot from the IGDB API
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct Image {
+    /// Unique identifier for the image
+    id: u64,
+    /// ID used to build the image URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image_id: Option<String>,
+}
+
+/// Represents a game from the IGDB API with the extra detail fields expanded
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct GameDetails {
+    #[serde(flatten)]
+    game: Game,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    storyline: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    involved_companies: Option<Vec<InvolvedCompany>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    franchises: Option<Vec<NamedEntity>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    game_modes: Option<Vec<NamedEntity>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    themes: Option<Vec<NamedEntity>>,
+    /// Average of critic and user ratings, from 0 to 100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_rating: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_rating_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    websites: Option<Vec<Website>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    similar_games: Option<Vec<SimilarGameRef>>,
+}
+
+/// Represents an involved company from the IGDB API, with the company expanded
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct InvolvedCompany {
+    id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    company: Option<NamedEntity>,
+    #[serde(default)]
+    developer: bool,
+    #[serde(default)]
+    publisher: bool,
+}
+
+/// Represents any IGDB resource expanded to just its name (franchise, theme, ...)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct NamedEntity {
+    id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+/// Represents a website from the IGDB API
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct Website {
+    id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    /// IGDB website category enum value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category: Option<u64>,
+}
+
+/// Represents a similar game from the IGDB API, expanded to its name and cover
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct SimilarGameRef {
+    id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cover: Option<Image>,
+}
+
+/// Maps an IGDB website category to a readable label
+fn website_category_label(category: u64) -> &'static str {
+    match category {
+        1 => "official",
+        2 => "wikia",
+        3 => "wikipedia",
+        4 => "facebook",
+        5 => "twitter",
+        6 => "twitch",
+        8 => "instagram",
+        9 => "youtube",
+        10 => "iphone",
+        11 => "ipad",
+        12 => "android",
+        13 => "steam",
+        14 => "reddit",
+        15 => "itch",
+        16 => "epicgames",
+        17 => "gog",
+        18 => "discord",
+        _ => "other",
+    }
+}
+
+/// Names of the expanded entities, skipping those without a name
+fn entity_names(entities: Option<Vec<NamedEntity>>) -> Vec<String> {
+    entities.into_iter().flatten().filter_map(|entity| entity.name).collect()
+}
+
+/// Represents a game's ID on another service, such as a Steam app ID, from the IGDB API
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct ExternalGame {
+    id: u64,
+    /// ID of the IGDB game
+    #[serde(skip_serializing_if = "Option::is_none")]
+    game: Option<u64>,
+    /// ID of the game on the other service
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uid: Option<String>,
 }
 
 /// Represents a platform from the IGDB API
@@ -58,105 +326,268 @@ struct Genre {
 /// 
 /// This struct handles authentication and provides methods to query
 /// games, platforms, genres, and search functionality.
+///
+/// The manager is meant to be shared behind an `Arc`. It keeps track of the
+/// Twitch token expiry itself and transparently refreshes the token before it
+/// expires, or after IGDB rejects it with a 401.
 pub struct IGDBManager {
-    /// Twitch Client ID for IGDB API authentication
-    client_id: String,
-    /// Access token for IGDB API authentication
-    access_token: String,
+    /// Base URL of the IGDB API
+    igdb_url: String,
+    /// URL of the Twitch OAuth2 token endpoint
+    token_url: String,
+    /// Source of the Twitch Client ID and secret
+    secrets: Arc<dyn SecretProvider>,
+    /// Twitch credentials (Client ID and access token) for IGDB API authentication
+    credentials: RwLock<Option<TwitchCredentials>>,
+    /// Held while refreshing so that concurrent requests trigger a single refresh
+    refresh_lock: Mutex<()>,
     /// Reusable HTTP client for making requests
     client: reqwest::Client,
+    /// Keeps requests under the IGDB rate limit across all handlers
+    rate_limiter: TokenBucket,
+    /// Platform ID to name cache
+    platform_names: TtlCache<u64, String>,
+    /// Genre ID to name cache
+    genre_names: TtlCache<u64, String>,
+    /// Game list and search results, keyed by the request body
+    game_results: TtlCache<String, Vec<GameData>>,
+    /// Game detail records, keyed by game ID
+    game_details: TtlCache<u64, GameDetail>,
+}
+
+/// Parses the `Retry-After` header of a response, if it is given in seconds
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    parse_retry_after(response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?)
 }
 
 impl IGDBManager {
-    /// Creates a new IGDBManager instance
-    pub fn new() -> Self {
+    /// Creates a new IGDBManager instance reading Twitch secrets from `secrets`
+    pub fn new(secrets: Arc<dyn SecretProvider>) -> Self {
+        Self::with_config(secrets, IGDBConfig::default())
+    }
+
+    /// Creates a new IGDBManager instance with the given endpoints and cache sizes
+    pub fn with_config(secrets: Arc<dyn SecretProvider>, config: IGDBConfig) -> Self {
         Self {
-            client_id: String::new(),
-            access_token: String::new(),
+            igdb_url: config.igdb_url,
+            token_url: config.token_url,
+            secrets,
+            credentials: RwLock::new(None),
+            refresh_lock: Mutex::new(()),
             client: reqwest::Client::new(),
+            rate_limiter: TokenBucket::new(RATE_LIMIT_PER_SECOND, RATE_LIMIT_PER_SECOND as f64),
+            platform_names: TtlCache::new(NAME_CACHE_TTL, config.name_cache_capacity),
+            genre_names: TtlCache::new(NAME_CACHE_TTL, config.name_cache_capacity),
+            game_results: TtlCache::new(GAME_CACHE_TTL, config.game_cache_capacity),
+            game_details: TtlCache::new(GAME_CACHE_TTL, config.game_cache_capacity),
         }
     }
 
-    pub async fn authenticate(&mut self) -> Result<SystemTime, Box<dyn std::error::Error + Send + Sync>> {
-        let twtich_credentials = authenticate_twitch().await.expect("Failed to authenticate with Twitch");
-        let (client_id, access_token) = twtich_credentials.get_client_id_and_access_token();
-        self.client_id = client_id;
-        self.access_token = access_token;
-        Ok(twtich_credentials.get_expires_at())
+    /// Returns the hit/miss counters of the response caches
+    pub fn cache_stats(&self) -> IGDBCacheStats {
+        IGDBCacheStats {
+            platforms: self.platform_names.stats(),
+            genres: self.genre_names.stats(),
+            games: self.game_results.stats(),
+            game_details: self.game_details.stats(),
+        }
     }
 
-    /// Makes an authenticated POST request to the IGDB API
-    async fn make_request(
+    /// Fetches a fresh Twitch token and returns its expiry time
+    pub async fn authenticate(&self) -> Result<SystemTime, IGDBError> {
+        let _guard = self.refresh_lock.lock().await;
+        let credentials = self.refresh_credentials().await?;
+        Ok(credentials.get_expires_at())
+    }
+
+    /// Requests a new token from Twitch and stores it. Callers must hold `refresh_lock`.
+    async fn refresh_credentials(&self) -> Result<TwitchCredentials, IGDBError> {
+        let credentials = authenticate_twitch(self.secrets.as_ref(), &self.client, &self.token_url)
+            .await
+            .map_err(|e| IGDBError::Auth(e.to_string()))?;
+        let expires_at = chrono::DateTime::<chrono::Utc>::from(credentials.get_expires_at());
+        println!("Refreshed Twitch token, expires at: {}", expires_at.format("%Y-%m-%d %H:%M:%S UTC"));
+        *self.credentials.write().await = Some(credentials.clone());
+        Ok(credentials)
+    }
+
+    /// Returns the cached credentials if they are not about to expire
+    async fn valid_credentials(&self) -> Option<TwitchCredentials> {
+        self.credentials
+            .read()
+            .await
+            .as_ref()
+            .filter(|credentials| !credentials.expires_within(TOKEN_REFRESH_MARGIN))
+            .cloned()
+    }
+
+    /// Returns usable credentials, refreshing them first if they are missing or about to expire
+    async fn current_credentials(&self) -> Result<TwitchCredentials, IGDBError> {
+        if let Some(credentials) = self.valid_credentials().await {
+            return Ok(credentials);
+        }
+
+        let _guard = self.refresh_lock.lock().await;
+        // Another request may have refreshed the token while we were waiting for the lock
+        if let Some(credentials) = self.valid_credentials().await {
+            return Ok(credentials);
+        }
+        self.refresh_credentials().await
+    }
+
+    /// Refreshes the credentials after IGDB rejected `rejected`, unless another request already did
+    async fn credentials_after_unauthorized(
         &self,
-        endpoint: &str,
+        rejected: &TwitchCredentials,
+    ) -> Result<TwitchCredentials, IGDBError> {
+        let _guard = self.refresh_lock.lock().await;
+        if let Some(credentials) = self.credentials.read().await.as_ref()
+            && !credentials.has_same_token(rejected)
+        {
+            return Ok(credentials.clone());
+        }
+        self.refresh_credentials().await
+    }
+
+    /// Sends a single POST request to the IGDB API with the given credentials
+    async fn send_request(
+        &self,
+        url: &str,
+        credentials: &TwitchCredentials,
         body: String,
-    ) -> Result<reqwest::Response, Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!("{}/{}", IGDB_URL, endpoint);
-        let response = self
-            .client
-            .post(&url)
-            .header("Client-ID", &self.client_id)
-            .header("Authorization", format!("Bearer {}", &self.access_token))
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let (client_id, access_token) = credentials.get_client_id_and_access_token();
+        self.client
+            .post(url)
+            .header("Client-ID", client_id)
+            .header("Authorization", format!("Bearer {}", access_token))
             .body(body)
             .send()
-            .await?;
-        
-        // Check if the response is an error
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("IGDB API error ({}): {}", status, error_text).into());
-        }
-        
-        Ok(response)
+            .await
     }
 
-    /// Formats a list of IDs into an IGDB API query format
-    fn format_ids(&self, ids: &[u64]) -> String {
-        if ids.is_empty() {
-            return String::new();
+    /// Makes an authenticated, rate limited POST request to the IGDB API
+    ///
+    /// If IGDB rejects the token with a 401, the token is refreshed and the
+    /// request is retried once. 429s, 5xx responses and network errors are
+    /// retried with jittered exponential backoff, up to `MAX_ATTEMPTS` times.
+    async fn make_request(
+        &self,
+        endpoint: &str,
+        body: String,
+    ) -> Result<reqwest::Response, IGDBError> {
+        let url = format!("{}/{}", self.igdb_url.trim_end_matches('/'), endpoint);
+        let mut credentials = self.current_credentials().await?;
+        let mut refreshed_token = false;
+        let mut attempt = 0;
+
+        loop {
+            self.rate_limiter.acquire().await;
+            let can_retry = attempt + 1 < MAX_ATTEMPTS;
+
+            let response = match self.send_request(&url, &credentials, body.clone()).await {
+                Ok(response) => response,
+                Err(e) if can_retry && (e.is_connect() || e.is_timeout()) => {
+                    tokio::time::sleep(backoff_delay(RETRY_BASE_DELAY, attempt)).await;
+                    attempt += 1;
+                    continue;
+                }
+                Err(e) => return Err(IGDBError::Http(e)),
+            };
+
+            let status = response.status();
+            if status.is_success() {
+                return Ok(response);
+            }
+
+            if status == reqwest::StatusCode::UNAUTHORIZED && !refreshed_token {
+                credentials = self.credentials_after_unauthorized(&credentials).await?;
+                refreshed_token = true;
+                continue;
+            }
+
+            let retryable = status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+            if retryable && can_retry {
+                let delay = retry_after(&response).unwrap_or_else(|| backoff_delay(RETRY_BASE_DELAY, attempt));
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+                continue;
+            }
+
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(match status {
+                reqwest::StatusCode::TOO_MANY_REQUESTS => IGDBError::RateLimited,
+                reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => IGDBError::Auth(error_text),
+                _ => IGDBError::Upstream {
+                    status: status.as_u16(),
+                    message: error_text,
+                },
+            });
         }
-        format!(
-            "({})",
-            ids.iter()
-                .map(|id| id.to_string())
-                .collect::<Vec<_>>()
-                .join(",")
-        )
     }
 
     /// Retrieves platform information by a list of platform IDs
-    async fn get_platforms_by_ids(&self, ids: Vec<u64>) -> Result<Vec<Platform>, Box<dyn std::error::Error + Send + Sync>> {
-        if ids.is_empty() {
-            return Ok(Vec::new());
+    async fn get_platforms_by_ids(&self, ids: Vec<u64>) -> Result<Vec<Platform>, IGDBError> {
+        // IGDB returns at most `MAX_PAGE_SIZE` records per request
+        let mut platforms = Vec::with_capacity(ids.len());
+        for chunk in ids.chunks(MAX_PAGE_SIZE as usize) {
+            let body = ApicalypseQuery::new()
+                .fields(&["name"])
+                .where_in("id", chunk)
+                .limit(chunk.len() as u32)
+                .build();
+            let response = self.make_request("v4/platforms", body).await?;
+            platforms.extend(response.json::<Vec<Platform>>().await?);
         }
-
-        let ids_str = self.format_ids(&ids);
-        let body = format!("fields name; where id = {};", ids_str);
-        let response = self.make_request("v4/platforms", body).await?;
-        let platforms: Vec<Platform> = response.json().await?;
         Ok(platforms)
     }
 
     /// Retrieves genre information by a list of genre IDs
-    async fn get_genres_by_ids(&self, ids: Vec<u64>) -> Result<Vec<Genre>, Box<dyn std::error::Error + Send + Sync>> {
-        if ids.is_empty() {
-            return Ok(Vec::new());
+    async fn get_genres_by_ids(&self, ids: Vec<u64>) -> Result<Vec<Genre>, IGDBError> {
+        // IGDB returns at most `MAX_PAGE_SIZE` records per request
+        let mut genres = Vec::with_capacity(ids.len());
+        for chunk in ids.chunks(MAX_PAGE_SIZE as usize) {
+            let body = ApicalypseQuery::new()
+                .fields(&["name"])
+                .where_in("id", chunk)
+                .limit(chunk.len() as u32)
+                .build();
+            let response = self.make_request("v4/genres", body).await?;
+            genres.extend(response.json::<Vec<Genre>>().await?);
         }
-
-        let ids_str = self.format_ids(&ids);
-        let body = format!("fields name; where id = {};", ids_str);
-        let response = self.make_request("v4/genres", body).await?;
-        let genres: Vec<Genre> = response.json().await?;
         Ok(genres)
     }
 
+    /// Returns the names of the given platforms, fetching only those not already cached
+    async fn get_platform_names(&self, ids: Vec<u64>) -> Result<HashMap<u64, String>, IGDBError> {
+        let (mut names, missing) = self.platform_names.get_many(&ids);
+        for platform in self.get_platforms_by_ids(missing).await? {
+            if let Some(name) = platform.name {
+                self.platform_names.insert(platform.id, name.clone());
+                names.insert(platform.id, name);
+            }
+        }
+        Ok(names)
+    }
+
+    /// Returns the names of the given genres, fetching only those not already cached
+    async fn get_genre_names(&self, ids: Vec<u64>) -> Result<HashMap<u64, String>, IGDBError> {
+        let (mut names, missing) = self.genre_names.get_many(&ids);
+        for genre in self.get_genres_by_ids(missing).await? {
+            if let Some(name) = genre.name {
+                self.genre_names.insert(genre.id, name.clone());
+                names.insert(genre.id, name);
+            }
+        }
+        Ok(names)
+    }
+
     /// Converts a Game to GameData using pre-fetched platform and genre maps
     fn game_data_from_game_with_maps(
         &self,
         game: &Game,
-        platform_map: &std::collections::HashMap<u64, String>,
-        genre_map: &std::collections::HashMap<u64, String>,
+        platform_map: &HashMap<u64, String>,
+        genre_map: &HashMap<u64, String>,
     ) -> GameData {
         let platforms: Vec<String> = game
             .platforms
@@ -178,16 +609,53 @@ impl IGDBManager {
             })
             .unwrap_or_default();
 
+        let entries = game.release_dates.as_deref().unwrap_or_default();
+        // IGDB only stores the timestamp of the first release; its precision
+        // comes from the release date entry it was taken from
+        let first_release_date = game.first_release_date.and_then(|timestamp| {
+            let precision = entries
+                .iter()
+                .find(|entry| entry.date == Some(timestamp))
+                .and_then(|entry| DatePrecision::from_igdb_category(entry.category.unwrap_or_default()))
+                .unwrap_or(DatePrecision::Day);
+            ReleaseDate::from_timestamp(timestamp, precision)
+        });
+        let mut release_dates: Vec<PlatformReleaseDate> = entries
+            .iter()
+            .filter_map(|entry| {
+                Some(PlatformReleaseDate {
+                    platform: platform_map.get(&entry.platform?)?.clone(),
+                    region: entry.region.and_then(ReleaseRegion::from_igdb_region),
+                    date: entry.release_date(),
+                })
+            })
+            .collect();
+        // Earliest first, with dates still to be announced last
+        release_dates.sort_by_key(|release| (release.date.is_none(), release.date.map(|date| date.date)));
+
         GameData {
-            id: game.id.clone(),
+            id: game.id,
             name: game.name.clone().unwrap_or_default(),
             platforms,
-            first_release_date: game.first_release_date.clone().unwrap_or_default().to_string(),
+            first_release_date,
             genres,
+            release_dates,
+            cover: game
+                .cover
+                .as_ref()
+                .and_then(|cover| cover.image_id.as_deref())
+                .map(GameImage::from_image_id),
+            screenshots: game
+                .screenshots
+                .iter()
+                .flatten()
+                .filter_map(|screenshot| screenshot.image_id.as_deref())
+                .map(GameImage::from_image_id)
+                .collect(),
         }
     }
     
-    async fn games_data_from_games(&self, games: Vec<Game>) -> Result<Vec<GameData>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn games_data_from_games(&self, games: Vec<Game>) -> Result<Vec<GameData>, IGDBError> {
         // Collect all unique platform and genre IDs
         let mut platform_ids = std::collections::HashSet::new();
         let mut genre_ids = std::collections::HashSet::new();
@@ -196,30 +664,22 @@ impl IGDBManager {
             if let Some(platforms) = &game.platforms {
                 platform_ids.extend(platforms.iter());
             }
+            if let Some(release_dates) = &game.release_dates {
+                platform_ids.extend(release_dates.iter().filter_map(|release| release.platform.as_ref()));
+            }
             if let Some(genres) = &game.genres {
                 genre_ids.extend(genres.iter());
             }
         }
 
-        // Fetch all platforms and genres in parallel
+        // Look up all platform and genre names in parallel
         let (platforms_result, genres_result) = tokio::join!(
-            self.get_platforms_by_ids(platform_ids.into_iter().collect()),
-            self.get_genres_by_ids(genre_ids.into_iter().collect())
+            self.get_platform_names(platform_ids.into_iter().collect()),
+            self.get_genre_names(genre_ids.into_iter().collect())
         );
 
-        let platforms = platforms_result?;
-        let genres = genres_result?;
-
-        // Build lookup maps
-        let platform_map: std::collections::HashMap<u64, String> = platforms
-            .into_iter()
-            .filter_map(|p| p.name.map(|name| (p.id, name)))
-            .collect();
-        
-        let genre_map: std::collections::HashMap<u64, String> = genres
-            .into_iter()
-            .filter_map(|g| g.name.map(|name| (g.id, name)))
-            .collect();
+        let platform_map = platforms_result?;
+        let genre_map = genres_result?;
 
         // Convert games to GameData using the maps
         let games_data: Vec<GameData> = games
@@ -230,19 +690,176 @@ impl IGDBManager {
         Ok(games_data)
     }
 
-    /// Retrieves all games from the IGDB API
-    pub async fn get_games(&self) -> Result<Vec<GameData>, Box<dyn std::error::Error + Send + Sync>> {
-        let body = "fields name,platforms,first_release_date,genres;".to_string();
-        let response = self.make_request("v4/games", body).await?;
+    /// Runs a query against `v4/games`, serving repeated queries from the cache
+    async fn query_games(&self, body: String) -> Result<Vec<GameData>, IGDBError> {
+        if let Some(games) = self.game_results.get(&body) {
+            return Ok(games);
+        }
+        let response = self.make_request("v4/games", body.clone()).await?;
         let games: Vec<Game> = response.json().await?;
-        self.games_data_from_games(games).await
+        let games_data = self.games_data_from_games(games).await?;
+        self.game_results.insert(body, games_data.clone());
+        Ok(games_data)
     }
 
-    /// Searches for games by query string
-    pub async fn search_games(&self, query: String) -> Result<Vec<GameData>, Box<dyn std::error::Error + Send + Sync>> {
-        let body = format!("search \"{}\"; fields name,platforms,first_release_date,genres;", query);
+    /// Retrieves the extended record for one game, or `None` if IGDB does not know the ID
+    pub async fn get_game_detail(&self, id: u64) -> Result<Option<GameDetail>, IGDBError> {
+        if let Some(detail) = self.game_details.get(&id) {
+            return Ok(Some(detail));
+        }
+
+        let body = ApicalypseQuery::new()
+            .fields(GAME_FIELDS)
+            .fields(GAME_DETAIL_FIELDS)
+            .where_in("id", &[id])
+            .limit(1)
+            .build();
         let response = self.make_request("v4/games", body).await?;
-        let games: Vec<Game> = response.json().await?;
-        self.games_data_from_games(games).await
+        let details: Vec<GameDetails> = response.json().await?;
+        let Some(details) = details.into_iter().next() else {
+            return Ok(None);
+        };
+
+        let game = self
+            .games_data_from_games(vec![details.game])
+            .await?
+            .into_iter()
+            .next()
+            .expect("one game in, one game out");
+        let detail = GameDetail {
+            game,
+            summary: details.summary,
+            storyline: details.storyline,
+            involved_companies: details
+                .involved_companies
+                .into_iter()
+                .flatten()
+                .filter_map(|involved| {
+                    Some(CompanyCredit {
+                        name: involved.company?.name?,
+                        developer: involved.developer,
+                        publisher: involved.publisher,
+                    })
+                })
+                .collect(),
+            franchises: entity_names(details.franchises),
+            game_modes: entity_names(details.game_modes),
+            themes: entity_names(details.themes),
+            rating: details.total_rating,
+            rating_count: details.total_rating_count,
+            websites: details
+                .websites
+                .into_iter()
+                .flatten()
+                .filter_map(|website| {
+                    Some(GameWebsite {
+                        url: website.url?,
+                        category: website_category_label(website.category.unwrap_or_default()).to_string(),
+                    })
+                })
+                .collect(),
+            similar_games: details
+                .similar_games
+                .into_iter()
+                .flatten()
+                .map(|similar| SimilarGame {
+                    id: similar.id,
+                    name: similar.name.unwrap_or_default(),
+                    cover: similar
+                        .cover
+                        .and_then(|cover| cover.image_id)
+                        .map(|image_id| GameImage::from_image_id(&image_id)),
+                })
+                .collect(),
+        };
+
+        self.game_details.insert(id, detail.clone());
+        Ok(Some(detail))
+    }
+
+    /// Runs `query` for one page of games
+    ///
+    /// One extra game is requested to find out whether another page follows.
+    async fn query_game_page(&self, query: ApicalypseQuery, page: Page) -> Result<GamePage, IGDBError> {
+        let limit = page.limit.min(MAX_GAME_PAGE_SIZE);
+        let body = query.limit(limit + 1).offset(page.offset).build();
+        let mut games = self.query_games(body).await?;
+        let has_more = games.len() > limit as usize;
+        games.truncate(limit as usize);
+        Ok(GamePage { games, has_more })
+    }
+
+    /// Retrieves a page of games from the IGDB API
+    pub async fn get_games(&self, page: Page) -> Result<GamePage, IGDBError> {
+        let query = ApicalypseQuery::new().fields(GAME_FIELDS);
+        self.query_game_page(query, page).await
+    }
+
+    /// Searches for games by query string, returning one page of results
+    pub async fn search_games(&self, query: String, page: Page) -> Result<GamePage, IGDBError> {
+        let query = ApicalypseQuery::new().fields(GAME_FIELDS).search(&query);
+        self.query_game_page(query, page).await
+    }
+
+    /// Looks up games by IGDB ID, leaving out the IDs IGDB does not know
+    pub async fn get_games_by_ids(&self, ids: &[u64]) -> Result<HashMap<u64, GameData>, IGDBError> {
+        let mut ids = ids.to_vec();
+        ids.sort_unstable();
+        ids.dedup();
+        let mut games = HashMap::new();
+        // IGDB returns at most `MAX_PAGE_SIZE` records per request
+        for chunk in ids.chunks(MAX_PAGE_SIZE as usize) {
+            let body = ApicalypseQuery::new()
+                .fields(GAME_FIELDS)
+                .where_in("id", chunk)
+                .limit(chunk.len() as u32)
+                .build();
+            games.extend(self.query_games(body).await?.into_iter().map(|game| (game.id, game)));
+        }
+        Ok(games)
     }
-}
\ No newline at end of file
+
+    /// Looks up the IGDB games of Steam app IDs, leaving out the ones IGDB does not know
+    ///
+    /// IDs are looked up `MAX_PAGE_SIZE` at a time, so whole libraries take
+    /// a few requests rather than one per game. IGDB can hold several records
+    /// for one app, so each lookup is paged until IGDB returns a short page.
+    pub async fn games_by_steam_app_ids(&self, app_ids: &[u64]) -> Result<HashMap<u64, GameData>, IGDBError> {
+        let mut game_ids = HashMap::new();
+        for chunk in app_ids.chunks(MAX_PAGE_SIZE as usize) {
+            let uids: Vec<String> = chunk.iter().map(u64::to_string).collect();
+            let mut offset = 0;
+            loop {
+                let body = ApicalypseQuery::new()
+                    .fields(&["game", "uid"])
+                    .where_eq_number("external_game_source", STEAM_SOURCE)
+                    .where_in_strings("uid", &uids)
+                    // A stable order, so pages neither repeat nor skip records
+                    .sort("id", SortOrder::Asc)
+                    .limit(MAX_PAGE_SIZE)
+                    .offset(offset)
+                    .build();
+                let response = self.make_request("v4/external_games", body).await?;
+                let external_games: Vec<ExternalGame> = response.json().await?;
+                let count = external_games.len();
+                for external in external_games {
+                    let app_id = external.uid.and_then(|uid| uid.parse::<u64>().ok());
+                    if let (Some(app_id), Some(game_id)) = (app_id, external.game) {
+                        game_ids.insert(app_id, game_id);
+                    }
+                }
+                if count < MAX_PAGE_SIZE as usize {
+                    break;
+                }
+                offset += MAX_PAGE_SIZE;
+            }
+        }
+
+        let ids: Vec<u64> = game_ids.values().copied().collect();
+        let games = self.get_games_by_ids(&ids).await?;
+        Ok(game_ids
+            .into_iter()
+            .filter_map(|(app_id, game_id)| Some((app_id, games.get(&game_id)?.clone())))
+            .collect())
+    }
+}
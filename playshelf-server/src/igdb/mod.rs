@@ -1,2 +1,9 @@
+pub mod cache;
+pub mod error;
 pub mod manager;
-mod credentials;
\ No newline at end of file
+pub mod query;
+pub mod secrets;
+mod credentials;
+mod rate_limit;
+
+pub use credentials::TWITCH_TOKEN_URL;
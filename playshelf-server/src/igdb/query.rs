@@ -0,0 +1,175 @@
+/// Sort direction of an Apicalypse query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Builder for Apicalypse request bodies sent to the IGDB API
+///
+/// String values passed to `search` and `where_eq` are quoted and escaped, so
+/// user input cannot terminate the string or inject extra clauses.
+#[derive(Debug, Clone, Default)]
+pub struct ApicalypseQuery {
+    fields: Vec<String>,
+    search: Option<String>,
+    conditions: Vec<String>,
+    sort: Option<(String, SortOrder)>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
+impl ApicalypseQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds fields to return; `*` and expanded fields such as `cover.image_id` are allowed
+    pub fn fields(mut self, fields: &[&str]) -> Self {
+        self.fields.extend(fields.iter().map(|field| field.to_string()));
+        self
+    }
+
+    /// Searches for `term` (a user supplied string)
+    pub fn search(mut self, term: &str) -> Self {
+        self.search = Some(quote(term));
+        self
+    }
+
+    /// Filters on `field` being any of `ids`
+    pub fn where_in(mut self, field: &str, ids: &[u64]) -> Self {
+        let ids = ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+        self.conditions.push(format!("{} = ({})", field, ids));
+        self
+    }
+
+    /// Filters on `field` being any of the strings `values` (user supplied strings)
+    pub fn where_in_strings(mut self, field: &str, values: &[String]) -> Self {
+        let values = values.iter().map(|value| quote(value)).collect::<Vec<_>>().join(",");
+        self.conditions.push(format!("{} = ({})", field, values));
+        self
+    }
+
+    /// Filters on `field` being equal to the string `value` (a user supplied string)
+    pub fn where_eq(mut self, field: &str, value: &str) -> Self {
+        self.conditions.push(format!("{} = {}", field, quote(value)));
+        self
+    }
+
+    /// Filters on `field` being equal to the number `value`
+    pub fn where_eq_number(mut self, field: &str, value: u64) -> Self {
+        self.conditions.push(format!("{} = {}", field, value));
+        self
+    }
+
+    /// Sorts the results by `field`
+    pub fn sort(mut self, field: &str, order: SortOrder) -> Self {
+        self.sort = Some((field.to_string(), order));
+        self
+    }
+
+    /// Limits the number of results
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skips the first `offset` results
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Renders the request body
+    pub fn build(&self) -> String {
+        let mut clauses = Vec::new();
+        if !self.fields.is_empty() {
+            clauses.push(format!("fields {};", self.fields.join(",")));
+        }
+        if let Some(search) = &self.search {
+            clauses.push(format!("search {};", search));
+        }
+        if !self.conditions.is_empty() {
+            clauses.push(format!("where {};", self.conditions.join(" & ")));
+        }
+        if let Some((field, order)) = &self.sort {
+            let order = match order {
+                SortOrder::Asc => "asc",
+                SortOrder::Desc => "desc",
+            };
+            clauses.push(format!("sort {} {};", field, order));
+        }
+        if let Some(limit) = self.limit {
+            clauses.push(format!("limit {};", limit));
+        }
+        if let Some(offset) = self.offset {
+            clauses.push(format!("offset {};", offset));
+        }
+        clauses.join(" ")
+    }
+}
+
+/// Quotes `value` as an Apicalypse string literal
+///
+/// Backslashes and double quotes are escaped; control characters (including
+/// newlines) are dropped since they never belong in a search term.
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c if c.is_control() => {}
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_full_query() {
+        let body = ApicalypseQuery::new()
+            .fields(&["name", "platforms"])
+            .search("Zelda")
+            .where_in("platforms", &[6, 130])
+            .where_eq_number("category", 0)
+            .sort("first_release_date", SortOrder::Desc)
+            .limit(20)
+            .offset(40)
+            .build();
+        assert_eq!(
+            body,
+            "fields name,platforms; search \"Zelda\"; where platforms = (6,130) & category = 0; \
+             sort first_release_date desc; limit 20; offset 40;"
+        );
+    }
+
+    #[test]
+    fn test_search_escapes_quotes() {
+        let body = ApicalypseQuery::new()
+            .search("Zelda\"; fields *; where id = 1; \\")
+            .build();
+        assert_eq!(body, r#"search "Zelda\"; fields *; where id = 1; \\";"#);
+    }
+
+    #[test]
+    fn test_where_eq_drops_control_characters() {
+        let body = ApicalypseQuery::new().where_eq("name", "Hal\nf-Life\t").build();
+        assert_eq!(body, "where name = \"Half-Life\";");
+    }
+
+    #[test]
+    fn test_where_in_strings_quotes_each_value() {
+        let values = ["620".to_string(), "6\"20".to_string()];
+        let body = ApicalypseQuery::new().where_in_strings("uid", &values).build();
+        assert_eq!(body, r#"where uid = ("620","6\"20");"#);
+    }
+}
@@ -0,0 +1,110 @@
+use rand::Rng;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Token bucket limiting how many requests are sent per second
+///
+/// The bucket starts full, so short bursts of up to `capacity` requests go out
+/// immediately; after that requests are spaced out to `refill_per_second`.
+pub struct TokenBucket {
+    state: Mutex<BucketState>,
+    capacity: f64,
+    refill_per_second: f64,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, refill_per_second: f64) -> Self {
+        Self {
+            state: Mutex::new(BucketState {
+                tokens: capacity as f64,
+                last_refill: Instant::now(),
+            }),
+            capacity: capacity as f64,
+            refill_per_second,
+        }
+    }
+
+    /// Waits until a token is available and takes it
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().await;
+                let now = Instant::now();
+                let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * self.refill_per_second).min(self.capacity);
+                state.last_refill = now;
+
+                if state.tokens >= 1.0 {
+                    state.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - state.tokens) / self.refill_per_second)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Delay before retry number `attempt` (starting at 0): exponential backoff from
+/// `base`, scaled by a random factor between 0.5 and 1.0 so that concurrent
+/// requests do not retry in lockstep
+pub fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    let jitter = rand::thread_rng().gen_range(0.5..=1.0);
+    base.saturating_mul(2u32.saturating_pow(attempt)).mul_f64(jitter)
+}
+
+/// Longest wait honoured from a `Retry-After` header, so a misbehaving
+/// upstream cannot stall a request for minutes
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// Parses a `Retry-After` header given in seconds, capped at `MAX_RETRY_AFTER`
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let seconds = value.trim().parse::<u64>().ok()?;
+    Some(Duration::from_secs(seconds).min(MAX_RETRY_AFTER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retry_after_is_capped() {
+        assert_eq!(parse_retry_after("2"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_retry_after("86400"), Some(MAX_RETRY_AFTER));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_token_bucket_allows_burst_then_throttles() {
+        let bucket = TokenBucket::new(4, 4.0);
+        let start = Instant::now();
+        for _ in 0..4 {
+            bucket.acquire().await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO, "Burst should not wait");
+
+        bucket.acquire().await;
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed >= Duration::from_millis(249) && elapsed <= Duration::from_millis(260),
+            "Fifth request should wait for one refill, waited {:?}",
+            elapsed
+        );
+    }
+
+    #[test]
+    fn test_backoff_delay_grows_with_jitter() {
+        let base = Duration::from_millis(100);
+        for attempt in 0..4 {
+            let max = base * 2u32.pow(attempt);
+            let delay = backoff_delay(base, attempt);
+            assert!(delay >= max / 2 && delay <= max, "attempt {}: {:?}", attempt, delay);
+        }
+    }
+}
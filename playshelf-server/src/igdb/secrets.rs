@@ -0,0 +1,251 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Secrets required to authenticate with Twitch and to sign session tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Secret {
+    TwitchClientId,
+    TwitchClientSecret,
+    SessionSigningKey,
+}
+
+impl Secret {
+    /// Name of the environment variable (or `.env` key) holding the secret
+    pub fn env_var(self) -> &'static str {
+        match self {
+            Secret::TwitchClientId => "TWITCH_CLIENT_ID",
+            Secret::TwitchClientSecret => "TWITCH_CLIENT_SECRET",
+            Secret::SessionSigningKey => "SESSION_SIGNING_KEY",
+        }
+    }
+
+    /// Key of the secret in a TOML secrets file
+    pub fn toml_key(self) -> &'static str {
+        match self {
+            Secret::TwitchClientId => "twitch_client_id",
+            Secret::TwitchClientSecret => "twitch_client_secret",
+            Secret::SessionSigningKey => "session_signing_key",
+        }
+    }
+
+    /// Name of the SSM parameter, relative to the configured parameter prefix
+    pub fn ssm_name(self) -> &'static str {
+        match self {
+            Secret::TwitchClientId => "twitch-client-id",
+            Secret::TwitchClientSecret => "twitch-client-secret",
+            Secret::SessionSigningKey => "session-signing-key",
+        }
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.ssm_name())
+    }
+}
+
+/// Errors returned while looking up secrets
+#[derive(Debug)]
+pub enum SecretError {
+    /// The secret is not defined by the provider
+    Missing { secret: Secret, provider: &'static str },
+    /// The secrets file could not be read or parsed
+    File { path: PathBuf, message: String },
+    /// The AWS SSM request failed
+    Ssm { name: String, message: String },
+}
+
+impl fmt::Display for SecretError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretError::Missing { secret, provider } => {
+                write!(f, "secret '{}' is not set in the {} provider", secret, provider)
+            }
+            SecretError::File { path, message } => {
+                write!(f, "failed to load secrets file '{}': {}", path.display(), message)
+            }
+            SecretError::Ssm { name, message } => {
+                write!(f, "failed to fetch SSM parameter '{}': {}", name, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SecretError {}
+
+/// Source of the secrets needed to talk to Twitch and IGDB
+#[async_trait]
+pub trait SecretProvider: Send + Sync {
+    /// Returns the value of `secret`, or an error if it cannot be found
+    async fn get_secret(&self, secret: Secret) -> Result<String, SecretError>;
+}
+
+/// Reads secrets from environment variables such as `TWITCH_CLIENT_ID`
+pub struct EnvSecretProvider;
+
+#[async_trait]
+impl SecretProvider for EnvSecretProvider {
+    async fn get_secret(&self, secret: Secret) -> Result<String, SecretError> {
+        std::env::var(secret.env_var())
+            .ok()
+            .filter(|value| !value.is_empty())
+            .ok_or(SecretError::Missing { secret, provider: "env" })
+    }
+}
+
+/// Reads secrets from a local file
+///
+/// Files ending in `.toml` are parsed as TOML with keys such as
+/// `twitch_client_id`; any other file is parsed in `.env` format with keys
+/// such as `TWITCH_CLIENT_ID`.
+pub struct FileSecretProvider {
+    values: HashMap<String, String>,
+    toml: bool,
+}
+
+impl FileSecretProvider {
+    /// Loads and parses the secrets file at `path`
+    pub fn load(path: &Path) -> Result<Self, SecretError> {
+        let file_error = |message: String| SecretError::File {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        let toml = path.extension().is_some_and(|ext| ext == "toml");
+        let content = std::fs::read_to_string(path).map_err(|e| file_error(e.to_string()))?;
+        let values = if toml {
+            let table: toml::Table = content.parse().map_err(|e: toml::de::Error| file_error(e.to_string()))?;
+            table
+                .into_iter()
+                .filter_map(|(key, value)| value.as_str().map(|v| (key, v.to_string())))
+                .collect()
+        } else {
+            parse_dotenv(&content).map_err(file_error)?
+        };
+
+        Ok(Self { values, toml })
+    }
+}
+
+/// Parses `KEY=VALUE` lines, ignoring blank lines, comments and a leading `export`
+fn parse_dotenv(content: &str) -> Result<HashMap<String, String>, String> {
+    let mut values = HashMap::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {} is not a KEY=VALUE pair", index + 1))?;
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+            .unwrap_or(value);
+        values.insert(key.trim().to_string(), value.to_string());
+    }
+    Ok(values)
+}
+
+#[async_trait]
+impl SecretProvider for FileSecretProvider {
+    async fn get_secret(&self, secret: Secret) -> Result<String, SecretError> {
+        let key = if self.toml { secret.toml_key() } else { secret.env_var() };
+        self.values
+            .get(key)
+            .filter(|value| !value.is_empty())
+            .cloned()
+            .ok_or(SecretError::Missing { secret, provider: "file" })
+    }
+}
+
+/// Reads secrets from AWS SSM Parameter Store
+pub struct SsmSecretProvider {
+    client: aws_sdk_ssm::Client,
+    /// Parameter path prefix, e.g. `/playshelf/prod`
+    prefix: String,
+}
+
+impl SsmSecretProvider {
+    /// Creates a provider reading parameters under `prefix` in the given AWS region
+    pub async fn new(region: &str, prefix: &str) -> Self {
+        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(aws_config::Region::new(region.to_string()))
+            .load()
+            .await;
+        Self {
+            client: aws_sdk_ssm::Client::new(&config),
+            prefix: prefix.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl SecretProvider for SsmSecretProvider {
+    async fn get_secret(&self, secret: Secret) -> Result<String, SecretError> {
+        let name = format!("{}/{}", self.prefix, secret.ssm_name());
+        let resp = self
+            .client
+            .get_parameter()
+            .name(&name)
+            .with_decryption(true) // Crucial for SecureString
+            .send()
+            .await
+            .map_err(|e| SecretError::Ssm {
+                name: name.clone(),
+                message: e.to_string(),
+            })?;
+
+        resp.parameter()
+            .and_then(|p| p.value())
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .ok_or(SecretError::Missing { secret, provider: "ssm" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_temp_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("playshelf-{}-{}", uuid::Uuid::new_v4(), name));
+        std::fs::write(&path, content).expect("Failed to write temp file");
+        path
+    }
+
+    #[tokio::test]
+    async fn test_file_provider_toml() {
+        let path = write_temp_file(
+            "secrets.toml",
+            "twitch_client_id = \"id\"\ntwitch_client_secret = \"secret\"\n",
+        );
+        let provider = FileSecretProvider::load(&path).expect("Failed to load TOML secrets");
+        assert_eq!(provider.get_secret(Secret::TwitchClientId).await.unwrap(), "id");
+        assert_eq!(provider.get_secret(Secret::TwitchClientSecret).await.unwrap(), "secret");
+        std::fs::remove_file(path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_file_provider_dotenv() {
+        let path = write_temp_file("secrets.env", "# Twitch\nexport TWITCH_CLIENT_ID=\"id\"\n");
+        let provider = FileSecretProvider::load(&path).expect("Failed to load .env secrets");
+        assert_eq!(provider.get_secret(Secret::TwitchClientId).await.unwrap(), "id");
+        assert!(matches!(
+            provider.get_secret(Secret::TwitchClientSecret).await,
+            Err(SecretError::Missing { secret: Secret::TwitchClientSecret, .. })
+        ));
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_file_provider_missing_file() {
+        let path = std::env::temp_dir().join("playshelf-does-not-exist.toml");
+        assert!(matches!(FileSecretProvider::load(&path), Err(SecretError::File { .. })));
+    }
+}
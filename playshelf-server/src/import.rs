@@ -0,0 +1,428 @@
+//! Importing spreadsheets of games onto a user's shelf
+//!
+//! An import takes two requests: a preview, which reads the CSV and matches
+//! every row against an IGDB search without saving anything, then a commit of
+//! the matches the user confirmed. Rows that carry an IGDB ID, such as those
+//! of a PlayShelf export, are matched by ID instead.
+
+use chrono::NaiveDate;
+use playshelf_types::{
+    GameData, ImportCandidate, ImportItem, ImportRow, MatchConfidence, Rating, ShelfEntry, ShelfStatus,
+    MAX_IMPORT_CANDIDATES, MAX_IMPORT_ROWS,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::export::strip_formula_guard;
+use crate::user::User;
+
+/// Candidates scoring less than this are not listed
+const MIN_SCORE: f64 = 0.3;
+/// Least score of a confident match
+const CONFIDENT_SCORE: f64 = 0.8;
+/// How far a confident match must be ahead of the runner-up
+const CONFIDENT_MARGIN: f64 = 0.1;
+/// Share of the score given for being on the row's platform
+const PLATFORM_WEIGHT: f64 = 0.1;
+
+/// Why a CSV could not be read at all
+#[derive(Debug, PartialEq, Eq)]
+pub enum ImportError {
+    /// The CSV is malformed
+    Csv(String),
+    /// The header row has no `title` column
+    MissingTitle,
+    /// The CSV has no rows after the header
+    Empty,
+    /// The CSV has more than `MAX_IMPORT_ROWS` rows
+    TooManyRows(usize),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Csv(e) => write!(f, "Failed to read CSV: {}", e),
+            ImportError::MissingTitle => write!(f, "The CSV needs a header row with a 'title' column"),
+            ImportError::Empty => write!(f, "The CSV has no games in it"),
+            ImportError::TooManyRows(rows) => write!(
+                f,
+                "The CSV has {} rows; import at most {} at a time",
+                rows, MAX_IMPORT_ROWS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// A CSV row read but not matched yet
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedRow {
+    pub line: usize,
+    /// IGDB ID of the game, when the CSV has an ID column
+    pub game_id: Option<u64>,
+    pub title: String,
+    pub platform: Option<String>,
+    pub status: ShelfStatus,
+    pub rating: Option<Rating>,
+    pub warnings: Vec<String>,
+}
+
+/// Reads a status by its label in any case, or by a common other name such
+/// as "finished"
+fn parse_status(value: &str) -> Option<ShelfStatus> {
+    let value = value.to_lowercase();
+    let status = match value.as_str() {
+        "finished" | "beaten" => ShelfStatus::Completed,
+        "dropped" => ShelfStatus::Abandoned,
+        "owned" => ShelfStatus::Backlog,
+        "wanted" => ShelfStatus::Wishlist,
+        _ => return ShelfStatus::ALL.into_iter().find(|status| status.label().to_lowercase() == value),
+    };
+    Some(status)
+}
+
+/// Reads the rows of an import CSV, skipping blank ones
+pub fn parse_csv(text: &str) -> Result<Vec<ParsedRow>, ImportError> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).trim(csv::Trim::All).from_reader(text.as_bytes());
+    let headers = reader.headers().map_err(|e| ImportError::Csv(e.to_string()))?.clone();
+    let column = |names: &[&str]| {
+        headers
+            .iter()
+            .position(|header| names.iter().any(|name| header.eq_ignore_ascii_case(name)))
+    };
+    let title = column(&["title", "name"]).ok_or(ImportError::MissingTitle)?;
+    let platform = column(&["platform", "platforms"]);
+    let status = column(&["status"]);
+    let rating = column(&["rating"]);
+    let game_id = column(&["id", "igdb_id", "game_id"]);
+
+    let records = reader
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ImportError::Csv(e.to_string()))?;
+    let records: Vec<_> = records.into_iter().filter(|record| !record.iter().all(str::is_empty)).collect();
+    if records.len() > MAX_IMPORT_ROWS {
+        return Err(ImportError::TooManyRows(records.len()));
+    }
+
+    let mut rows = Vec::new();
+    for record in records {
+        let cell = |index: Option<usize>| index.and_then(|index| record.get(index)).filter(|value| !value.is_empty());
+        let mut warnings = Vec::new();
+        let status = match cell(status) {
+            None => ShelfStatus::default(),
+            Some(value) => parse_status(value).unwrap_or_else(|| {
+                warnings.push(format!("Unknown status '{}', using Backlog", value));
+                ShelfStatus::default()
+            }),
+        };
+        let rating = cell(rating).and_then(|value| {
+            let rating = value.parse().ok().and_then(Rating::from_stars);
+            if rating.is_none() {
+                warnings.push(format!("Rating '{}' is not 0.5 to 5 stars in half stars, leaving it out", value));
+            }
+            rating
+        });
+        let game_id = cell(game_id).and_then(|value| {
+            let id = value.parse().ok();
+            if id.is_none() {
+                warnings.push(format!("IGDB ID '{}' is not a number, matching by title", value));
+            }
+            id
+        });
+        if cell(Some(title)).is_none() && game_id.is_none() {
+            warnings.push("Row has no title".to_string());
+        }
+        rows.push(ParsedRow {
+            line: record.position().map_or(0, |position| position.line() as usize),
+            game_id,
+            title: strip_formula_guard(cell(Some(title)).unwrap_or_default()).to_string(),
+            // Exports list every platform; the first stands for the row
+            platform: cell(platform)
+                .and_then(|value| value.split(';').next())
+                .map(|value| value.trim().to_string()),
+            status,
+            rating,
+            warnings,
+        });
+    }
+    if rows.is_empty() {
+        return Err(ImportError::Empty);
+    }
+    Ok(rows)
+}
+
+/// Lowercases `text` and reduces it to words of letters and digits
+fn normalize(text: &str) -> String {
+    let text = text.to_lowercase().replace('&', " and ");
+    let cleaned: String = text.chars().map(|c| if c.is_alphanumeric() { c } else { ' ' }).collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn bigrams(text: &str) -> Vec<(char, char)> {
+    let chars: Vec<char> = text.chars().collect();
+    chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+/// How alike two titles are, from 0 to 1
+///
+/// Compares the character pairs of both titles (the Sørensen–Dice
+/// coefficient), and scores a title whose words all appear in the other,
+/// e.g. "Zelda" in "Zelda II", as a likely but unsure match.
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalize(a), normalize(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+    let (a_pairs, mut b_pairs) = (bigrams(&a), bigrams(&b));
+    let total = a_pairs.len() + b_pairs.len();
+    let mut shared = 0;
+    for pair in &a_pairs {
+        if let Some(index) = b_pairs.iter().position(|other| other == pair) {
+            b_pairs.swap_remove(index);
+            shared += 1;
+        }
+    }
+    let dice = if total == 0 { 0.0 } else { 2.0 * shared as f64 / total as f64 };
+
+    let (a_words, b_words): (HashSet<&str>, HashSet<&str>) = (a.split(' ').collect(), b.split(' ').collect());
+    let contained = a_words.is_subset(&b_words) || b_words.is_subset(&a_words);
+    if contained { dice.max(0.6) } else { dice }
+}
+
+/// Whether the words of `needle` appear together in `haystack`
+fn contains_words(haystack: &str, needle: &str) -> bool {
+    format!(" {} ", haystack).contains(&format!(" {} ", needle))
+}
+
+/// Whether one of the game's platforms is the row's, e.g. "Switch" for "Nintendo Switch"
+fn on_platform(platform: &str, game: &GameData) -> bool {
+    let platform = normalize(platform);
+    !platform.is_empty()
+        && game
+            .platforms
+            .iter()
+            .map(|name| normalize(name))
+            .any(|name| contains_words(&name, &platform) || contains_words(&platform, &name))
+}
+
+/// How likely `game` is the one a row with `title` and `platform` means, from 0 to 1
+pub fn match_score(title: &str, platform: Option<&str>, game: &GameData) -> f64 {
+    let platform_score = match platform {
+        Some(platform) if !on_platform(platform, game) => 0.0,
+        _ => 1.0,
+    };
+    title_similarity(title, &game.name) * (1.0 - PLATFORM_WEIGHT) + platform_score * PLATFORM_WEIGHT
+}
+
+/// Ranks the games a search found for a row and decides how sure the match is
+///
+/// A game with the row's IGDB ID is an exact match and the only candidate.
+pub fn match_row(row: ParsedRow, mut games: Vec<GameData>, shelf: &[ShelfEntry]) -> ImportRow {
+    let exact = row.game_id.and_then(|id| games.iter().position(|game| game.id == id));
+    let mut candidates: Vec<ImportCandidate> = match exact {
+        Some(index) => vec![ImportCandidate {
+            game: games.swap_remove(index),
+            score: 1.0,
+        }],
+        None => games
+            .into_iter()
+            .map(|game| ImportCandidate {
+                score: match_score(&row.title, row.platform.as_deref(), &game),
+                game,
+            })
+            .filter(|candidate| candidate.score >= MIN_SCORE)
+            .collect(),
+    };
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates.truncate(MAX_IMPORT_CANDIDATES);
+
+    let confidence = match candidates.as_slice() {
+        [] => MatchConfidence::Unmatched,
+        [best, rest @ ..]
+            if best.score >= CONFIDENT_SCORE
+                && rest.first().is_none_or(|second| best.score - second.score >= CONFIDENT_MARGIN) =>
+        {
+            MatchConfidence::Confident
+        }
+        _ => MatchConfidence::Ambiguous,
+    };
+    let on_shelf = candidates
+        .first()
+        .map(|best| best.game.id)
+        .filter(|id| shelf.iter().any(|entry| entry.game.id == *id));
+    ImportRow {
+        line: row.line,
+        title: row.title,
+        platform: row.platform,
+        status: row.status,
+        rating: row.rating,
+        confidence,
+        candidates,
+        warnings: row.warnings,
+        on_shelf,
+    }
+}
+
+/// What an import did with each confirmed match
+#[derive(Debug, Default, PartialEq)]
+pub struct GamesImport {
+    pub imported: Vec<u64>,
+    /// Already on the shelf, or listed twice
+    pub skipped: Vec<u64>,
+    /// Not among the games looked up on IGDB
+    pub not_found: Vec<u64>,
+}
+
+impl User {
+    /// Adds the confirmed matches of an import to the shelf as of `today`,
+    /// taking each game's data from `games` (keyed by IGDB ID)
+    pub fn import_games(
+        &mut self,
+        items: Vec<ImportItem>,
+        games: &HashMap<u64, GameData>,
+        today: NaiveDate,
+    ) -> GamesImport {
+        let mut import = GamesImport::default();
+        for item in items {
+            let Some(game) = games.get(&item.game_id) else {
+                import.not_found.push(item.game_id);
+                continue;
+            };
+            let mut entry = ShelfEntry::new(game.clone(), today);
+            entry.set_status(item.status, today);
+            if item.rating.is_some() {
+                entry.set_review(item.rating, None, today);
+            }
+            if self.add_game(entry) {
+                import.imported.push(item.game_id);
+            } else {
+                import.skipped.push(item.game_id);
+            }
+        }
+        import
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::game;
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "Title,Platform,Status,Rating,Notes\n\
+                   Celeste,PC,completed,4.5,great\n\
+                   ,,,,\n\
+                   \"Ori, and the Blind Forest\",,Finished,7,\n\
+                   ,Switch,,,\n";
+        let rows = parse_csv(csv).unwrap();
+        assert_eq!(rows.len(), 3, "Blank rows are skipped");
+        assert_eq!(rows[0].title, "Celeste");
+        assert_eq!(rows[0].platform.as_deref(), Some("PC"));
+        assert_eq!(rows[0].status, ShelfStatus::Completed);
+        assert_eq!(rows[0].rating, Rating::from_half_stars(9));
+        assert!(rows[0].warnings.is_empty());
+
+        assert_eq!(rows[1].line, 4);
+        assert_eq!(rows[1].title, "Ori, and the Blind Forest");
+        assert_eq!((rows[1].status, rows[1].rating), (ShelfStatus::Completed, None));
+        assert_eq!(rows[1].warnings.len(), 1);
+        assert_eq!(rows[2].warnings, vec!["Row has no title"]);
+
+        let csv = "game_id,name,platforms\n72,Portal 2,PC\nabc,Celeste,\n1942,,\n";
+        let rows = parse_csv(csv).unwrap();
+        let ids: Vec<Option<u64>> = rows.iter().map(|row| row.game_id).collect();
+        assert_eq!(ids, vec![Some(72), None, Some(1942)]);
+        assert_eq!(rows[1].warnings.len(), 1);
+        assert!(rows[2].warnings.is_empty(), "An ID stands in for the title");
+
+        assert_eq!(parse_csv("name,platform\n"), Err(ImportError::Empty));
+        assert_eq!(parse_csv("game,platform\nCeleste,PC\n"), Err(ImportError::MissingTitle));
+        let many = format!("title\n{}", "Celeste\n".repeat(MAX_IMPORT_ROWS + 2));
+        assert_eq!(parse_csv(&many), Err(ImportError::TooManyRows(MAX_IMPORT_ROWS + 2)));
+    }
+
+    #[test]
+    fn test_title_similarity() {
+        assert_eq!(title_similarity("The Witcher 3: Wild Hunt", "the witcher 3 wild hunt"), 1.0);
+        assert!(title_similarity("Witcher 3 Wild Hunt", "The Witcher 3: Wild Hunt") > 0.8);
+        assert!(title_similarity("Zelda", "Zelda II: The Adventure of Link") >= 0.6);
+        assert!(title_similarity("Portal 2", "The Witcher 3: Wild Hunt") < MIN_SCORE);
+        assert_eq!(title_similarity("", "Portal"), 0.0);
+    }
+
+    #[test]
+    fn test_match_row() {
+        let row = |title: &str, platform: Option<&str>| ParsedRow {
+            line: 2,
+            game_id: None,
+            title: title.to_string(),
+            platform: platform.map(str::to_string),
+            status: ShelfStatus::Completed,
+            rating: None,
+            warnings: Vec::new(),
+        };
+        let botw = game(7346, "The Legend of Zelda: Breath of the Wild", &["Wii U", "Nintendo Switch"]);
+        let zelda2 = game(1025, "Zelda II: The Adventure of Link", &["Nintendo Entertainment System"]);
+        let portal = game(72, "Portal 2", &["PC (Microsoft Windows)"]);
+
+        let matched = match_row(row("Portal 2", Some("PC")), vec![portal.clone()], &[]);
+        assert_eq!(matched.confidence, MatchConfidence::Confident);
+        assert_eq!(matched.candidates[0].score, 1.0);
+        let elsewhere = match_row(row("Portal 2", Some("Switch")), vec![portal.clone()], &[]);
+        assert!(elsewhere.candidates[0].score < 1.0, "Other platforms score lower");
+
+        let zelda = match_row(row("Zelda", None), vec![botw.clone(), zelda2.clone()], &[]);
+        assert_eq!(zelda.confidence, MatchConfidence::Ambiguous);
+        assert_eq!(zelda.candidates.len(), 2);
+        let zelda = match_row(row("Zelda II", Some("Nintendo Entertainment System")), vec![botw.clone(), zelda2], &[]);
+        assert_eq!(zelda.candidates[0].game.id, 1025);
+
+        let mut by_id = row("Portal", None);
+        by_id.game_id = Some(72);
+        let exact = match_row(by_id, vec![botw.clone(), portal.clone()], &[]);
+        assert_eq!(exact.confidence, MatchConfidence::Confident);
+        assert_eq!((exact.candidates.len(), exact.candidates[0].game.id), (1, 72));
+
+        let none = match_row(row("Hollow Knight", None), vec![portal], &[]);
+        assert_eq!(none.confidence, MatchConfidence::Unmatched);
+        assert!(none.candidates.is_empty());
+
+        let shelf = [ShelfEntry::new(botw.clone(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap())];
+        let owned = match_row(row("Breath of the Wild", None), vec![botw], &shelf);
+        assert_eq!(owned.on_shelf, Some(7346));
+    }
+
+    #[test]
+    fn test_import_games() {
+        let mut user = User::new("link".to_string(), "Link".to_string(), String::new());
+        let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let item = |game_id: u64, status: ShelfStatus, rating: Option<Rating>| ImportItem {
+            game_id,
+            status,
+            rating,
+        };
+        let games: HashMap<u64, GameData> = (1..=2).map(|id| (id, game(id, &format!("Game {}", id), &[]))).collect();
+        let import = user.import_games(
+            vec![
+                item(1, ShelfStatus::Completed, Rating::from_half_stars(8)),
+                item(2, ShelfStatus::Wishlist, None),
+                item(1, ShelfStatus::Playing, None),
+                item(404, ShelfStatus::Backlog, None),
+            ],
+            &games,
+            today,
+        );
+        assert_eq!((import.imported, import.skipped, import.not_found), (vec![1, 2], vec![1], vec![404]));
+        let entry = &user.games[0];
+        assert_eq!(entry.status, ShelfStatus::Completed);
+        assert_eq!((entry.started_on, entry.finished_on), (Some(today), Some(today)));
+        assert_eq!(entry.rating, Rating::from_half_stars(8));
+        assert_eq!(user.games[1].rating, None);
+    }
+}
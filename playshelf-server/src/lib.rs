@@ -0,0 +1,82 @@
+pub mod auth;
+pub mod collections;
+pub mod config;
+pub mod export;
+pub mod handlers;
+pub mod igdb;
+pub mod import;
+pub mod reviews;
+pub mod sessions;
+pub mod stats;
+pub mod state;
+pub mod steam;
+pub mod store;
+pub mod user;
+
+#[cfg(test)]
+mod test_support;
+
+use axum::{
+    routing::{delete, get, post, put},
+    Router,
+};
+
+use crate::state::AppState;
+
+/// Builds the application router with every route wired to the shared state
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(|| async { "Hello, World!" }))
+        .route("/games", get(handlers::get_games_handler))
+        .route("/games/search", get(handlers::search_games_handler))
+        .route("/games/{id}", get(handlers::get_game_detail_handler))
+        .route("/games/{id}/reviews", get(handlers::game_reviews_handler))
+        .route("/cache/stats", get(handlers::cache_stats_handler))
+        .route("/auth/register", post(handlers::register_handler))
+        .route("/auth/login", post(handlers::login_handler))
+        .route("/auth/me", get(handlers::me_handler))
+        .route("/users", get(handlers::list_users_handler))
+        .route(
+            "/users/{username}",
+            get(handlers::get_user_handler).patch(handlers::update_profile_handler),
+        )
+        .route(
+            "/users/{username}/games",
+            get(handlers::list_user_games_handler).post(handlers::add_user_game_handler),
+        )
+        .route("/users/{username}/games/{game_id}", delete(handlers::remove_user_game_handler))
+        .route("/users/{username}/games/{game_id}/status", put(handlers::set_game_status_handler))
+        .route("/users/{username}/games/{game_id}/review", put(handlers::set_game_review_handler))
+        .route(
+            "/users/{username}/games/{game_id}/sessions",
+            get(handlers::list_game_sessions_handler).post(handlers::log_session_handler),
+        )
+        .route(
+            "/users/{username}/games/{game_id}/sessions/{id}",
+            delete(handlers::delete_session_handler),
+        )
+        .route("/users/{username}/playtime", get(handlers::playtime_handler))
+        .route("/users/{username}/stats", get(handlers::user_stats_handler))
+        .route("/users/{username}/export", get(handlers::export_library_handler))
+        .route("/users/{username}/import", post(handlers::import_handler))
+        .route("/users/{username}/import/preview", post(handlers::import_preview_handler))
+        .route("/users/{username}/import/steam", post(handlers::import_steam_handler))
+        .route(
+            "/users/{username}/collections",
+            get(handlers::list_collections_handler).post(handlers::create_collection_handler),
+        )
+        .route("/users/{username}/collections/order", put(handlers::reorder_collections_handler))
+        .route(
+            "/users/{username}/collections/{id}",
+            get(handlers::get_collection_handler)
+                .patch(handlers::rename_collection_handler)
+                .delete(handlers::delete_collection_handler),
+        )
+        .route("/users/{username}/collections/{id}/games", post(handlers::collect_game_handler))
+        .route(
+            "/users/{username}/collections/{id}/games/{game_id}",
+            delete(handlers::uncollect_game_handler),
+        )
+        .route("/users/{username}/collections/{id}/order", put(handlers::reorder_collection_handler))
+        .with_state(state)
+}
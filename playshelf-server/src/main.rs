@@ -1,19 +1,18 @@
 mod args;
-mod handlers;
-mod igdb;
-mod user;
 
-use axum::{
-    routing::get,
-    Router,
-};
-use tower_http::cors::CorsLayer;
 use chrono::{DateTime, Utc};
 use dotenv::dotenv;
-use igdb::manager::{IGDBManager, GameData};
+use playshelf_server::auth::SessionKeys;
+use playshelf_server::config::Config;
+use playshelf_server::igdb::manager::{IGDBConfig, IGDBManager, Page};
+use playshelf_types::GameData;
+use playshelf_server::igdb::secrets::{EnvSecretProvider, FileSecretProvider, Secret, SecretError, SecretProvider, SsmSecretProvider};
 use std::sync::Arc;
 
-use crate::args::Args;
+use crate::args::{Args, SecretSource, StoreKind};
+use playshelf_server::reviews::{ReviewIndex, REVIEW_INDEX_TTL};
+use playshelf_server::state::AppState;
+use playshelf_server::store::{DynamoUserStore, MemoryUserStore, SqliteUserStore, StoreError, UserStore};
 use clap::Parser;
 
 /// Prints a list of games in a formatted, readable way
@@ -24,20 +23,8 @@ fn print_game_data(games: &[GameData], title: &str) {
         if !game.platforms.is_empty() {
             println!("   Platforms: {}", game.platforms.join(", "));
         }
-        if !game.first_release_date.is_empty() && game.first_release_date != "0" {
-            match game.first_release_date.parse::<i64>() {
-                Ok(timestamp) => {
-                    // IGDB first_release_date is a Unix timestamp in seconds
-                    if let Some(release_date) = DateTime::<Utc>::from_timestamp(timestamp, 0) {
-                        println!("   Release Date: {} (timestamp: {})", release_date.format("%Y-%m-%d"), timestamp);
-                    } else {
-                        println!("   Release Date: Invalid timestamp ({})", timestamp);
-                    }
-                }
-                Err(e) => {
-                    eprintln!("   Release Date: Failed to parse '{}' as timestamp: {}", game.first_release_date, e);
-                }
-            }
+        if let Some(release_date) = &game.first_release_date {
+            println!("   Release Date: {} ({})", release_date, release_date.date.format("%Y-%m-%d"));
         }
         if !game.genres.is_empty() {
             println!("   Genres: {}", game.genres.join(", "));
@@ -46,54 +33,103 @@ fn print_game_data(games: &[GameData], title: &str) {
     }
 }
 
-async fn main_dev() {
-    dotenv().ok();
+/// Builds the secret provider selected on the command line
+async fn secret_provider(flags: &Args, config: &Config) -> Result<Arc<dyn SecretProvider>, SecretError> {
+    Ok(match flags.secrets {
+        SecretSource::Env => Arc::new(EnvSecretProvider),
+        SecretSource::File => Arc::new(FileSecretProvider::load(&flags.secrets_file)?),
+        SecretSource::Ssm => Arc::new(SsmSecretProvider::new(&config.aws_region, &flags.ssm_prefix).await),
+    })
+}
 
+/// Builds the user store selected on the command line
+async fn user_store(flags: &Args, config: &Config) -> Result<Arc<dyn UserStore>, StoreError> {
+    Ok(match flags.store {
+        StoreKind::Dynamodb => {
+            Arc::new(DynamoUserStore::new(&config.aws_region, &config.tables.users, &config.tables.usernames).await)
+        }
+        StoreKind::Sqlite => Arc::new(SqliteUserStore::open(&flags.sqlite_path)?),
+        StoreKind::Memory => Arc::new(MemoryUserStore::new()),
+    })
+}
+
+/// Loads the key signing session tokens, falling back to a random one if none is configured
+async fn session_keys(secrets: &dyn SecretProvider) -> Result<SessionKeys, SecretError> {
+    match secrets.get_secret(Secret::SessionSigningKey).await {
+        Ok(key) => Ok(SessionKeys::new(key.as_bytes())),
+        Err(SecretError::Missing { .. }) => {
+            eprintln!(
+                "Warning: {} is not set; using a random key, so users are signed out on every restart",
+                Secret::SessionSigningKey.env_var()
+            );
+            Ok(SessionKeys::random())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn main_dev(secrets: Arc<dyn SecretProvider>, igdb_config: IGDBConfig) {
     // Authenticate with Twitch before setting up the app
-    let mut igdb_manager = IGDBManager::new();
+    let igdb_manager = IGDBManager::with_config(secrets, igdb_config);
     let expires_at = igdb_manager.authenticate().await.expect("Failed to authenticate with Twitch");
     let datetime = DateTime::<Utc>::from(expires_at);
     println!("Token expires at: {}\n", datetime.format("%Y-%m-%d %H:%M:%S UTC"));
 
     // Get List of Games
-    let games = igdb_manager.get_games().await.expect("Failed to get game list");
-    print_game_data(&games, "Found games");
+    let games = igdb_manager.get_games(Page::default()).await.expect("Failed to get game list");
+    print_game_data(&games.games, "Found games");
 
     // Search for Games
-    let search_result = igdb_manager.search_games("Zelda".to_string()).await.expect("Failed to search for games");
-    print_game_data(&search_result, "Search results for 'Zelda'");
+    let search_result = igdb_manager
+        .search_games("Zelda".to_string(), Page::default())
+        .await
+        .expect("Failed to search for games");
+    print_game_data(&search_result.games, "Search results for 'Zelda'");
 }
 
 // Migrate from axum to Dioxus
 #[tokio::main]
 async fn main() {
+    // Load .env first so it can provide PLAYSHELF_* settings
+    dotenv().ok();
     let flags = Args::parse();
+    let config = match flags.load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        }
+    };
+    let secrets = secret_provider(&flags, &config).await.expect("Failed to load secret provider");
+
     if flags.dev {
         println!("Running in development mode\n");
-        main_dev().await;
+        main_dev(secrets, config.igdb.clone()).await;
     } else {
         println!("Running in production mode\n");
-        dotenv().ok();
+
+        let sessions = session_keys(secrets.as_ref()).await.expect("Failed to load session signing key");
 
         // Authenticate with Twitch before setting up the app
-        let mut igdb_manager = IGDBManager::new();
+        let igdb_manager = IGDBManager::with_config(secrets, config.igdb.clone());
         let expires_at = igdb_manager.authenticate().await.expect("Failed to authenticate with Twitch");
         let datetime = DateTime::<Utc>::from(expires_at);
         println!("Token expires at: {}\n", datetime.format("%Y-%m-%d %H:%M:%S UTC"));
         
         // Wrap IGDBManager in Arc to share across requests
-        let igdb_manager = Arc::new(igdb_manager);
+        let state = AppState {
+            igdb: Arc::new(igdb_manager),
+            users: user_store(&flags, &config).await.expect("Failed to open user store"),
+            sessions: Arc::new(sessions),
+            reviews: Arc::new(ReviewIndex::new(REVIEW_INDEX_TTL)),
+        };
         
-        // build our application with routes that have access to IGDBManager
-        let app = Router::new()
-            .route("/", get(|| async { "Hello, World!" }))
-            .route("/games", get(handlers::get_games_handler))
-            .route("/games/search", get(handlers::search_games_handler))
-            .layer(CorsLayer::permissive())
-            .with_state(igdb_manager);
+        // build our application with routes that have access to the shared state
+        let app = playshelf_server::router(state).layer(config.cors_layer());
 
-        // run our app with hyper, listening globally on port 8081
-        let listener = tokio::net::TcpListener::bind("0.0.0.0:8081").await.unwrap();
+        // run our app with hyper on the configured address
+        println!("Listening on {}", config.bind_address);
+        let listener = tokio::net::TcpListener::bind(config.bind_address).await.unwrap();
         axum::serve(listener, app).await.unwrap();
     }
 }
\ No newline at end of file
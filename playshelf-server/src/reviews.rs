@@ -0,0 +1,147 @@
+//! Every user's rating and review of each game, for `GET /games/{id}/reviews`
+//!
+//! The stores cannot look entries up by game, so the index is built from one
+//! `list` of every user and reused until it is older than its time-to-live.
+//! Handlers that change a shelf through this server update the index right
+//! away; writes from other servers show up once it is rebuilt.
+
+use playshelf_types::GameReview;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use crate::store::{StoreResult, UserStore};
+use crate::user::User;
+
+/// How long a built index answers requests before every user is read again
+pub const REVIEW_INDEX_TTL: Duration = Duration::from_secs(60);
+
+/// Reviews of each game, newest first, next to the ID of the user who wrote them
+type GameReviews = HashMap<u64, Vec<(u128, GameReview)>>;
+
+/// Reviews of every game, built lazily from the user store
+pub struct ReviewIndex {
+    ttl: Duration,
+    built: Mutex<Option<(Instant, GameReviews)>>,
+}
+
+impl ReviewIndex {
+    /// Creates an empty index that is rebuilt when older than `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            built: Mutex::new(None),
+        }
+    }
+
+    /// Returns the reviews of `game_id`, newest first, reading every user
+    /// from `store` if the index is missing or stale
+    pub async fn reviews(&self, store: &dyn UserStore, game_id: u64) -> StoreResult<Vec<GameReview>> {
+        let mut built = self.built.lock().await;
+        if built.as_ref().is_none_or(|(built_at, _)| built_at.elapsed() >= self.ttl) {
+            let mut index = GameReviews::new();
+            for user in store.list().await? {
+                add_reviews(&mut index, &user);
+            }
+            index.values_mut().for_each(|reviews| sort_newest_first(reviews));
+            *built = Some((Instant::now(), index));
+        }
+        let reviews = built
+            .as_ref()
+            .and_then(|(_, index)| index.get(&game_id))
+            .map(|reviews| reviews.iter().map(|(_, review)| review.clone()).collect())
+            .unwrap_or_default();
+        Ok(reviews)
+    }
+
+    /// Replaces `user`'s reviews in the index with those on their saved shelf
+    ///
+    /// Called after every successful write that can change a review, the
+    /// status shown next to it or the user's names.
+    pub async fn refresh_user(&self, user: &User) {
+        let mut built = self.built.lock().await;
+        let Some((_, index)) = built.as_mut() else {
+            return;
+        };
+        for reviews in index.values_mut() {
+            reviews.retain(|(user_id, _)| *user_id != user.id);
+        }
+        index.retain(|_, reviews| !reviews.is_empty());
+        add_reviews(index, user);
+        for entry in user.get_games().iter().filter(|entry| entry.is_reviewed()) {
+            if let Some(reviews) = index.get_mut(&entry.game.id) {
+                sort_newest_first(reviews);
+            }
+        }
+    }
+}
+
+fn add_reviews(index: &mut GameReviews, user: &User) {
+    for entry in user.get_games().iter().filter(|entry| entry.is_reviewed()) {
+        let review = GameReview {
+            username: user.username.clone(),
+            name: user.name.clone(),
+            status: entry.status,
+            rating: entry.rating,
+            review: entry.review.clone(),
+            reviewed_on: entry.reviewed_on,
+        };
+        index.entry(entry.game.id).or_default().push((user.id, review));
+    }
+}
+
+/// Orders reviews newest first, and reviews from the same day by username
+fn sort_newest_first(reviews: &mut [(u128, GameReview)]) {
+    reviews.sort_by(|(_, a), (_, b)| {
+        b.reviewed_on
+            .cmp(&a.reviewed_on)
+            .then_with(|| a.username.cmp(&b.username))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::memory::MemoryUserStore;
+    use chrono::NaiveDate;
+    use crate::test_support::game;
+    use playshelf_types::{Rating, ShelfEntry};
+
+    fn reviewed_game(user: &mut User, game_id: u64, half_stars: u8, day: u32) {
+        let date = NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+        let mut entry = ShelfEntry::new(game(game_id, &format!("Game {}", game_id), &[]), date);
+        entry.set_review(Rating::from_half_stars(half_stars), None, date);
+        user.add_game(entry);
+    }
+
+    #[tokio::test]
+    async fn test_review_index_follows_writes_until_rebuilt() {
+        let store = MemoryUserStore::new();
+        let mut link = User::new("link".to_string(), "Link".to_string(), String::new());
+        reviewed_game(&mut link, 1942, 9, 1);
+        store.create(&link).await.unwrap();
+        let mut zelda = User::new("zelda".to_string(), "Zelda".to_string(), String::new());
+        reviewed_game(&mut zelda, 1942, 6, 2);
+        store.create(&zelda).await.unwrap();
+
+        let index = ReviewIndex::new(Duration::from_secs(3600));
+        let reviews = index.reviews(&store, 1942).await.unwrap();
+        let usernames: Vec<&str> = reviews.iter().map(|review| review.username.as_str()).collect();
+        assert_eq!(usernames, vec!["zelda", "link"]);
+
+        // Written through this server: seen right away
+        link.username = "hero".to_string();
+        reviewed_game(&mut link, 7346, 10, 3);
+        store.update(&mut link).await.unwrap();
+        index.refresh_user(&link).await;
+        let reviews = index.reviews(&store, 1942).await.unwrap();
+        assert_eq!(reviews[1].username, "hero");
+        assert_eq!(index.reviews(&store, 7346).await.unwrap().len(), 1);
+
+        // Written elsewhere: seen once the index is rebuilt
+        store.delete(zelda.id).await.unwrap();
+        assert_eq!(index.reviews(&store, 1942).await.unwrap().len(), 2);
+        let index = ReviewIndex::new(Duration::ZERO);
+        assert_eq!(index.reviews(&store, 1942).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,178 @@
+//! Logging play sessions and totalling time played
+//!
+//! Each shelf entry keeps its own sessions in the order they were played, so a
+//! session logged late is slotted in by its start time. Session IDs are only
+//! unique within their entry and come from a counter on the entry, so deleting
+//! a session never frees its ID for the next one. Totals are recomputed from
+//! the sessions on every request rather than stored.
+
+use playshelf_types::{
+    GamePlaytime, GameSessionsResponse, LogSessionRequest, PlaySession, PlaytimeResponse, PlaytimeTotals, ShelfEntry,
+};
+use std::cmp::Reverse;
+use std::fmt;
+
+use crate::user::User;
+
+/// Why a change to a user's play sessions was refused
+#[derive(Debug, PartialEq, Eq)]
+pub enum SessionError {
+    /// The game is not on the user's shelf
+    NotOnShelf(u64),
+    /// The game has no session with this ID
+    NotFound(u64),
+    /// The request has neither or both of an end time and a duration
+    InvalidLength,
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::NotOnShelf(game_id) => write!(f, "Game {} is not in the library", game_id),
+            SessionError::NotFound(id) => write!(f, "Session {} not found", id),
+            SessionError::InvalidLength => write!(f, "A session needs either an end time or a duration"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+pub type SessionResult<T> = Result<T, SessionError>;
+
+/// Trims `text`, treating blank text as none
+fn trimmed(text: Option<String>) -> Option<String> {
+    text.map(|text| text.trim().to_string()).filter(|text| !text.is_empty())
+}
+
+impl User {
+    fn shelf_entry(&self, game_id: u64) -> SessionResult<&ShelfEntry> {
+        self.games
+            .iter()
+            .find(|entry| entry.game.id == game_id)
+            .ok_or(SessionError::NotOnShelf(game_id))
+    }
+
+    /// Logs a session of a shelved game and returns its ID
+    ///
+    /// The request should have passed validation; only its length is checked here.
+    pub fn log_session(&mut self, game_id: u64, request: LogSessionRequest) -> SessionResult<u64> {
+        let duration_minutes = request.duration_minutes().ok_or(SessionError::InvalidLength)?;
+        let entry = self.entry_mut(game_id).ok_or(SessionError::NotOnShelf(game_id))?;
+        // Sessions logged before the counter existed may hold higher IDs
+        let highest = entry.sessions.iter().map(|session| session.id).max().unwrap_or(0);
+        let id = entry.last_session_id.max(highest) + 1;
+        entry.last_session_id = id;
+        let session = PlaySession {
+            id,
+            started_at: request.started_at,
+            duration_minutes,
+            platform: trimmed(request.platform),
+            note: trimmed(request.note),
+        };
+        // Keep sessions in the order they were played, even when logged late
+        let position = entry.sessions.partition_point(|logged| logged.started_at <= session.started_at);
+        entry.sessions.insert(position, session);
+        Ok(id)
+    }
+
+    pub fn delete_session(&mut self, game_id: u64, id: u64) -> SessionResult<()> {
+        let entry = self.entry_mut(game_id).ok_or(SessionError::NotOnShelf(game_id))?;
+        let len = entry.sessions.len();
+        entry.sessions.retain(|session| session.id != id);
+        if entry.sessions.len() == len {
+            return Err(SessionError::NotFound(id));
+        }
+        Ok(())
+    }
+
+    /// A shelved game's sessions, newest first, with their totals
+    pub fn game_sessions(&self, game_id: u64) -> SessionResult<GameSessionsResponse> {
+        let entry = self.shelf_entry(game_id)?;
+        Ok(GameSessionsResponse {
+            game_id,
+            sessions: entry.sessions.iter().rev().cloned().collect(),
+            totals: entry.playtime_totals(),
+        })
+    }
+
+    /// Time played across the whole shelf, in total and per game
+    ///
+    /// Imported playtime counts towards its game and the total, but towards no
+    /// platform or month.
+    pub fn playtime(&self) -> PlaytimeResponse {
+        let mut by_game: Vec<GamePlaytime> = self
+            .games
+            .iter()
+            .map(|entry| GamePlaytime {
+                game_id: entry.game.id,
+                name: entry.game.name.clone(),
+                minutes: entry.minutes_played(),
+            })
+            .filter(|game| game.minutes > 0)
+            .collect();
+        by_game.sort_by_key(|game| Reverse(game.minutes));
+        let mut totals = PlaytimeTotals::from_sessions(self.games.iter().flat_map(|entry| &entry.sessions));
+        for imported in self.games.iter().filter_map(|entry| entry.imported_playtime) {
+            totals.add_unattributed(imported.minutes);
+        }
+        PlaytimeResponse { totals, by_game }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::shelf_of;
+
+    fn request(started_at: &str, duration_minutes: u32, platform: Option<&str>) -> LogSessionRequest {
+        LogSessionRequest {
+            started_at: started_at.parse().unwrap(),
+            ended_at: None,
+            duration_minutes: Some(duration_minutes),
+            platform: platform.map(str::to_string),
+            note: Some("  ".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_session_log() {
+        let mut user = shelf_of(&[1, 2]);
+        let late = user.log_session(1, request("2024-05-02T18:00:00Z", 60, Some(" PC "))).unwrap();
+        let early = user.log_session(1, request("2024-05-01T18:00:00Z", 30, None)).unwrap();
+        assert_ne!(late, early);
+        assert_eq!(
+            user.log_session(9, request("2024-05-01T18:00:00Z", 30, None)),
+            Err(SessionError::NotOnShelf(9))
+        );
+        let mut both = request("2024-05-01T18:00:00Z", 30, None);
+        both.ended_at = Some("2024-05-01T19:00:00Z".parse().unwrap());
+        assert_eq!(user.log_session(1, both), Err(SessionError::InvalidLength));
+
+        let sessions = user.game_sessions(1).unwrap();
+        let ids: Vec<u64> = sessions.sessions.iter().map(|session| session.id).collect();
+        assert_eq!(ids, vec![late, early], "Newest first");
+        assert_eq!(sessions.sessions[0].platform.as_deref(), Some("PC"));
+        assert_eq!(sessions.sessions[0].note, None, "Blank notes are dropped");
+        assert_eq!(sessions.totals.minutes, 90);
+
+        user.delete_session(1, early).unwrap();
+        assert_eq!(user.delete_session(1, early), Err(SessionError::NotFound(early)));
+        let next = user.log_session(1, request("2024-05-03T18:00:00Z", 10, None)).unwrap();
+        assert_ne!(next, early, "IDs of deleted sessions are not reused");
+    }
+
+    #[test]
+    fn test_playtime_across_shelf() {
+        let mut user = shelf_of(&[1, 2, 3]);
+        user.log_session(1, request("2024-05-01T18:00:00Z", 30, Some("PC"))).unwrap();
+        user.log_session(2, request("2024-05-02T18:00:00Z", 120, Some("PC"))).unwrap();
+        user.log_session(2, request("2024-06-02T18:00:00Z", 15, Some("Steam Deck"))).unwrap();
+
+        let playtime = user.playtime();
+        let games: Vec<(u64, u64)> = playtime.by_game.iter().map(|game| (game.game_id, game.minutes)).collect();
+        assert_eq!(games, vec![(2, 135), (1, 30)], "Games without sessions are left out");
+        assert_eq!((playtime.totals.minutes, playtime.totals.sessions), (165, 3));
+        assert_eq!(playtime.totals.by_platform[0].minutes, 150);
+        assert_eq!(playtime.totals.by_month.len(), 2);
+    }
+}
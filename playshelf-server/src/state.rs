@@ -0,0 +1,44 @@
+use axum::extract::FromRef;
+use std::sync::Arc;
+
+use crate::auth::SessionKeys;
+use crate::igdb::manager::IGDBManager;
+use crate::reviews::ReviewIndex;
+use crate::store::UserStore;
+
+/// State shared by all request handlers
+#[derive(Clone)]
+pub struct AppState {
+    /// Client for the IGDB API
+    pub igdb: Arc<IGDBManager>,
+    /// Storage for users and their libraries
+    pub users: Arc<dyn UserStore>,
+    /// Keys signing the session tokens handed out at login
+    pub sessions: Arc<SessionKeys>,
+    /// Every user's reviews of each game, kept between requests
+    pub reviews: Arc<ReviewIndex>,
+}
+
+impl FromRef<AppState> for Arc<IGDBManager> {
+    fn from_ref(state: &AppState) -> Self {
+        state.igdb.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn UserStore> {
+    fn from_ref(state: &AppState) -> Self {
+        state.users.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<SessionKeys> {
+    fn from_ref(state: &AppState) -> Self {
+        state.sessions.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<ReviewIndex> {
+    fn from_ref(state: &AppState) -> Self {
+        state.reviews.clone()
+    }
+}
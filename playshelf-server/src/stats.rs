@@ -0,0 +1,176 @@
+//! Breakdowns and trends of a user's shelf
+
+use chrono::{Datelike, NaiveDate};
+use playshelf_types::{LabelCount, LibraryStats, ReleaseYearCount, ShelfStatus, StatusCount, YearTrend};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::user::User;
+
+/// Counts each label, most common first and then by name
+fn label_counts<'a>(labels: impl Iterator<Item = &'a String>) -> Vec<LabelCount> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for label in labels {
+        *counts.entry(label.as_str()).or_default() += 1;
+    }
+    let mut counts: Vec<LabelCount> = counts
+        .into_iter()
+        .map(|(label, count)| LabelCount {
+            label: label.to_string(),
+            count,
+        })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.label.cmp(&b.label)));
+    counts
+}
+
+/// The trend of the year `date` falls in, added if it is not there yet
+fn year_trend(trends: &mut BTreeMap<i32, YearTrend>, date: NaiveDate) -> &mut YearTrend {
+    let year = date.year();
+    trends.entry(year).or_insert_with(|| YearTrend {
+        year,
+        ..YearTrend::default()
+    })
+}
+
+impl User {
+    pub fn stats(&self) -> LibraryStats {
+        let count = |status: ShelfStatus| self.games.iter().filter(|entry| entry.status == status).count();
+        let owned = self.games.len() - count(ShelfStatus::Wishlist);
+
+        let mut by_release_year: BTreeMap<i32, usize> = BTreeMap::new();
+        for date in self.games.iter().filter_map(|entry| entry.game.first_release_date.as_ref()) {
+            *by_release_year.entry(date.year()).or_default() += 1;
+        }
+        let unknown_release_year = self.games.len() - by_release_year.values().sum::<usize>();
+
+        let mut trends: BTreeMap<i32, YearTrend> = BTreeMap::new();
+        for entry in &self.games {
+            if let Some(date) = entry.added_on {
+                year_trend(&mut trends, date).added += 1;
+            }
+            if let Some(date) = entry.started_on {
+                year_trend(&mut trends, date).started += 1;
+            }
+            if let Some(date) = entry.finished_on {
+                match entry.status {
+                    ShelfStatus::Completed => year_trend(&mut trends, date).completed += 1,
+                    ShelfStatus::Abandoned => year_trend(&mut trends, date).abandoned += 1,
+                    _ => {}
+                }
+            }
+            for session in &entry.sessions {
+                year_trend(&mut trends, session.started_at.date_naive()).minutes_played += u64::from(session.duration_minutes);
+            }
+        }
+
+        LibraryStats {
+            username: self.username.clone(),
+            total_games: self.games.len(),
+            by_status: ShelfStatus::ALL
+                .iter()
+                .map(|&status| StatusCount {
+                    status,
+                    count: count(status),
+                })
+                .collect(),
+            by_platform: label_counts(self.games.iter().flat_map(|entry| &entry.game.platforms)),
+            by_genre: label_counts(self.games.iter().flat_map(|entry| &entry.game.genres)),
+            by_release_year: by_release_year
+                .into_iter()
+                .map(|(year, count)| ReleaseYearCount { year, count })
+                .collect(),
+            unknown_release_year,
+            completion_rate: (owned > 0).then(|| count(ShelfStatus::Completed) as f64 / owned as f64),
+            backlog_size: count(ShelfStatus::Backlog),
+            yearly_trends: trends.into_values().collect(),
+            unattributed_minutes_played: self
+                .games
+                .iter()
+                .filter_map(|entry| entry.imported_playtime)
+                .map(|imported| imported.minutes)
+                .sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::game;
+    use playshelf_types::{
+        DatePrecision, ImportedPlaytime, LogSessionRequest, PlaytimeSource, ReleaseDate, ShelfEntry,
+    };
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn entry(id: u64, platforms: &[&str], genres: &[&str], released: Option<i64>) -> ShelfEntry {
+        let mut game = game(id, &format!("Game {}", id), platforms);
+        game.genres = genres.iter().map(|genre| genre.to_string()).collect();
+        game.first_release_date =
+            released.and_then(|timestamp| ReleaseDate::from_timestamp(timestamp, DatePrecision::Day));
+        ShelfEntry::new(game, day(2023, 1, 1))
+    }
+
+    #[test]
+    fn test_empty_shelf() {
+        let stats = User::new("link".to_string(), "Link".to_string(), String::new()).stats();
+        assert_eq!(stats.total_games, 0);
+        assert_eq!(stats.completion_rate, None);
+        assert_eq!(stats.by_status.len(), ShelfStatus::ALL.len());
+        assert!(stats.yearly_trends.is_empty());
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut user = User::new("link".to_string(), "Link".to_string(), String::new());
+        // 2017-03-03 and 2015-05-19
+        let mut zelda = entry(1, &["Nintendo Switch", "Wii U"], &["Adventure", "RPG"], Some(1488499200));
+        zelda.set_status(ShelfStatus::Completed, day(2023, 12, 24));
+        let mut witcher = entry(2, &["PC"], &["RPG"], Some(1431993600));
+        witcher.set_status(ShelfStatus::Playing, day(2024, 2, 1));
+        let mut wished = entry(3, &["PC", "Nintendo Switch"], &[], None);
+        wished.set_status(ShelfStatus::Wishlist, day(2024, 3, 1));
+        let mut backlog = entry(4, &["Nintendo Switch"], &["Adventure"], Some(1488499200));
+        backlog.imported_playtime = Some(ImportedPlaytime {
+            minutes: 600,
+            source: PlaytimeSource::Steam,
+        });
+        for entry in [zelda, witcher, wished, backlog] {
+            user.add_game(entry);
+        }
+        let session = LogSessionRequest {
+            started_at: "2024-02-01T20:00:00Z".parse().unwrap(),
+            ended_at: None,
+            duration_minutes: Some(90),
+            platform: None,
+            note: None,
+        };
+        user.log_session(2, session).unwrap();
+
+        let stats = user.stats();
+        assert_eq!(stats.total_games, 4);
+        let platforms: Vec<(&str, usize)> =
+            stats.by_platform.iter().map(|platform| (platform.label.as_str(), platform.count)).collect();
+        assert_eq!(platforms, vec![("Nintendo Switch", 3), ("PC", 2), ("Wii U", 1)]);
+        let genres: Vec<(&str, usize)> =
+            stats.by_genre.iter().map(|genre| (genre.label.as_str(), genre.count)).collect();
+        assert_eq!(genres, vec![("Adventure", 2), ("RPG", 2)]);
+        assert_eq!(
+            stats.by_release_year,
+            vec![ReleaseYearCount { year: 2015, count: 1 }, ReleaseYearCount { year: 2017, count: 2 }]
+        );
+        assert_eq!(stats.unknown_release_year, 1);
+        assert_eq!(stats.completion_rate, Some(1.0 / 3.0), "The wishlist is not owned");
+        assert_eq!(stats.backlog_size, 1);
+
+        let years: Vec<(i32, usize, usize, usize, u64)> = stats
+            .yearly_trends
+            .iter()
+            .map(|trend| (trend.year, trend.added, trend.started, trend.completed, trend.minutes_played))
+            .collect();
+        assert_eq!(years, vec![(2023, 4, 1, 1, 0), (2024, 0, 1, 0, 90)]);
+        assert_eq!(stats.unattributed_minutes_played, 600, "Imported playtime counts towards no year");
+    }
+}
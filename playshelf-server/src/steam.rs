@@ -0,0 +1,157 @@
+//! Importing a Steam library from a saved `GetOwnedGames` response
+//!
+//! Steam app IDs are matched to IGDB games by the handler; this module adds
+//! the matches to the shelf with their playtime.
+
+use chrono::{DateTime, Utc};
+use playshelf_types::{GameData, ImportedPlaytime, PlaytimeSource, ShelfEntry, SteamOwnedGame};
+use std::collections::HashMap;
+
+use crate::user::User;
+
+/// What a Steam import did to each game
+#[derive(Debug, Default, PartialEq)]
+pub struct SteamImport {
+    pub imported: Vec<u64>,
+    pub playtime_added: Vec<u64>,
+    pub skipped: Vec<u64>,
+    pub unmatched: Vec<SteamOwnedGame>,
+}
+
+impl User {
+    /// Adds the Steam games matched to IGDB `games` (keyed by app ID) to the
+    /// shelf as of `now`, with their Steam playtime as imported playtime
+    ///
+    /// Statuses are left at backlog, as Steam does not know which games were
+    /// finished. Steam only keeps a total per game, so it is stored as one
+    /// undated figure rather than as sessions. Apps matched to the same IGDB
+    /// game have their playtime added up. Games already on the shelf only get
+    /// their playtime, and only if they have no sessions or imported playtime,
+    /// so importing again counts nothing twice.
+    pub fn import_steam(
+        &mut self,
+        owned: Vec<SteamOwnedGame>,
+        games: &HashMap<u64, GameData>,
+        now: DateTime<Utc>,
+    ) -> SteamImport {
+        let mut import = SteamImport::default();
+        // Playtime of each matched game, in library order
+        let mut matched: Vec<(&GameData, u64)> = Vec::new();
+        let mut positions: HashMap<u64, usize> = HashMap::new();
+        for owned in owned {
+            let Some(game) = games.get(&owned.appid) else {
+                import.unmatched.push(owned);
+                continue;
+            };
+            let minutes = u64::from(owned.playtime_forever);
+            match positions.get(&game.id) {
+                Some(&position) => matched[position].1 += minutes,
+                None => {
+                    positions.insert(game.id, matched.len());
+                    matched.push((game, minutes));
+                }
+            }
+        }
+
+        for (game, minutes) in matched {
+            let game_id = game.id;
+            let added = self.add_game(ShelfEntry::new(game.clone(), now.date_naive()));
+            let unplayed = self
+                .entry_mut(game_id)
+                .filter(|entry| entry.sessions.is_empty() && entry.imported_playtime.is_none());
+            let playtime_added = match unplayed {
+                Some(entry) if minutes > 0 => {
+                    entry.imported_playtime = Some(ImportedPlaytime {
+                        minutes,
+                        source: PlaytimeSource::Steam,
+                    });
+                    true
+                }
+                _ => false,
+            };
+            if added {
+                import.imported.push(game_id);
+            } else if playtime_added {
+                import.playtime_added.push(game_id);
+            } else {
+                import.skipped.push(game_id);
+            }
+        }
+        import
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use chrono::NaiveDate;
+    use playshelf_types::ShelfStatus;
+
+    fn game(id: u64) -> GameData {
+        test_support::game(id, &format!("Game {}", id), &[])
+    }
+
+    fn owned(appid: u64, playtime_forever: u32, rtime_last_played: i64) -> SteamOwnedGame {
+        SteamOwnedGame {
+            appid,
+            name: Some(format!("App {}", appid)),
+            playtime_forever,
+            rtime_last_played,
+        }
+    }
+
+    #[test]
+    fn test_import_steam() {
+        let mut user = User::new("link".to_string(), "Link".to_string(), String::new());
+        let now: DateTime<Utc> = "2024-05-01T12:00:00Z".parse().unwrap();
+        let games = HashMap::from([(620, game(72)), (292030, game(1942)), (400, game(71)), (401, game(71))]);
+        let import = user.import_steam(
+            vec![
+                owned(620, 90, 1_700_000_000),
+                owned(292030, 0, 0),
+                owned(400, 30, 0),
+                owned(9, 5, 0),
+                owned(401, 1440, 0),
+            ],
+            &games,
+            now,
+        );
+        assert_eq!(import.imported, vec![72, 1942, 71]);
+        assert_eq!(import.unmatched, vec![owned(9, 5, 0)]);
+
+        let portal = &user.games[0];
+        assert_eq!(portal.status, ShelfStatus::Backlog);
+        assert_eq!(portal.added_on, NaiveDate::from_ymd_opt(2024, 5, 1));
+        let steam = |minutes| {
+            Some(ImportedPlaytime {
+                minutes,
+                source: PlaytimeSource::Steam,
+            })
+        };
+        assert_eq!(portal.imported_playtime, steam(90));
+        assert!(portal.sessions.is_empty(), "Steam totals are not turned into sessions");
+        assert_eq!(user.games[1].imported_playtime, None, "Unplayed games get no playtime");
+        // Both apps' playtime
+        assert_eq!(user.games[2].imported_playtime, steam(1470));
+
+        let totals = user.playtime().totals;
+        assert_eq!((totals.minutes, totals.unattributed_minutes), (1560, 1560));
+        assert!(totals.by_month.is_empty());
+    }
+
+    #[test]
+    fn test_import_steam_again_counts_nothing_twice() {
+        let mut user = User::new("link".to_string(), "Link".to_string(), String::new());
+        let now: DateTime<Utc> = "2024-05-01T12:00:00Z".parse().unwrap();
+        user.add_game(ShelfEntry::new(game(1942), now.date_naive()));
+        let games = HashMap::from([(620, game(72)), (292030, game(1942))]);
+        let library = vec![owned(620, 90, 0), owned(292030, 600, 0)];
+
+        let first = user.import_steam(library.clone(), &games, now);
+        assert_eq!((first.imported, first.playtime_added), (vec![72], vec![1942]));
+        let second = user.import_steam(library, &games, now);
+        assert_eq!(second.skipped, vec![72, 1942]);
+        assert_eq!(user.playtime().totals.minutes, 690);
+    }
+}
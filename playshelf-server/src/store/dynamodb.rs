@@ -0,0 +1,339 @@
+use async_trait::async_trait;
+use aws_sdk_dynamodb::operation::transact_write_items::TransactWriteItemsError;
+use aws_sdk_dynamodb::types::{AttributeValue, Delete, Put, TransactWriteItem};
+use std::collections::HashMap;
+
+use super::{games_json, StoreError, StoreResult, UserStore};
+use crate::user::User;
+
+const USER_ID_ATTRIBUTE: &str = "user_id";
+/// Key of the usernames table
+const USERNAME_ATTRIBUTE: &str = "username";
+/// Condition letting a username claim be written or removed only by its owner
+const OWNS_USERNAME: &str = "attribute_not_exists(username) OR user_id = :user_id";
+/// Global secondary index on `username`, used for lookups by username
+const USERNAME_INDEX: &str = "username-index";
+/// Number of times a user was saved, checked on every update
+const VERSION_ATTRIBUTE: &str = "version";
+
+/// Stores users in a DynamoDB table keyed by `user_id`
+///
+/// Table setup:
+/// - the users table has the string partition key `user_id` and a global
+///   secondary index named `username-index` with the string partition key
+///   `username`, projecting at least `user_id`
+/// - the usernames table has the string partition key `username`
+///
+/// DynamoDB cannot put a unique constraint on the `username` index, so a
+/// second table keyed by `username` holds one claim item per user. Every
+/// write that adds, renames or removes a user updates both tables in one
+/// transaction, with conditions that fail if another user holds the username.
+/// Every update rewrites the claim of the user's current username, so users
+/// created before the claims table existed are claimed the next time they
+/// are saved.
+///
+/// Updates only go through if the stored `version` is the one the user was
+/// read at, so concurrent requests cannot overwrite each other's changes.
+/// Users saved before versions existed have no `version` and count as 0.
+pub struct DynamoUserStore {
+    client: aws_sdk_dynamodb::Client,
+    table_name: String,
+    usernames_table: String,
+}
+
+impl DynamoUserStore {
+    /// Creates a store for the users and usernames tables in the given AWS region
+    pub async fn new(region: &str, table_name: &str, usernames_table: &str) -> Self {
+        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(aws_config::Region::new(region.to_string()))
+            .load()
+            .await;
+        Self {
+            client: aws_sdk_dynamodb::Client::new(&config),
+            table_name: table_name.to_string(),
+            usernames_table: usernames_table.to_string(),
+        }
+    }
+
+    /// Builds the item attributes for `user`
+    fn user_to_item(user: &User) -> StoreResult<HashMap<String, AttributeValue>> {
+        let games_json = games_json(&user.games).map_err(backend_error)?;
+        let mut item = HashMap::from([
+            (USER_ID_ATTRIBUTE.to_string(), AttributeValue::S(user.id.to_string())),
+            ("username".to_string(), AttributeValue::S(user.username.clone())),
+            ("name".to_string(), AttributeValue::S(user.name.clone())),
+            ("description".to_string(), AttributeValue::S(user.description.clone())),
+            ("games".to_string(), AttributeValue::S(games_json)),
+            (VERSION_ATTRIBUTE.to_string(), AttributeValue::N(user.version.to_string())),
+        ]);
+        if let Some(password_hash) = &user.password_hash {
+            item.insert("password_hash".to_string(), AttributeValue::S(password_hash.clone()));
+        }
+        if !user.collections.is_empty() {
+            let collections_json = serde_json::to_string(&user.collections).map_err(backend_error)?;
+            item.insert("collections".to_string(), AttributeValue::S(collections_json));
+        }
+        if user.last_collection_id > 0 {
+            item.insert(
+                "last_collection_id".to_string(),
+                AttributeValue::N(user.last_collection_id.to_string()),
+            );
+        }
+        Ok(item)
+    }
+
+    /// Parses a `User` back out of the item attributes
+    fn user_from_item(item: &HashMap<String, AttributeValue>) -> StoreResult<User> {
+        let string_attribute = |name: &str| -> StoreResult<String> {
+            item.get(name)
+                .and_then(|value| value.as_s().ok())
+                .cloned()
+                .ok_or_else(|| StoreError::Backend(format!("item is missing string attribute '{}'", name)))
+        };
+
+        Ok(User {
+            id: string_attribute(USER_ID_ATTRIBUTE)?.parse().map_err(backend_error)?,
+            username: string_attribute("username")?,
+            name: string_attribute("name")?,
+            description: string_attribute("description")?,
+            games: serde_json::from_str(&string_attribute("games")?).map_err(backend_error)?,
+            // Absent on users saved before collections existed
+            collections: match string_attribute("collections") {
+                Ok(json) => serde_json::from_str(&json).map_err(backend_error)?,
+                Err(_) => Vec::new(),
+            },
+            last_collection_id: match item.get("last_collection_id").and_then(|value| value.as_n().ok()) {
+                Some(number) => number.parse().map_err(backend_error)?,
+                None => 0,
+            },
+            password_hash: string_attribute("password_hash").ok(),
+            version: match item.get(VERSION_ATTRIBUTE).and_then(|value| value.as_n().ok()) {
+                Some(version) => version.parse().map_err(backend_error)?,
+                None => 0,
+            },
+        })
+    }
+
+    /// Transaction item writing `user` if `condition` holds
+    fn put_user_item(&self, user: &User, condition: &str) -> StoreResult<TransactWriteItem> {
+        let put = Put::builder()
+            .table_name(&self.table_name)
+            .set_item(Some(Self::user_to_item(user)?))
+            .condition_expression(condition)
+            .build()
+            .map_err(backend_error)?;
+        Ok(TransactWriteItem::builder().put(put).build())
+    }
+
+    /// Transaction item replacing the stored user with `user` if it is still
+    /// named `username` and at `version`
+    fn replace_user_item(&self, user: &User, username: &str, version: u64) -> StoreResult<TransactWriteItem> {
+        let unchanged = match version {
+            0 => format!("attribute_not_exists({})", VERSION_ATTRIBUTE),
+            _ => format!("{} = :version", VERSION_ATTRIBUTE),
+        };
+        let mut put = Put::builder()
+            .table_name(&self.table_name)
+            .set_item(Some(Self::user_to_item(user)?))
+            .condition_expression(format!(
+                "attribute_exists({}) AND username = :username AND {}",
+                USER_ID_ATTRIBUTE, unchanged
+            ))
+            .expression_attribute_values(":username", AttributeValue::S(username.to_string()));
+        if version > 0 {
+            put = put.expression_attribute_values(":version", AttributeValue::N(version.to_string()));
+        }
+        Ok(TransactWriteItem::builder().put(put.build().map_err(backend_error)?).build())
+    }
+
+    /// Transaction item claiming `user.username`, unless another user holds it
+    fn claim_username_item(&self, user: &User) -> StoreResult<TransactWriteItem> {
+        let put = Put::builder()
+            .table_name(&self.usernames_table)
+            .item(USERNAME_ATTRIBUTE, AttributeValue::S(user.username.clone()))
+            .item(USER_ID_ATTRIBUTE, AttributeValue::S(user.id.to_string()))
+            .condition_expression(OWNS_USERNAME)
+            .expression_attribute_values(":user_id", AttributeValue::S(user.id.to_string()))
+            .build()
+            .map_err(backend_error)?;
+        Ok(TransactWriteItem::builder().put(put).build())
+    }
+
+    /// Transaction item giving up the claim of `user_id` on `username`
+    fn release_username_item(&self, user_id: u128, username: &str) -> StoreResult<TransactWriteItem> {
+        let delete = Delete::builder()
+            .table_name(&self.usernames_table)
+            .key(USERNAME_ATTRIBUTE, AttributeValue::S(username.to_string()))
+            .condition_expression(OWNS_USERNAME)
+            .expression_attribute_values(":user_id", AttributeValue::S(user_id.to_string()))
+            .build()
+            .map_err(backend_error)?;
+        Ok(TransactWriteItem::builder().delete(delete).build())
+    }
+
+    /// Runs the writes in one transaction, returning the error paired with the
+    /// first write whose condition failed
+    async fn transact(&self, writes: Vec<(TransactWriteItem, StoreError)>) -> StoreResult<()> {
+        let (items, conflicts): (Vec<_>, Vec<_>) = writes.into_iter().unzip();
+        let result = self
+            .client
+            .transact_write_items()
+            .set_transact_items(Some(items))
+            .send()
+            .await;
+        let Err(e) = result else {
+            return Ok(());
+        };
+
+        if let Some(TransactWriteItemsError::TransactionCanceledException(canceled)) = e.as_service_error() {
+            // One reason per write, in order, with code "None" for those that passed
+            let failed = canceled
+                .cancellation_reasons()
+                .iter()
+                .position(|reason| reason.code() == Some("ConditionalCheckFailed"));
+            if let Some(conflict) = failed.and_then(|index| conflicts.into_iter().nth(index)) {
+                return Err(conflict);
+            }
+        }
+        Err(backend_error(e))
+    }
+}
+
+fn backend_error(e: impl std::fmt::Display) -> StoreError {
+    StoreError::Backend(e.to_string())
+}
+
+#[async_trait]
+impl UserStore for DynamoUserStore {
+    async fn create(&self, user: &User) -> StoreResult<()> {
+        self.transact(vec![
+            (
+                self.put_user_item(user, &format!("attribute_not_exists({})", USER_ID_ATTRIBUTE))?,
+                StoreError::AlreadyExists,
+            ),
+            (self.claim_username_item(user)?, StoreError::UsernameTaken),
+        ])
+        .await
+    }
+
+    async fn get_by_id(&self, id: u128) -> StoreResult<Option<User>> {
+        let resp = self
+            .client
+            .get_item()
+            .table_name(&self.table_name)
+            .key(USER_ID_ATTRIBUTE, AttributeValue::S(id.to_string()))
+            .send()
+            .await
+            .map_err(backend_error)?;
+
+        resp.item().map(Self::user_from_item).transpose()
+    }
+
+    async fn get_by_username(&self, username: &str) -> StoreResult<Option<User>> {
+        let resp = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            .index_name(USERNAME_INDEX)
+            .key_condition_expression("username = :username")
+            .expression_attribute_values(":username", AttributeValue::S(username.to_string()))
+            .limit(1)
+            .send()
+            .await
+            .map_err(backend_error)?;
+
+        // The index only projects keys we can rely on, so re-read the full item
+        let Some(user_id) = resp
+            .items()
+            .first()
+            .and_then(|item| item.get(USER_ID_ATTRIBUTE))
+            .and_then(|value| value.as_s().ok())
+        else {
+            return Ok(None);
+        };
+        self.get_by_id(user_id.parse().map_err(backend_error)?).await
+    }
+
+    async fn update(&self, user: &mut User) -> StoreResult<()> {
+        let mut saved = user.clone();
+        saved.version += 1;
+        // Most updates keep the username, so try that first without reading the
+        // user; the claim is rewritten too, which also claims it for legacy users
+        let kept = self
+            .transact(vec![
+                (self.replace_user_item(&saved, &user.username, user.version)?, StoreError::Conflict),
+                (self.claim_username_item(user)?, StoreError::UsernameTaken),
+            ])
+            .await;
+        if !matches!(kept, Err(StoreError::Conflict)) {
+            if kept.is_ok() {
+                user.version = saved.version;
+            }
+            return kept;
+        }
+
+        // The user is gone, was saved since it was read, or this is a rename,
+        // which moves the claim from the old username to the new one. The user
+        // put only succeeds if the stored user is still the one read here.
+        let current = self.get_by_id(user.id).await?.ok_or(StoreError::NotFound)?;
+        if current.version != user.version || current.username == user.username {
+            return Err(StoreError::Conflict);
+        }
+        self.transact(vec![
+            (self.replace_user_item(&saved, &current.username, user.version)?, StoreError::Conflict),
+            (self.claim_username_item(user)?, StoreError::UsernameTaken),
+            (
+                self.release_username_item(user.id, &current.username)?,
+                StoreError::Backend(format!("username '{}' is claimed by another user", current.username)),
+            ),
+        ])
+        .await?;
+        user.version = saved.version;
+        Ok(())
+    }
+
+    async fn delete(&self, id: u128) -> StoreResult<()> {
+        let user = self.get_by_id(id).await?.ok_or(StoreError::NotFound)?;
+        let delete = Delete::builder()
+            .table_name(&self.table_name)
+            .key(USER_ID_ATTRIBUTE, AttributeValue::S(id.to_string()))
+            .condition_expression(format!("attribute_exists({})", USER_ID_ATTRIBUTE))
+            .build()
+            .map_err(backend_error)?;
+
+        self.transact(vec![
+            (TransactWriteItem::builder().delete(delete).build(), StoreError::NotFound),
+            (
+                self.release_username_item(id, &user.username)?,
+                StoreError::Backend(format!("username '{}' is claimed by another user", user.username)),
+            ),
+        ])
+        .await
+    }
+
+    async fn list(&self) -> StoreResult<Vec<User>> {
+        let mut users = Vec::new();
+        let mut start_key = None;
+        loop {
+            let resp = self
+                .client
+                .scan()
+                .table_name(&self.table_name)
+                .set_exclusive_start_key(start_key)
+                .send()
+                .await
+                .map_err(backend_error)?;
+
+            for item in resp.items() {
+                users.push(Self::user_from_item(item)?);
+            }
+
+            match resp.last_evaluated_key() {
+                Some(key) if !key.is_empty() => start_key = Some(key.clone()),
+                _ => break,
+            }
+        }
+        users.sort_by(|a, b| a.username.cmp(&b.username));
+        Ok(users)
+    }
+}
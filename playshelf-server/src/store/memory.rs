@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+use super::{StoreError, StoreResult, UserStore};
+use crate::user::User;
+
+/// Keeps users in memory; everything is lost when the process exits
+#[derive(Default)]
+pub struct MemoryUserStore {
+    users: RwLock<HashMap<u128, User>>,
+}
+
+impl MemoryUserStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl UserStore for MemoryUserStore {
+    async fn create(&self, user: &User) -> StoreResult<()> {
+        let mut users = self.users.write().await;
+        if users.contains_key(&user.id) {
+            return Err(StoreError::AlreadyExists);
+        }
+        if users.values().any(|other| other.username == user.username) {
+            return Err(StoreError::UsernameTaken);
+        }
+        users.insert(user.id, user.clone());
+        Ok(())
+    }
+
+    async fn get_by_id(&self, id: u128) -> StoreResult<Option<User>> {
+        Ok(self.users.read().await.get(&id).cloned())
+    }
+
+    async fn get_by_username(&self, username: &str) -> StoreResult<Option<User>> {
+        Ok(self
+            .users
+            .read()
+            .await
+            .values()
+            .find(|user| user.username == username)
+            .cloned())
+    }
+
+    async fn update(&self, user: &mut User) -> StoreResult<()> {
+        let mut users = self.users.write().await;
+        if users
+            .values()
+            .any(|other| other.id != user.id && other.username == user.username)
+        {
+            return Err(StoreError::UsernameTaken);
+        }
+        match users.get_mut(&user.id) {
+            Some(existing) if existing.version != user.version => Err(StoreError::Conflict),
+            Some(existing) => {
+                user.version += 1;
+                *existing = user.clone();
+                Ok(())
+            }
+            None => Err(StoreError::NotFound),
+        }
+    }
+
+    async fn delete(&self, id: u128) -> StoreResult<()> {
+        self.users
+            .write()
+            .await
+            .remove(&id)
+            .map(|_| ())
+            .ok_or(StoreError::NotFound)
+    }
+
+    async fn list(&self) -> StoreResult<Vec<User>> {
+        let mut users: Vec<User> = self.users.read().await.values().cloned().collect();
+        users.sort_by(|a, b| a.username.cmp(&b.username));
+        Ok(users)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_store() {
+        super::super::exercise_store(&MemoryUserStore::new()).await;
+    }
+}
@@ -0,0 +1,168 @@
+pub mod dynamodb;
+pub mod memory;
+pub mod sqlite;
+
+use async_trait::async_trait;
+use playshelf_types::ShelfEntry;
+use serde_json::Value;
+use std::fmt;
+
+use crate::user::User;
+
+pub use dynamodb::DynamoUserStore;
+pub use memory::MemoryUserStore;
+pub use sqlite::SqliteUserStore;
+
+/// Errors returned by a `UserStore`
+#[derive(Debug)]
+pub enum StoreError {
+    /// No user exists with the requested id
+    NotFound,
+    /// A user with the same id already exists
+    AlreadyExists,
+    /// Another user already has the username
+    UsernameTaken,
+    /// The user was saved by someone else since it was read
+    Conflict,
+    /// The storage backend failed
+    Backend(String),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::NotFound => write!(f, "user not found"),
+            StoreError::AlreadyExists => write!(f, "user already exists"),
+            StoreError::UsernameTaken => write!(f, "username is already taken"),
+            StoreError::Conflict => write!(f, "user was changed by another request, try again"),
+            StoreError::Backend(message) => write!(f, "storage error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+pub type StoreResult<T> = Result<T, StoreError>;
+
+/// Persistent storage for users and their libraries
+#[async_trait]
+pub trait UserStore: Send + Sync {
+    /// Stores a new user, failing with `AlreadyExists` if the id is taken and
+    /// `UsernameTaken` if another user has the username
+    async fn create(&self, user: &User) -> StoreResult<()>;
+
+    /// Looks up a user by id
+    async fn get_by_id(&self, id: u128) -> StoreResult<Option<User>>;
+
+    /// Looks up a user by username
+    async fn get_by_username(&self, username: &str) -> StoreResult<Option<User>>;
+
+    /// Replaces an existing user, failing with `NotFound` if it does not exist,
+    /// `UsernameTaken` if it was renamed to another user's username and
+    /// `Conflict` if it was saved since `user` was read
+    ///
+    /// On success `user.version` is bumped to the version now stored.
+    async fn update(&self, user: &mut User) -> StoreResult<()>;
+
+    /// Deletes a user, failing with `NotFound` if it does not exist
+    async fn delete(&self, id: u128) -> StoreResult<()>;
+
+    /// Returns all users ordered by username
+    async fn list(&self) -> StoreResult<Vec<User>>;
+}
+
+/// Serializes a shelf for storage, keeping only the `image_id` of each image
+///
+/// Game data is copied into every entry, so the image URLs would take up most
+/// of a stored library; they are rebuilt from the ID when it is read back.
+pub(crate) fn games_json(games: &[ShelfEntry]) -> serde_json::Result<String> {
+    let mut games = serde_json::to_value(games)?;
+    for entry in games.as_array_mut().into_iter().flatten() {
+        let game = &mut entry["game"];
+        if let Some(cover) = game.get_mut("cover") {
+            keep_image_id(cover);
+        }
+        if let Some(Value::Array(screenshots)) = game.get_mut("screenshots") {
+            screenshots.iter_mut().for_each(keep_image_id);
+        }
+    }
+    serde_json::to_string(&games)
+}
+
+fn keep_image_id(image: &mut Value) {
+    if let Value::Object(fields) = image {
+        fields.retain(|field, _| field == "image_id");
+    }
+}
+
+/// Runs the same checks against any `UserStore` implementation
+#[cfg(test)]
+pub(crate) async fn exercise_store(store: &dyn UserStore) {
+    use chrono::NaiveDate;
+    use playshelf_types::{DatePrecision, GameData, GameImage, ReleaseDate, ShelfEntry, ShelfStatus};
+
+    let mut alice = User::new("alice".to_string(), "Alice".to_string(), "Alice's games".to_string());
+    let bob = User::new("bob".to_string(), "Bob".to_string(), "Bob's games".to_string());
+
+    store.create(&bob).await.expect("Failed to create bob");
+    store.create(&alice).await.expect("Failed to create alice");
+    assert!(matches!(store.create(&alice).await, Err(StoreError::AlreadyExists)));
+
+    assert_eq!(store.get_by_id(alice.id).await.unwrap(), Some(alice.clone()));
+    assert_eq!(store.get_by_username("bob").await.unwrap(), Some(bob.clone()));
+    assert_eq!(store.get_by_username("carol").await.unwrap(), None);
+
+    // Usernames are unique, whether taken on creation or by renaming
+    let impostor = User::new("bob".to_string(), "Not Bob".to_string(), String::new());
+    assert!(matches!(store.create(&impostor).await, Err(StoreError::UsernameTaken)));
+    assert_eq!(store.get_by_id(impostor.id).await.unwrap(), None);
+    let mut renamed = alice.clone();
+    renamed.username = "bob".to_string();
+    assert!(matches!(store.update(&mut renamed).await, Err(StoreError::UsernameTaken)));
+    assert_eq!(store.get_by_id(alice.id).await.unwrap(), Some(alice.clone()));
+
+    let persona = GameData {
+        id: 7,
+        name: "Persona 5".to_string(),
+        platforms: vec!["PC".to_string()],
+        first_release_date: ReleaseDate::from_timestamp(1473897600, DatePrecision::Day),
+        genres: vec!["Role-playing (RPG)".to_string()],
+        release_dates: Vec::new(),
+        cover: Some(GameImage::from_image_id("co1r76")),
+        screenshots: Vec::new(),
+    };
+    let mut entry = ShelfEntry::new(persona, NaiveDate::from_ymd_opt(2024, 5, 1).unwrap());
+    entry.set_status(ShelfStatus::Completed, NaiveDate::from_ymd_opt(2024, 6, 30).unwrap());
+    alice.add_game(entry);
+    let favourites = alice.create_collection("Favourites".to_string()).unwrap();
+    alice.collect_game(favourites, 7, None).unwrap();
+    alice.password_hash = Some("$argon2id$v=19$m=19456,t=2,p=1$c2FsdA$aGFzaA".to_string());
+    store.update(&mut alice).await.expect("Failed to update alice");
+    assert_eq!(store.get_by_id(alice.id).await.unwrap(), Some(alice.clone()));
+
+    // Saving a copy read before the last save would undo that save
+    let mut stale = alice.clone();
+    stale.version -= 1;
+    stale.description = "Stale".to_string();
+    assert!(matches!(store.update(&mut stale).await, Err(StoreError::Conflict)));
+    assert_eq!(store.get_by_id(alice.id).await.unwrap(), Some(alice.clone()));
+
+    let users = store.list().await.unwrap();
+    let usernames: Vec<_> = users.iter().map(|u| u.username.as_str()).collect();
+    assert_eq!(usernames, vec!["alice", "bob"]);
+
+    // A rename frees the old username
+    alice.username = "alicia".to_string();
+    store.update(&mut alice).await.expect("Failed to rename alice");
+    assert_eq!(store.get_by_username("alice").await.unwrap(), None);
+    let new_alice = User::new("alice".to_string(), "Alice".to_string(), String::new());
+    store.create(&new_alice).await.expect("The old username should be free");
+
+    store.delete(bob.id).await.expect("Failed to delete bob");
+    assert_eq!(store.get_by_id(bob.id).await.unwrap(), None);
+    assert!(matches!(store.delete(bob.id).await, Err(StoreError::NotFound)));
+    let mut bob = bob;
+    assert!(matches!(store.update(&mut bob).await, Err(StoreError::NotFound)));
+    // Deleting a user frees their username too
+    store.create(&impostor).await.expect("Bob's username should be free");
+}
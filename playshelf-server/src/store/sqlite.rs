@@ -0,0 +1,373 @@
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use super::{games_json, StoreError, StoreResult, UserStore};
+use crate::user::User;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS users (
+        id TEXT PRIMARY KEY,
+        username TEXT NOT NULL,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        games TEXT NOT NULL,
+        password_hash TEXT,
+        collections TEXT NOT NULL DEFAULT '[]',
+        last_collection_id INTEGER NOT NULL DEFAULT 0,
+        version INTEGER NOT NULL DEFAULT 0
+    );
+";
+
+const USER_COLUMNS: &str =
+    "id, username, name, description, games, password_hash, collections, last_collection_id, version";
+
+/// Stores users in a SQLite database, with the library and collections kept as JSON columns
+pub struct SqliteUserStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteUserStore {
+    /// Opens (or creates) the database file at `path`
+    pub fn open(path: &Path) -> StoreResult<Self> {
+        Self::from_connection(Connection::open(path).map_err(backend_error)?)
+    }
+
+    /// Opens a private in-memory database
+    #[cfg(test)]
+    pub fn open_in_memory() -> StoreResult<Self> {
+        Self::from_connection(Connection::open_in_memory().map_err(backend_error)?)
+    }
+
+    fn from_connection(conn: Connection) -> StoreResult<Self> {
+        conn.execute_batch(SCHEMA).map_err(backend_error)?;
+        migrate(&conn)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Runs `f` with the connection on the blocking thread pool
+    async fn with_conn<T, F>(&self, f: F) -> StoreResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> StoreResult<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn
+                .lock()
+                .map_err(|_| StoreError::Backend("SQLite connection lock poisoned".to_string()))?;
+            f(&conn)
+        })
+        .await
+        .map_err(backend_error)?
+    }
+}
+
+/// Brings databases created by older versions up to date
+fn migrate(conn: &Connection) -> StoreResult<()> {
+    let mut statement = conn
+        .prepare("SELECT name FROM pragma_table_info('users')")
+        .map_err(backend_error)?;
+    let columns = statement
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(backend_error)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(backend_error)?;
+    if !columns.iter().any(|column| column == "password_hash") {
+        conn.execute_batch("ALTER TABLE users ADD COLUMN password_hash TEXT")
+            .map_err(backend_error)?;
+    }
+    if !columns.iter().any(|column| column == "collections") {
+        conn.execute_batch("ALTER TABLE users ADD COLUMN collections TEXT NOT NULL DEFAULT '[]'")
+            .map_err(backend_error)?;
+    }
+    if !columns.iter().any(|column| column == "last_collection_id") {
+        conn.execute_batch("ALTER TABLE users ADD COLUMN last_collection_id INTEGER NOT NULL DEFAULT 0")
+            .map_err(backend_error)?;
+    }
+    if !columns.iter().any(|column| column == "version") {
+        conn.execute_batch("ALTER TABLE users ADD COLUMN version INTEGER NOT NULL DEFAULT 0")
+            .map_err(backend_error)?;
+    }
+
+    // Older databases only had a plain index on `username`
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS users_username;
+         CREATE UNIQUE INDEX IF NOT EXISTS users_username_unique ON users (username);",
+    )
+    .map_err(|e| {
+        StoreError::Backend(format!(
+            "cannot enforce unique usernames, rename duplicate users first: {}",
+            e
+        ))
+    })
+}
+
+fn backend_error(e: impl std::fmt::Display) -> StoreError {
+    StoreError::Backend(e.to_string())
+}
+
+/// Maps a failed write, telling a clash on the `username` unique index apart
+/// from one on the primary key
+fn write_error(e: rusqlite::Error) -> StoreError {
+    match &e {
+        rusqlite::Error::SqliteFailure(failure, message) => match failure.extended_code {
+            rusqlite::ffi::SQLITE_CONSTRAINT_PRIMARYKEY => StoreError::AlreadyExists,
+            // SQLite may check the username index before the primary key, so
+            // tell the two apart by the column named in the message
+            rusqlite::ffi::SQLITE_CONSTRAINT_UNIQUE
+                if message.as_deref().is_some_and(|message| message.contains("users.username")) =>
+            {
+                StoreError::UsernameTaken
+            }
+            _ => backend_error(e),
+        },
+        _ => backend_error(e),
+    }
+}
+
+/// Raw column values of a `users` row, decoded into a `User` outside the rusqlite closure
+struct UserRow {
+    id: String,
+    username: String,
+    name: String,
+    description: String,
+    games: String,
+    password_hash: Option<String>,
+    collections: String,
+    last_collection_id: i64,
+    version: i64,
+}
+
+impl UserRow {
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            username: row.get(1)?,
+            name: row.get(2)?,
+            description: row.get(3)?,
+            games: row.get(4)?,
+            password_hash: row.get(5)?,
+            collections: row.get(6)?,
+            last_collection_id: row.get(7)?,
+            version: row.get(8)?,
+        })
+    }
+
+    fn into_user(self) -> StoreResult<User> {
+        Ok(User {
+            id: self.id.parse().map_err(backend_error)?,
+            username: self.username,
+            name: self.name,
+            description: self.description,
+            games: serde_json::from_str(&self.games).map_err(backend_error)?,
+            collections: serde_json::from_str(&self.collections).map_err(backend_error)?,
+            last_collection_id: self.last_collection_id.try_into().map_err(backend_error)?,
+            password_hash: self.password_hash,
+            version: u64::try_from(self.version).map_err(backend_error)?,
+        })
+    }
+}
+
+#[async_trait]
+impl UserStore for SqliteUserStore {
+    async fn create(&self, user: &User) -> StoreResult<()> {
+        let user = user.clone();
+        let games = games_json(&user.games).map_err(backend_error)?;
+        let collections = serde_json::to_string(&user.collections).map_err(backend_error)?;
+        let last_collection_id = i64::try_from(user.last_collection_id).map_err(backend_error)?;
+        self.with_conn(move |conn| {
+            conn.execute(
+                &format!("INSERT INTO users ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)", USER_COLUMNS),
+                params![
+                    user.id.to_string(),
+                    user.username,
+                    user.name,
+                    user.description,
+                    games,
+                    user.password_hash,
+                    collections,
+                    last_collection_id,
+                    user.version
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| {
+                // Re-creating a user clashes on both indexes; report the ID
+                let exists = conn
+                    .query_row("SELECT 1 FROM users WHERE id = ?1", params![user.id.to_string()], |_| Ok(()))
+                    .is_ok();
+                if exists { StoreError::AlreadyExists } else { write_error(e) }
+            })
+        })
+        .await
+    }
+
+    async fn get_by_id(&self, id: u128) -> StoreResult<Option<User>> {
+        self.with_conn(move |conn| {
+            conn.query_row(
+                &format!("SELECT {} FROM users WHERE id = ?1", USER_COLUMNS),
+                params![id.to_string()],
+                UserRow::from_row,
+            )
+            .optional()
+            .map_err(backend_error)
+        })
+        .await?
+        .map(UserRow::into_user)
+        .transpose()
+    }
+
+    async fn get_by_username(&self, username: &str) -> StoreResult<Option<User>> {
+        let username = username.to_string();
+        self.with_conn(move |conn| {
+            conn.query_row(
+                &format!("SELECT {} FROM users WHERE username = ?1", USER_COLUMNS),
+                params![username],
+                UserRow::from_row,
+            )
+            .optional()
+            .map_err(backend_error)
+        })
+        .await?
+        .map(UserRow::into_user)
+        .transpose()
+    }
+
+    async fn update(&self, user: &mut User) -> StoreResult<()> {
+        let saved = user.clone();
+        let games = games_json(&user.games).map_err(backend_error)?;
+        let collections = serde_json::to_string(&user.collections).map_err(backend_error)?;
+        let last_collection_id = i64::try_from(user.last_collection_id).map_err(backend_error)?;
+        self.with_conn(move |conn| {
+            let updated = conn
+                .execute(
+                    "UPDATE users SET username = ?2, name = ?3, description = ?4, games = ?5, password_hash = ?6, \
+                     collections = ?7, last_collection_id = ?8, version = ?9 + 1 WHERE id = ?1 AND version = ?9",
+                    params![
+                        saved.id.to_string(),
+                        saved.username,
+                        saved.name,
+                        saved.description,
+                        games,
+                        saved.password_hash,
+                        collections,
+                        last_collection_id,
+                        saved.version
+                    ],
+                )
+                .map_err(write_error)?;
+            if updated > 0 {
+                return Ok(());
+            }
+            let exists = conn
+                .query_row("SELECT 1 FROM users WHERE id = ?1", params![saved.id.to_string()], |_| Ok(()))
+                .optional()
+                .map_err(backend_error)?;
+            Err(if exists.is_some() { StoreError::Conflict } else { StoreError::NotFound })
+        })
+        .await?;
+        user.version += 1;
+        Ok(())
+    }
+
+    async fn delete(&self, id: u128) -> StoreResult<()> {
+        let deleted = self
+            .with_conn(move |conn| {
+                conn.execute("DELETE FROM users WHERE id = ?1", params![id.to_string()])
+                    .map_err(backend_error)
+            })
+            .await?;
+        if deleted == 0 {
+            return Err(StoreError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn list(&self) -> StoreResult<Vec<User>> {
+        self.with_conn(|conn| {
+            let mut statement = conn
+                .prepare(&format!("SELECT {} FROM users ORDER BY username", USER_COLUMNS))
+                .map_err(backend_error)?;
+            let rows = statement
+                .query_map([], UserRow::from_row)
+                .map_err(backend_error)?
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .map_err(backend_error)?;
+            Ok(rows)
+        })
+        .await?
+        .into_iter()
+        .map(UserRow::into_user)
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sqlite_store() {
+        let store = SqliteUserStore::open_in_memory().expect("Failed to open SQLite store");
+        super::super::exercise_store(&store).await;
+    }
+
+    #[test]
+    fn test_sqlite_migrates_old_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id TEXT PRIMARY KEY, username TEXT NOT NULL, name TEXT NOT NULL, \
+             description TEXT NOT NULL, games TEXT NOT NULL);
+             INSERT INTO users VALUES ('1', 'alice', 'Alice', '', '[]');",
+        )
+        .unwrap();
+        let store = SqliteUserStore::from_connection(conn).expect("Failed to migrate");
+        let conn = store.conn.lock().unwrap();
+        let hash: Option<String> = conn
+            .query_row("SELECT password_hash FROM users WHERE id = '1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(hash, None);
+        let collections: String = conn
+            .query_row("SELECT collections FROM users WHERE id = '1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(collections, "[]");
+        let version: u64 = conn
+            .query_row("SELECT version FROM users WHERE id = '1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, 0);
+        let duplicate = conn.execute(
+            "INSERT INTO users (id, username, name, description, games) VALUES ('2', 'alice', '', '', '[]')",
+            [],
+        );
+        assert!(matches!(duplicate.map_err(write_error), Err(StoreError::UsernameTaken)));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_stores_image_ids_only() {
+        let store = SqliteUserStore::open_in_memory().expect("Failed to open SQLite store");
+        let mut user = User::new("alice".to_string(), "Alice".to_string(), String::new());
+        let game = serde_json::from_value(serde_json::json!({
+            "id": 7,
+            "name": "Persona 5",
+            "platforms": [],
+            "genres": [],
+            "cover": {"image_id": "co1r76"},
+            "screenshots": [{"image_id": "sc5yr2"}],
+        }))
+        .unwrap();
+        user.add_game(playshelf_types::ShelfEntry::new(game, chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()));
+        store.create(&user).await.expect("Failed to create alice");
+
+        let games: String = {
+            let conn = store.conn.lock().unwrap();
+            conn.query_row("SELECT games FROM users", [], |row| row.get(0)).unwrap()
+        };
+        assert!(games.contains(r#""cover":{"image_id":"co1r76"}"#), "{}", games);
+        assert!(!games.contains("https://"), "{}", games);
+        assert_eq!(store.get_by_id(user.id).await.unwrap(), Some(user));
+    }
+}
@@ -0,0 +1,31 @@
+//! Fixtures shared by the unit tests
+
+use chrono::NaiveDate;
+use playshelf_types::{GameData, ShelfEntry};
+
+use crate::user::User;
+
+/// A game with no release date, genres or images
+pub(crate) fn game(id: u64, name: &str, platforms: &[&str]) -> GameData {
+    GameData {
+        id,
+        name: name.to_string(),
+        platforms: platforms.iter().map(|platform| platform.to_string()).collect(),
+        first_release_date: None,
+        genres: Vec::new(),
+        release_dates: Vec::new(),
+        cover: None,
+        screenshots: Vec::new(),
+    }
+}
+
+/// A user named link with a backlog of the given games, named "Game {id}" and
+/// added on 2024-05-01
+pub(crate) fn shelf_of(game_ids: &[u64]) -> User {
+    let mut user = User::new("link".to_string(), "Link".to_string(), String::new());
+    let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+    for &id in game_ids {
+        user.add_game(ShelfEntry::new(game(id, &format!("Game {}", id), &[]), today));
+    }
+    user
+}
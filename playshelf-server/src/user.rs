@@ -1,20 +1,34 @@
-use aws_sdk_dynamodb::types::AttributeValue;
 use serde::{Deserialize, Serialize};
-use serde_json;
 use uuid::Uuid;
 
-use crate::igdb::manager::GameData;
-
-const USER_TABLE_NAME: &str = "playshelf_user";
-const USER_ID_ATTRIBUTE: &str = "user_id";
+use playshelf_types::{Collection, ShelfEntry, UserResponse};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct User {
+    #[serde(with = "playshelf_types::user_id")]
     pub id: u128,
     pub username: String,
     pub name: String,
     pub description: String,
-    pub games: Vec<GameData>,
+    /// The user's shelf; stored libraries of bare games load as backlog entries
+    pub games: Vec<ShelfEntry>,
+    /// Named lists of games from `games`, in the user's order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collections: Vec<Collection>,
+    /// Highest collection ID handed out so far, so IDs of deleted collections are not reused
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub last_collection_id: u64,
+    /// Argon2 hash of the password, `None` for accounts created before sign-in existed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_hash: Option<String>,
+    /// Number of times the user was saved, so a store can refuse to overwrite
+    /// changes it has not seen
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub version: u64,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
 }
 
 impl User {
@@ -25,60 +39,64 @@ impl User {
             name,
             description,
             games: Vec::new(),
+            collections: Vec::new(),
+            last_collection_id: 0,
+            password_hash: None,
+            version: 0,
+        }
+    }
+
+    /// Adds an entry to the shelf, returning false if its game is already there
+    pub fn add_game(&mut self, entry: ShelfEntry) -> bool {
+        if self.games.iter().any(|e| e.game.id == entry.game.id) {
+            return false;
         }
+        self.games.push(entry);
+        true
     }
 
-    pub fn add_game(&mut self, game: GameData) {
-        self.games.push(game);
+    /// Removes a game's entry from the shelf and from every collection,
+    /// returning false if it was not there
+    pub fn remove_game(&mut self, game_id: u64) -> bool {
+        let len = self.games.len();
+        self.games.retain(|e| e.game.id != game_id);
+        if self.games.len() == len {
+            return false;
+        }
+        for collection in &mut self.collections {
+            collection.game_ids.retain(|&id| id != game_id);
+        }
+        true
     }
 
-    pub fn remove_game(&mut self, game: GameData) {
-        self.games.retain(|g| g.id != game.id);
+    /// Returns the shelf entry of a game, to change its status
+    pub fn entry_mut(&mut self, game_id: u64) -> Option<&mut ShelfEntry> {
+        self.games.iter_mut().find(|e| e.game.id == game_id)
     }
 
-    pub fn get_games(&self) -> &Vec<GameData> {
+    pub fn get_games(&self) -> &Vec<ShelfEntry> {
         &self.games
     }
+}
 
-    pub async fn update_db(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-            .region("us-east-2") // Ensure this matches your SSM region
-            .load()
-            .await;
-        let client = aws_sdk_dynamodb::Client::new(&config);
-        let id_av = AttributeValue::S(self.id.to_string());
-        let username_av = AttributeValue::S(self.username.clone());
-        let name_av = AttributeValue::S(self.name.clone());
-        let description_av = AttributeValue::S(self.description.clone());
-        let games_json = serde_json::to_string(&self.games).unwrap();
-        let games_av = AttributeValue::S(games_json);
-
-        let request = client
-            .put_item()
-            .table_name(USER_TABLE_NAME)
-            .item(USER_ID_ATTRIBUTE, id_av)
-            .item("username", username_av)
-            .item("name", name_av)
-            .item("description", description_av)
-            .item("games", games_av);
-
-        println!("Executing request [{request:?}] to add item...");
-
-        let resp = request.send().await?;
-
-        if let Some(attributes) = resp.attributes() {
-            println!("Response attributes: {attributes:?}");
-        } else {
-            println!("PutItem succeeded (no attributes returned)");
+impl From<&User> for UserResponse {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id,
+            username: user.username.clone(),
+            name: user.name.clone(),
+            description: user.description.clone(),
+            games: user.games.clone(),
+            collections: user.collections.clone(),
         }
-
-        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::NaiveDate;
+    use playshelf_types::{DatePrecision, GameData, ReleaseDate, ShelfStatus};
 
     const TEST_USERNAME: &str = "testuser";
     const TEST_NAME: &str = "Test User";
@@ -96,6 +114,38 @@ mod tests {
         assert_eq!(user.description, TEST_DESCRIPTION);
     }
 
+    #[test]
+    fn test_user_add_and_remove_game() {
+        let mut user = User::new(
+            TEST_USERNAME.to_string(),
+            TEST_NAME.to_string(),
+            TEST_DESCRIPTION.to_string(),
+        );
+        let game = GameData {
+            id: 42,
+            name: "Celeste".to_string(),
+            platforms: vec!["PC".to_string()],
+            first_release_date: ReleaseDate::from_timestamp(1516665600, DatePrecision::Day),
+            genres: vec!["Platform".to_string()],
+            release_dates: Vec::new(),
+            cover: None,
+            screenshots: Vec::new(),
+        };
+
+        let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        assert!(user.add_game(ShelfEntry::new(game.clone(), today)));
+        assert!(!user.add_game(ShelfEntry::new(game, today)), "Adding the same game twice should fail");
+        assert_eq!(user.get_games().len(), 1);
+
+        user.entry_mut(42).unwrap().set_status(ShelfStatus::Playing, today);
+        assert_eq!(user.get_games()[0].started_on, Some(today));
+        assert!(user.entry_mut(7).is_none());
+
+        assert!(!user.remove_game(7), "Removing a missing game should fail");
+        assert!(user.remove_game(42));
+        assert!(user.get_games().is_empty());
+    }
+
     #[test]
     fn test_user_golden_file() {
         use serde_json::json;
@@ -140,14 +190,17 @@ mod tests {
         assert_eq!(user.games.len(), 2, "User should have 2 games");
 
         // Check first game
-        let game1 = &user.games[0];
+        assert_eq!(user.games[0].status, ShelfStatus::Completed);
+        assert_eq!(user.games[0].finished_on, NaiveDate::from_ymd_opt(2017, 12, 24));
+        let game1 = &user.games[0].game;
         assert_eq!(game1.id, 0, "First game id should be 0");
         assert_eq!(
             game1.name, "The Legend of Zelda: Breath of the Wild",
             "First game name should match"
         );
         assert_eq!(
-            game1.first_release_date, "1488499200",
+            game1.first_release_date.map(|date| date.label()).as_deref(),
+            Some("March 3, 2017"),
             "First game release date should match"
         );
         assert_eq!(
@@ -162,11 +215,13 @@ mod tests {
         );
 
         // Check second game
-        let game2 = &user.games[1];
+        assert_eq!(user.games[1].status, ShelfStatus::Playing);
+        let game2 = &user.games[1].game;
         assert_eq!(game2.id, 1, "Second game id should be 1");
         assert_eq!(game2.name, "Persona 5", "Second game name should match");
         assert_eq!(
-            game2.first_release_date, "1473897600",
+            game2.first_release_date.map(|date| date.label()).as_deref(),
+            Some("September 15, 2016"),
             "Second game release date should match"
         );
         assert_eq!(
@@ -201,15 +256,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_user_legacy_release_dates() {
+        // Libraries stored before release dates were typed hold string timestamps
+        let legacy = serde_json::json!({
+            "id": 0,
+            "username": TEST_USERNAME,
+            "name": TEST_NAME,
+            "description": TEST_DESCRIPTION,
+            "games": [
+                {"id": 0, "name": "Persona 5", "first_release_date": "1473897600", "platforms": [], "genres": []},
+                {"id": 1, "name": "Hollow Knight: Silksong", "first_release_date": "0", "platforms": [], "genres": []},
+            ]
+        });
+        let user: User = serde_json::from_value(legacy).expect("Failed to deserialize legacy user");
+        assert_eq!(
+            user.games[0].game.first_release_date,
+            ReleaseDate::from_timestamp(1473897600, DatePrecision::Day)
+        );
+        assert_eq!(user.games[1].game.first_release_date, None);
+        // Bare games from before shelf entries existed land in the backlog
+        assert_eq!(user.games[0].status, ShelfStatus::Backlog);
+        assert_eq!(user.games[0].added_on, None);
+
+        // Once saved again they use the typed format
+        let value = serde_json::to_value(&user).expect("Failed to serialize user");
+        assert_eq!(
+            value["games"][0]["game"]["first_release_date"],
+            serde_json::json!({"date": "2016-09-15", "precision": "day"})
+        );
+    }
+
+    #[test]
+    fn test_user_large_id_round_trip() {
+        let user = User::new(
+            TEST_USERNAME.to_string(),
+            TEST_NAME.to_string(),
+            TEST_DESCRIPTION.to_string(),
+        );
+        let value = serde_json::to_value(&user).expect("Failed to serialize user");
+        assert_eq!(value["id"], serde_json::json!(user.id.to_string()));
+        let parsed: User = serde_json::from_value(value).expect("Failed to deserialize user");
+        assert_eq!(parsed, user);
+    }
+
     #[tokio::test]
+    #[ignore = "requires AWS credentials and the playshelf_user (with username-index) and playshelf_username DynamoDB tables"]
     async fn test_user_update_db() {
+        use crate::store::{DynamoUserStore, UserStore};
+
         // Load .env from workspace root (one level up from playshelf-server)
         dotenv::from_path("../.env").ok();
-        let user = User::new(
+        let mut user = User::new(
             TEST_USERNAME.to_string(),
             TEST_NAME.to_string(),
             TEST_DESCRIPTION.to_string(),
         );
-        user.update_db().await.expect("Failed to update user in DB");
+        let store = DynamoUserStore::new("us-east-2", "playshelf_user", "playshelf_username").await;
+        store.create(&user).await.expect("Failed to create user in DB");
+        user.description = "Updated description".to_string();
+        let updated = store.update(&mut user).await;
+        let stored = store.get_by_id(user.id).await;
+        // Remove the rows before asserting, so a failed run leaves nothing behind
+        // and frees the username for the next run
+        store.delete(user.id).await.expect("Failed to delete user from DB");
+        updated.expect("Failed to update user in DB");
+        let stored = stored.expect("Failed to read user from DB").expect("User not found in DB");
+        assert_eq!(stored.description, "Updated description");
     }
 }
@@ -0,0 +1,721 @@
+//! End-to-end tests driving the axum router against a mock IGDB API
+
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Datelike, Utc};
+use common::{StaticSecretProvider, TestApp, CLIENT_ID};
+use playshelf_types::{
+    CollectionDetail, CollectionsResponse, ErrorResponse, GameDetail, GameReviewsResponse, GameSessionsResponse,
+    GamesResponse, ImportItem, ImportPreview, ImportRequest, ImportResponse, ImportRow, LibraryExport, LibraryStats,
+    MatchConfidence, PlaytimeResponse, Rating, ReleaseRegion, SessionResponse, ShelfStatus, SteamImportResponse,
+    UserGamesResponse, UserResponse, UsersResponse, ValidationCode, MAX_IMPORT_ROWS, MAX_STEAM_GAMES,
+};
+use serde_json::{json, Value};
+
+fn game_names(body: &Value) -> Vec<&str> {
+    body["games"]
+        .as_array()
+        .expect("games is not an array")
+        .iter()
+        .map(|game| game["name"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_root() {
+    let app = TestApp::spawn().await;
+    let response = app.client.get(app.url("/")).send().await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "Hello, World!");
+}
+
+#[tokio::test]
+async fn test_get_games_resolves_names_and_paginates() {
+    let app = TestApp::spawn().await;
+
+    let (status, body) = app.get("/games?limit=3").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["count"], 3);
+    assert_eq!(body["has_more"], true);
+    assert_eq!(body["next_offset"], 3);
+
+    let zelda = &body["games"][0];
+    assert_eq!(zelda["name"], "The Legend of Zelda: Breath of the Wild");
+    assert_eq!(zelda["platforms"], json!(["Nintendo Switch", "Wii U"]));
+    assert_eq!(zelda["genres"], json!(["Role-playing (RPG)", "Adventure"]));
+    assert_eq!(zelda["first_release_date"], json!({"date": "2017-03-03", "precision": "day"}));
+    assert_eq!(zelda["cover"]["image_id"], "co3p2d");
+
+    let (status, body) = app.get("/games?limit=3&offset=3").await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GamesResponse = serde_json::from_value(body).expect("Body is not a GamesResponse");
+    assert_eq!(page.games.len(), 1);
+    assert_eq!(page.games[0].name, "Portal 2");
+    assert!(!page.has_more);
+    assert_eq!(page.next_offset, None);
+
+    // The extra result used to detect further pages is requested from IGDB
+    assert!(app.mock.requests_to("games")[0].contains("limit 4;"));
+}
+
+#[tokio::test]
+async fn test_get_games_rejects_invalid_limit() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app.get("/games?limit=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].is_string());
+    // The extra game probing for another page would take IGDB past its 500
+    let (status, _) = app.get("/games?limit=500").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(app.mock.requests_to("games").is_empty());
+
+    let (status, _) = app.get("/games?limit=499").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(app.mock.requests_to("games")[0].contains("limit 500;"));
+}
+
+#[tokio::test]
+async fn test_search_games() {
+    let app = TestApp::spawn().await;
+
+    let (status, body) = app.get("/games/search?query=zelda").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        game_names(&body),
+        vec!["The Legend of Zelda: Breath of the Wild", "Zelda II: The Adventure of Link"]
+    );
+
+    let (status, body) = app.get("/games/search?query=Zelda%22%3B%20fields%20*%3B").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["count"], 0);
+    let searches = app.mock.requests_to("games");
+    assert!(searches[1].contains(r#"search "Zelda\"; fields *;";"#));
+}
+
+#[tokio::test]
+async fn test_game_results_are_cached() {
+    let app = TestApp::spawn().await;
+    app.get("/games/search?query=witcher").await;
+    app.get("/games/search?query=witcher").await;
+    assert_eq!(app.mock.requests_to("games").len(), 1);
+    assert_eq!(app.mock.token_requests(), 1);
+
+    let (status, body) = app.get("/cache/stats").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["games"]["hits"], 1);
+    assert_eq!(body["games"]["misses"], 1);
+}
+
+#[tokio::test]
+async fn test_get_game_detail() {
+    let app = TestApp::spawn().await;
+
+    let (status, body) = app.get("/games/7346").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["name"], "The Legend of Zelda: Breath of the Wild");
+    assert_eq!(body["platforms"], json!(["Nintendo Switch", "Wii U"]));
+    assert_eq!(body["storyline"], "Link awakens from a hundred-year slumber.");
+    assert_eq!(
+        body["involved_companies"],
+        json!([
+            {"name": "Nintendo EPD", "developer": true, "publisher": false},
+            {"name": "Nintendo", "developer": false, "publisher": true},
+        ])
+    );
+    assert_eq!(body["franchises"], json!(["The Legend of Zelda"]));
+    assert_eq!(body["themes"], json!(["Action", "Open world"]));
+    assert_eq!(body["rating"], 94.6);
+    assert_eq!(body["websites"][1]["category"], "wikipedia");
+    assert_eq!(body["similar_games"][0]["id"], 1025);
+    assert_eq!(body["similar_games"][0]["cover"]["image_id"], "co1uii");
+    let detail: GameDetail = serde_json::from_value(body).expect("Body is not a GameDetail");
+    assert_eq!(detail.game.id, 7346);
+    let releases: Vec<(&str, Option<ReleaseRegion>, Option<String>)> = detail
+        .game
+        .release_dates
+        .iter()
+        .map(|release| (release.platform.as_str(), release.region, release.date.map(|date| date.label())))
+        .collect();
+    assert_eq!(
+        releases,
+        vec![
+            ("Wii U", Some(ReleaseRegion::Worldwide), Some("March 3, 2017".to_string())),
+            ("Nintendo Switch", Some(ReleaseRegion::Worldwide), Some("March 3, 2017".to_string())),
+            ("Nintendo Switch", Some(ReleaseRegion::China), None),
+        ]
+    );
+
+    // The precision of the first release date comes from its release date entry
+    let (_, body) = app.get("/games/1025").await;
+    let detail: GameDetail = serde_json::from_value(body).expect("Body is not a GameDetail");
+    assert_eq!(detail.game.first_release_date.map(|date| date.label()).as_deref(), Some("January 1987"));
+
+    let (status, _) = app.get("/games/404").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_invalid_twitch_credentials() {
+    let app = TestApp::spawn_with_secrets(StaticSecretProvider::new(CLIENT_ID, "wrong-secret")).await;
+    let (status, body) = app.get("/games").await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert!(body["error"].as_str().unwrap().contains("Twitch authentication failed"));
+    assert_eq!(app.mock.token_requests(), 0);
+}
+
+#[tokio::test]
+async fn test_register_and_login() {
+    let app = TestApp::spawn().await;
+
+    let user = json!({"username": "link", "name": "Link", "description": "Hero of Hyrule", "password": "triforce"});
+    let (status, body) = app.post("/auth/register", &user).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let session: SessionResponse = serde_json::from_value(body).expect("Body is not a SessionResponse");
+    assert_eq!(session.user.username, "link");
+    let (status, body) = app.post("/auth/register", &user).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["fields"][0]["code"], "taken");
+
+    let short = json!({"username": "zelda", "name": "Zelda", "password": "short"});
+    let (status, body) = app.post("/auth/register", &short).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["error"].as_str().unwrap().contains("at least 8 characters"));
+
+    let (status, body) = app.post("/auth/login", &json!({"username": "link", "password": "triforce"})).await;
+    assert_eq!(status, StatusCode::OK);
+    let token = body["token"].as_str().unwrap();
+    let (status, body) = app.get_as(token, "/auth/me").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["username"], "link");
+    assert!(body.get("password_hash").is_none(), "The password hash must never be returned");
+
+    // Wrong passwords and unknown users get the same answer
+    let (status, wrong_password) = app.post("/auth/login", &json!({"username": "link", "password": "ganondorf"})).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, unknown_user) = app.post("/auth/login", &json!({"username": "ganon", "password": "triforce"})).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(wrong_password, unknown_user);
+
+    let (status, _) = app.get("/auth/me").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = app.get_as("not-a-token", "/auth/me").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_register_validates_fields() {
+    let app = TestApp::spawn().await;
+
+    // Every problem is reported at once
+    let invalid = json!({"username": "Link!", "name": "", "password": "short"});
+    let (status, body) = app.post("/auth/register", &invalid).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let error: ErrorResponse = serde_json::from_value(body).expect("Body is not an ErrorResponse");
+    let fields: Vec<(&str, ValidationCode)> =
+        error.fields.iter().map(|field| (field.field.as_str(), field.code)).collect();
+    assert_eq!(
+        fields,
+        vec![
+            ("username", ValidationCode::InvalidCharacters),
+            ("name", ValidationCode::Required),
+            ("password", ValidationCode::TooShort),
+        ]
+    );
+    assert_eq!(error.error, error.fields[0].message);
+
+    let reserved = json!({"username": "admin", "name": "Admin", "password": "triforce"});
+    let (status, body) = app.post("/auth/register", &reserved).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["fields"][0]["code"], "reserved");
+}
+
+#[tokio::test]
+async fn test_update_profile() {
+    let app = TestApp::spawn().await;
+    let link = app.register("link", "triforce").await;
+    let zelda = app.register("zelda", "wisdom-of-hyrule").await;
+
+    let (status, _) = app.patch_as(&zelda, "/users/link", &json!({"name": "Not Link"})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, body) = app.patch_as(&link, "/users/link", &json!({"username": "zelda"})).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["fields"][0]["field"], "username");
+    let (status, body) = app.patch_as(&link, "/users/link", &json!({"description": "\u{7}"})).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["fields"][0]["field"], "description");
+
+    // Renaming keeps the session, frees the old username and leaves other fields alone
+    let (status, body) = app
+        .patch_as(&link, "/users/link", &json!({"username": "hero", "description": "Hero of Time"}))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let user: UserResponse = serde_json::from_value(body).expect("Body is not a UserResponse");
+    assert_eq!((user.username.as_str(), user.name.as_str()), ("hero", "link"));
+    assert_eq!(user.description, "Hero of Time");
+    let (_, body) = app.get_as(&link, "/auth/me").await;
+    assert_eq!(body["username"], "hero");
+    let (status, _) = app.get("/users/link").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    app.register("link", "another-password").await;
+}
+
+#[tokio::test]
+async fn test_user_library() {
+    let app = TestApp::spawn().await;
+    let link = app.register("link", "triforce").await;
+    let zelda = app.register("zelda", "wisdom-of-hyrule").await;
+
+    // Add a game exactly as the API returned it
+    let (_, games) = app.get("/games/search?query=witcher").await;
+    let witcher = games["games"][0].clone();
+    let (status, _) = app.post("/users/link/games", &witcher).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = app.post_as(&zelda, "/users/link/games", &witcher).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = app.post_as(&link, "/users/link/games", &witcher).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = app.post_as(&link, "/users/link/games", &witcher).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, body) = app.get("/users/link/games").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["games"][0]["game"], witcher);
+    let library: UserGamesResponse = serde_json::from_value(body).expect("Body is not a UserGamesResponse");
+    assert_eq!(library.count, 1);
+    assert_eq!(library.games[0].status, ShelfStatus::Backlog);
+    assert!(library.games[0].added_on.is_some());
+
+    let (status, body) = app.get("/users").await;
+    assert_eq!(status, StatusCode::OK);
+    let users: UsersResponse = serde_json::from_value(body).expect("Body is not a UsersResponse");
+    assert_eq!(users.users.len(), 2);
+    assert_eq!(users.users[0].games.len(), 1);
+
+    let (status, _) = app.delete("/users/link/games/1942").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = app.delete_as(&zelda, "/users/link/games/1942").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = app.delete_as(&link, "/users/link/games/1942").await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app.delete_as(&link, "/users/link/games/1942").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = app.get("/users/ganon").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_shelf_status() {
+    let app = TestApp::spawn().await;
+    let link = app.register("link", "triforce").await;
+    let zelda = app.register("zelda", "wisdom-of-hyrule").await;
+
+    let (_, games) = app.get("/games/search?query=zelda").await;
+    let today = Utc::now().date_naive();
+    // Dates sent with the entry are ignored; the server adds it today
+    let wishlist = json!({"game": games["games"][0], "status": "wishlist", "added_on": "2024-05-01"});
+    let (status, _) = app.post_as(&link, "/users/link/games", &wishlist).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = app.post_as(&link, "/users/link/games", &games["games"][1]).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let started = json!({"status": "playing", "date": today});
+    let (status, _) = app.put_as(&zelda, "/users/link/games/7346/status", &started).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = app.put_as(&link, "/users/link/games/404/status", &started).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let before_added = json!({"status": "playing", "date": today.pred_opt().unwrap()});
+    let (status, body) = app.put_as(&link, "/users/link/games/7346/status", &before_added).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["fields"][0]["field"], "date");
+    let tomorrow = json!({"status": "playing", "date": today.succ_opt().unwrap()});
+    let (status, _) = app.put_as(&link, "/users/link/games/7346/status", &tomorrow).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, body) = app.put_as(&link, "/users/link/games/7346/status", &started).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["games"][0]["started_on"], json!(today));
+    let finished = json!({"status": "completed", "date": today});
+    app.put_as(&link, "/users/link/games/7346/status", &finished).await;
+
+    let (status, body) = app.get("/users/link/games?status=completed").await;
+    assert_eq!(status, StatusCode::OK);
+    let shelf: UserGamesResponse = serde_json::from_value(body).expect("Body is not a UserGamesResponse");
+    assert_eq!(shelf.count, 1);
+    let entry = &shelf.games[0];
+    assert_eq!(entry.game.id, 7346);
+    assert_eq!(entry.added_on, Some(today));
+    assert_eq!(entry.finished_on, Some(today));
+
+    let (_, body) = app.get("/users/link/games?status=backlog").await;
+    assert_eq!(body["count"], 1);
+    let (status, _) = app.get("/users/link/games?status=finished").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_game_reviews() {
+    let app = TestApp::spawn().await;
+    let link = app.register("link", "triforce").await;
+    let zelda = app.register("zelda", "wisdom-of-hyrule").await;
+    let (_, games) = app.get("/games/search?query=witcher").await;
+    let witcher = &games["games"][0];
+    app.post_as(&link, "/users/link/games", witcher).await;
+    // Reviews can only be set through the review endpoint, which validates them
+    let smuggled = json!({"game": witcher, "rating": 5, "review": "x".repeat(10_001)});
+    let (status, body) = app.post_as(&zelda, "/users/zelda/games", &smuggled).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(body["games"][0].get("rating").is_none());
+    assert!(body["games"][0].get("review").is_none());
+
+    let review = json!({"rating": 4.5, "review": "# Great\n\nGwent alone is worth it."});
+    let (status, _) = app.put_as(&link, "/users/link/games/404/review", &review).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = app.put_as(&zelda, "/users/link/games/1942/review", &review).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = app.put_as(&link, "/users/link/games/1942/review", &json!({"rating": 4.25})).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, body) = app.put_as(&link, "/users/link/games/1942/review", &review).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["games"][0]["rating"], 4.5);
+    app.put_as(&zelda, "/users/zelda/games/1942/review", &json!({"rating": 3})).await;
+
+    let (status, body) = app.get("/games/1942/reviews").await;
+    assert_eq!(status, StatusCode::OK);
+    let reviews: GameReviewsResponse = serde_json::from_value(body).expect("Body is not a GameReviewsResponse");
+    assert_eq!(reviews.rating_count, 2);
+    assert_eq!(reviews.average_rating, Some(3.75));
+    let usernames: Vec<&str> = reviews.reviews.iter().map(|review| review.username.as_str()).collect();
+    assert_eq!(usernames, vec!["link", "zelda"]);
+    assert_eq!(reviews.reviews[1].review, None);
+    assert_eq!(reviews.review_count, 2);
+    assert_eq!(reviews.next_offset, None);
+
+    // Pages split the reviews, while the average still covers all of them
+    let (_, body) = app.get("/games/1942/reviews?limit=1").await;
+    assert_eq!(body["reviews"][0]["username"], "link");
+    assert_eq!(body["next_offset"], 1);
+    assert_eq!(body["average_rating"], 3.75);
+    let (_, body) = app.get("/games/1942/reviews?limit=1&offset=1").await;
+    assert_eq!(body["reviews"][0]["username"], "zelda");
+    assert!(body.get("next_offset").is_none());
+    let (status, _) = app.get("/games/1942/reviews?limit=101").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Removing both takes the user out of the reviews
+    app.put_as(&zelda, "/users/zelda/games/1942/review", &json!({})).await;
+    let (_, body) = app.get("/games/1942/reviews").await;
+    assert_eq!(body["rating_count"], 1);
+    assert_eq!(body["average_rating"], 4.5);
+    let (_, body) = app.get("/games/7346/reviews").await;
+    assert_eq!(body["average_rating"], Value::Null);
+}
+
+#[tokio::test]
+async fn test_collections() {
+    let app = TestApp::spawn().await;
+    let link = app.register("link", "triforce").await;
+    let zelda = app.register("zelda", "wisdom-of-hyrule").await;
+    let (_, games) = app.get("/games?limit=3").await;
+    for game in games["games"].as_array().unwrap() {
+        app.post_as(&link, "/users/link/games", game).await;
+    }
+    let ids: Vec<u64> = games["games"].as_array().unwrap().iter().map(|game| game["id"].as_u64().unwrap()).collect();
+
+    let (status, _) = app.post_as(&zelda, "/users/link/collections", &json!({"name": "Couch co-op"})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = app.post_as(&link, "/users/link/collections", &json!({"name": " "})).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, body) = app.post_as(&link, "/users/link/collections", &json!({"name": "Couch co-op"})).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["collections"][0]["id"], 1);
+    app.post_as(&link, "/users/link/collections", &json!({"name": "Retro 2024"})).await;
+    let (status, body) = app.post_as(&link, "/users/link/collections", &json!({"name": "retro 2024"})).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["fields"][0]["code"], "taken");
+
+    // Games go in manually ordered, and one game can be in both collections
+    for &game_id in &ids {
+        let (status, _) = app.post_as(&link, "/users/link/collections/1/games", &json!({"game_id": game_id})).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+    let (status, _) = app.post_as(&link, "/users/link/collections/2/games", &json!({"game_id": ids[0]})).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = app.post_as(&link, "/users/link/collections/2/games", &json!({"game_id": 404})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let reversed: Vec<u64> = ids.iter().rev().copied().collect();
+    let (status, _) = app.put_as(&link, "/users/link/collections/1/order", &json!({"ids": reversed})).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app.put_as(&link, "/users/link/collections/1/order", &json!({"ids": [ids[0]]})).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, body) = app.get("/users/link/collections/1").await;
+    assert_eq!(status, StatusCode::OK);
+    let couch: CollectionDetail = serde_json::from_value(body).expect("Body is not a CollectionDetail");
+    let order: Vec<u64> = couch.games.iter().map(|entry| entry.game.id).collect();
+    assert_eq!(order, reversed);
+
+    // Rename, reorder and delete the collections themselves
+    let (status, _) = app.patch_as(&link, "/users/link/collections/2", &json!({"name": "Retro 2025"})).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app.put_as(&link, "/users/link/collections/order", &json!({"ids": [2, 1]})).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = app.get("/users/link/collections").await;
+    let collections: CollectionsResponse = serde_json::from_value(body).expect("Body is not a CollectionsResponse");
+    let names: Vec<&str> = collections.collections.iter().map(|collection| collection.name.as_str()).collect();
+    assert_eq!(names, vec!["Retro 2025", "Couch co-op"]);
+
+    let (status, _) = app.delete_as(&link, &format!("/users/link/collections/1/games/{}", ids[1])).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = app.delete_as(&link, "/users/link/collections/2").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["collections"].as_array().unwrap().len(), 1);
+    assert_eq!(body["games"].as_array().unwrap().len(), 3, "Deleting a collection keeps its games");
+    let (status, _) = app.get("/users/link/collections/2").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_play_sessions() {
+    let app = TestApp::spawn().await;
+    let link = app.register("link", "triforce").await;
+    let zelda = app.register("zelda", "wisdom-of-hyrule").await;
+    let (_, games) = app.get("/games/search?query=zelda").await;
+    // Sessions can only be logged through the sessions endpoint, which validates them
+    let smuggled = json!({
+        "game": games["games"][0],
+        "sessions": [{"id": 1, "started_at": "2024-05-01T20:00:00Z", "duration_minutes": 100_000}],
+    });
+    let (status, body) = app.post_as(&link, "/users/link/games", &smuggled).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(body["games"][0].get("sessions").is_none());
+    app.post_as(&link, "/users/link/games", &games["games"][1]).await;
+
+    let timed = json!({
+        "started_at": "2024-05-31T22:00:00Z", "ended_at": "2024-06-01T00:30:00Z", "platform": "Nintendo Switch"
+    });
+    let (status, _) = app.post_as(&zelda, "/users/link/games/7346/sessions", &timed).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = app.post_as(&link, "/users/link/games/404/sessions", &timed).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, body) = app.post_as(&link, "/users/link/games/7346/sessions", &timed).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["games"][0]["sessions"][0]["duration_minutes"], 150);
+
+    let invalid = json!({"started_at": "2024-06-02T20:00:00Z", "note": "\u{7}"});
+    let (status, body) = app.post_as(&link, "/users/link/games/7346/sessions", &invalid).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["fields"].as_array().unwrap().iter().map(|f| f["field"].as_str().unwrap()).collect();
+    assert_eq!(fields, vec!["duration_minutes", "note"]);
+
+    let manual = json!({"started_at": "2024-06-02T20:00:00Z", "duration_minutes": 45, "note": "Shrines"});
+    app.post_as(&link, "/users/link/games/7346/sessions", &manual).await;
+    let other = json!({"started_at": "2024-06-03T20:00:00Z", "duration_minutes": 20, "platform": "Wii U"});
+    let game_id = games["games"][1]["id"].as_u64().unwrap();
+    app.post_as(&link, &format!("/users/link/games/{}/sessions", game_id), &other).await;
+
+    let (status, body) = app.get("/users/link/games/7346/sessions").await;
+    assert_eq!(status, StatusCode::OK);
+    let sessions: GameSessionsResponse = serde_json::from_value(body).expect("Body is not a GameSessionsResponse");
+    assert_eq!(sessions.totals.minutes, 195);
+    assert_eq!(sessions.sessions[0].note.as_deref(), Some("Shrines"));
+    let months: Vec<&str> = sessions.totals.by_month.iter().map(|month| month.month.as_str()).collect();
+    assert_eq!(months, vec!["2024-05", "2024-06"], "Sessions count towards the month they started in");
+
+    let (status, body) = app.get("/users/link/playtime").await;
+    assert_eq!(status, StatusCode::OK);
+    let playtime: PlaytimeResponse = serde_json::from_value(body).expect("Body is not a PlaytimeResponse");
+    assert_eq!(playtime.totals.minutes, 215);
+    assert_eq!(playtime.by_game[0].game_id, 7346);
+    let platforms: Vec<Option<&str>> =
+        playtime.totals.by_platform.iter().map(|platform| platform.platform.as_deref()).collect();
+    assert_eq!(platforms, vec![Some("Nintendo Switch"), None, Some("Wii U")]);
+
+    let id = sessions.sessions[0].id;
+    let path = format!("/users/link/games/7346/sessions/{}", id);
+    let (status, _) = app.delete_as(&link, &path).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app.delete_as(&link, &path).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_library_stats() {
+    let app = TestApp::spawn().await;
+    let link = app.register("link", "triforce").await;
+    let (_, games) = app.get("/games?limit=3").await;
+    for game in games["games"].as_array().unwrap() {
+        app.post_as(&link, "/users/link/games", game).await;
+    }
+    let game_id = games["games"][0]["id"].as_u64().unwrap();
+    let today = Utc::now().date_naive();
+    let finished = json!({"status": "completed", "date": today});
+    app.put_as(&link, &format!("/users/link/games/{}/status", game_id), &finished).await;
+
+    let (status, body) = app.get("/users/link/stats").await;
+    assert_eq!(status, StatusCode::OK);
+    let stats: LibraryStats = serde_json::from_value(body).expect("Body is not a LibraryStats");
+    assert_eq!(stats.total_games, 3);
+    assert_eq!(stats.backlog_size, 2);
+    assert_eq!(stats.completion_rate, Some(1.0 / 3.0));
+    let platform_total: usize =
+        games["games"].as_array().unwrap().iter().map(|game| game["platforms"].as_array().unwrap().len()).sum();
+    assert_eq!(stats.by_platform.iter().map(|platform| platform.count).sum::<usize>(), platform_total);
+    let trend = stats.yearly_trends.iter().find(|trend| trend.year == today.year()).expect("No trend this year");
+    assert_eq!(trend.completed, 1);
+
+    let (status, _) = app.get("/users/nobody/stats").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_library_export() {
+    let app = TestApp::spawn().await;
+    let link = app.register("link", "triforce").await;
+    let (_, games) = app.get("/games/search?query=witcher").await;
+    app.post_as(&link, "/users/link/games", &games["games"][0]).await;
+    let review = json!({"rating": 4.5, "review": "Gwent, \"obviously\""});
+    app.put_as(&link, "/users/link/games/1942/review", &review).await;
+
+    let response = reqwest::get(app.url("/users/link/export")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let disposition = response.headers()["content-disposition"].to_str().unwrap().to_string();
+    assert!(disposition.starts_with("attachment; filename=\"playshelf-link-"));
+    assert!(disposition.ends_with(".json\""));
+    let export: LibraryExport = response.json().await.expect("Body is not a LibraryExport");
+    assert_eq!(export.version, 1);
+    assert_eq!(export.users[0].games[0].review.as_deref(), Some("Gwent, \"obviously\""));
+
+    let response = reqwest::get(app.url("/users/link/export?format=csv")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/csv; charset=utf-8");
+    let csv = response.text().await.unwrap();
+    let mut lines = csv.lines();
+    assert!(lines.next().unwrap().starts_with("game_id,title,platforms"));
+    assert!(lines.next().unwrap().contains("\"Gwent, \"\"obviously\"\"\""));
+
+    let (status, _) = app.get("/users/link/export?format=xml").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = app.get("/users/nobody/export").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_library_import() {
+    let app = TestApp::spawn().await;
+    let link = app.register("link", "triforce").await;
+    let zelda = app.register("zelda", "wisdom-of-hyrule").await;
+    let (_, games) = app.get("/games/search?query=portal").await;
+    app.post_as(&link, "/users/link/games", &games["games"][0]).await;
+
+    let csv = "Title,Platform,Status,Rating\n\
+               The Witcher 3: Wild Hunt,PC,Completed,4.5\n\
+               Zelda,,Playing,\n\
+               Unknown Game,,,\n\
+               Portal 2,PC,Backlog,7\n";
+    let body = json!({"csv": csv});
+    let (status, preview) = app.post_as(&link, "/users/link/import/preview", &body).await;
+    assert_eq!(status, StatusCode::OK);
+    let preview: ImportPreview = serde_json::from_value(preview).unwrap();
+    let [witcher, zelda_row, unknown, portal] = preview.rows.as_slice() else {
+        panic!("Expected 4 rows, got {:?}", preview.rows);
+    };
+    assert_eq!(witcher.confidence, MatchConfidence::Confident);
+    assert_eq!(witcher.candidates[0].game.id, 1942);
+    assert_eq!(witcher.rating, Rating::from_half_stars(9));
+    assert_eq!(zelda_row.confidence, MatchConfidence::Ambiguous);
+    assert_eq!(zelda_row.candidates.len(), 2);
+    assert_eq!(unknown.confidence, MatchConfidence::Unmatched);
+    assert_eq!(portal.on_shelf, Some(72));
+    assert_eq!(portal.line, 5);
+    assert_eq!(portal.warnings.len(), 1, "A rating of 7 stars is left out");
+
+    let (status, _) = app.post_as(&zelda, "/users/link/import/preview", &body).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = app.post("/users/link/import/preview", &body).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = app.post_as(&link, "/users/link/import/preview", &json!({"csv": "game\nPortal"})).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["error"].as_str().unwrap().contains("'title'"));
+
+    // Rows with an IGDB ID are looked up by it, and each title is searched once
+    let searches = app.mock.requests_to("games").len();
+    let csv = "igdb_id,title\n7346,Breath of the Wild\n,Zelda\n,zelda\n";
+    let (status, preview) = app.post_as(&link, "/users/link/import/preview", &json!({"csv": csv})).await;
+    assert_eq!(status, StatusCode::OK);
+    let preview: ImportPreview = serde_json::from_value(preview).unwrap();
+    assert_eq!(preview.rows[0].confidence, MatchConfidence::Confident);
+    assert_eq!(preview.rows[0].candidates[0].game.id, 7346);
+    assert_eq!(app.mock.requests_to("games").len(), searches + 2, "One lookup by ID and one search");
+
+    let item = |game_id, row: &ImportRow| ImportItem {
+        game_id,
+        status: row.status,
+        rating: row.rating,
+    };
+    let items = vec![item(1942, witcher), item(72, portal), item(999_999, unknown)];
+    let (status, body) = app.post_as(&link, "/users/link/import", &json!(ImportRequest { items })).await;
+    assert_eq!(status, StatusCode::OK);
+    let response: ImportResponse = serde_json::from_value(body).unwrap();
+    assert_eq!((response.imported, response.skipped), (vec![1942], vec![72]));
+    assert_eq!(response.not_found, vec![999_999]);
+    let (_, user) = app.get("/users/link").await;
+    assert_eq!(user["games"][1]["game"]["id"], 1942);
+    assert_eq!(user["games"][1]["status"], "completed");
+    assert_eq!(user["games"][1]["rating"], 4.5);
+
+    let items = vec![item(1942, witcher); MAX_IMPORT_ROWS + 1];
+    let (status, _) = app.post_as(&link, "/users/link/import", &json!(ImportRequest { items })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn test_steam_import() {
+    let app = TestApp::spawn().await;
+    let link = app.register("link", "triforce").await;
+    let (_, games) = app.get("/games/search?query=witcher").await;
+    app.post_as(&link, "/users/link/games", &games["games"][0]).await;
+
+    let owned = json!({"response": {"game_count": 3, "games": [
+        {"appid": 620, "name": "Portal 2", "playtime_forever": 754, "rtime_last_played": 1700000000},
+        {"appid": 292030, "name": "The Witcher 3: Wild Hunt", "playtime_forever": 6000, "rtime_last_played": 0},
+        {"appid": 4000, "name": "Garry's Mod", "playtime_forever": 15},
+    ]}});
+    let (status, body) = app.post_as(&link, "/users/link/import/steam", &owned).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let response: SteamImportResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(response.imported, vec![72]);
+    assert_eq!(response.playtime_added, vec![1942]);
+    assert_eq!(response.unmatched.len(), 1);
+    assert_eq!(response.unmatched[0].label(), "Garry's Mod");
+    let requests = app.mock.requests_to("external_games");
+    assert!(requests[0].contains("external_game_source = 1"), "{}", requests[0]);
+    assert_eq!(requests.len(), 1, "A short page ends the lookup");
+
+    // Steam only keeps totals, which count towards no month or year
+    let (_, sessions) = app.get("/users/link/games/72/sessions").await;
+    assert_eq!(sessions["totals"]["minutes"], 754);
+    assert_eq!(sessions["totals"]["unattributed_minutes"], 754);
+    assert_eq!(sessions["sessions"], json!([]));
+    let (_, playtime) = app.get("/users/link/playtime").await;
+    assert_eq!(playtime["totals"]["minutes"], 6754);
+    assert_eq!(playtime["totals"]["by_month"], json!([]));
+    assert_eq!(playtime["by_game"][0]["game_id"], 1942);
+    let (_, stats) = app.get("/users/link/stats").await;
+    assert_eq!(stats["unattributed_minutes_played"], 6754);
+
+    // Importing again counts nothing twice
+    let (_, body) = app.post_as(&link, "/users/link/import/steam", &owned).await;
+    assert_eq!(body["skipped"], json!([72, 1942]));
+    let (_, playtime) = app.get("/users/link/playtime").await;
+    assert_eq!(playtime["totals"]["minutes"], 6754);
+
+    let private = json!({"response": {}});
+    let (status, _) = app.post_as(&link, "/users/link/import/steam", &private).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let huge: Vec<Value> = (0..=MAX_STEAM_GAMES as u64).map(|appid| json!({"appid": appid})).collect();
+    let huge = json!({"response": {"games": huge}});
+    let (status, _) = app.post_as(&link, "/users/link/import/steam", &huge).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, _) = app.post("/users/link/import/steam", &owned).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,408 @@
+//! Local stand-in for the Twitch token endpoint and the IGDB API
+//!
+//! The mock serves canned IGDB records and understands just enough Apicalypse
+//! (`search`, `where id = (...)`, `where uid = (...)`, `limit` and `offset`)
+//! to answer the queries the server sends. Every request body is recorded so tests can assert on it.
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::post,
+    Router,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use playshelf_server::auth::SessionKeys;
+use playshelf_server::igdb::manager::{IGDBConfig, IGDBManager};
+use playshelf_server::igdb::secrets::{Secret, SecretError, SecretProvider};
+use playshelf_server::reviews::{ReviewIndex, REVIEW_INDEX_TTL};
+use playshelf_server::state::AppState;
+use playshelf_server::store::MemoryUserStore;
+
+pub const CLIENT_ID: &str = "mock-client-id";
+pub const CLIENT_SECRET: &str = "mock-client-secret";
+const ACCESS_TOKEN: &str = "mock-access-token";
+const SESSION_SIGNING_KEY: &str = "mock-session-signing-key";
+
+/// Returns fixed Twitch credentials
+pub struct StaticSecretProvider {
+    client_id: String,
+    client_secret: String,
+}
+
+impl StaticSecretProvider {
+    pub fn new(client_id: &str, client_secret: &str) -> Self {
+        Self {
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl SecretProvider for StaticSecretProvider {
+    async fn get_secret(&self, secret: Secret) -> Result<String, SecretError> {
+        Ok(match secret {
+            Secret::TwitchClientId => self.client_id.clone(),
+            Secret::TwitchClientSecret => self.client_secret.clone(),
+            Secret::SessionSigningKey => SESSION_SIGNING_KEY.to_string(),
+        })
+    }
+}
+
+/// A request received by the mock IGDB API
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub endpoint: String,
+    pub body: String,
+}
+
+#[derive(Default)]
+struct MockState {
+    requests: Mutex<Vec<RecordedRequest>>,
+    token_requests: Mutex<usize>,
+}
+
+/// A running mock server
+pub struct MockIgdb {
+    pub base_url: String,
+    state: Arc<MockState>,
+}
+
+impl MockIgdb {
+    /// Starts the mock on an ephemeral local port
+    pub async fn start() -> Self {
+        let state = Arc::new(MockState::default());
+        let app = Router::new()
+            .route("/oauth2/token", post(token_handler))
+            .route("/v4/{endpoint}", post(igdb_handler))
+            .with_state(state.clone());
+        let base_url = serve(app).await;
+        Self { base_url, state }
+    }
+
+    /// Configuration pointing the IGDB manager at this mock
+    pub fn config(&self) -> IGDBConfig {
+        IGDBConfig {
+            igdb_url: self.base_url.clone(),
+            token_url: format!("{}/oauth2/token", self.base_url),
+            ..IGDBConfig::default()
+        }
+    }
+
+    /// Request bodies received by the IGDB `endpoint`, e.g. `games`
+    pub fn requests_to(&self, endpoint: &str) -> Vec<String> {
+        self.state
+            .requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.endpoint == endpoint)
+            .map(|request| request.body.clone())
+            .collect()
+    }
+
+    /// Number of tokens handed out
+    pub fn token_requests(&self) -> usize {
+        *self.state.token_requests.lock().unwrap()
+    }
+}
+
+/// Serves `app` on an ephemeral local port and returns its base URL
+async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind test listener");
+    let address = listener.local_addr().expect("Failed to read listener address");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("Test server failed");
+    });
+    format!("http://{}", address)
+}
+
+/// A PlayShelf server running against the mock IGDB API and an in-memory store
+pub struct TestApp {
+    pub base_url: String,
+    pub client: reqwest::Client,
+    pub mock: MockIgdb,
+}
+
+impl TestApp {
+    pub async fn spawn() -> Self {
+        Self::spawn_with_secrets(StaticSecretProvider::new(CLIENT_ID, CLIENT_SECRET)).await
+    }
+
+    pub async fn spawn_with_secrets(secrets: StaticSecretProvider) -> Self {
+        let mock = MockIgdb::start().await;
+        let state = AppState {
+            igdb: Arc::new(IGDBManager::with_config(Arc::new(secrets), mock.config())),
+            users: Arc::new(MemoryUserStore::new()),
+            sessions: Arc::new(SessionKeys::new(SESSION_SIGNING_KEY.as_bytes())),
+            reviews: Arc::new(ReviewIndex::new(REVIEW_INDEX_TTL)),
+        };
+        let base_url = serve(playshelf_server::router(state)).await;
+        Self {
+            base_url,
+            client: reqwest::Client::new(),
+            mock,
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Sends a GET request and returns the status and JSON body
+    pub async fn get(&self, path: &str) -> (StatusCode, Value) {
+        self.send(self.client.get(self.url(path)), None).await
+    }
+
+    /// Sends a GET request signed in with the session `token`
+    pub async fn get_as(&self, token: &str, path: &str) -> (StatusCode, Value) {
+        self.send(self.client.get(self.url(path)), Some(token)).await
+    }
+
+    /// Sends a POST request with a JSON body and returns the status and JSON body
+    pub async fn post(&self, path: &str, body: &Value) -> (StatusCode, Value) {
+        self.send(self.client.post(self.url(path)).json(body), None).await
+    }
+
+    /// Sends a DELETE request and returns the status and JSON body
+    pub async fn delete(&self, path: &str) -> (StatusCode, Value) {
+        self.send(self.client.delete(self.url(path)), None).await
+    }
+
+    /// Sends a POST request signed in with the session `token`
+    pub async fn post_as(&self, token: &str, path: &str, body: &Value) -> (StatusCode, Value) {
+        self.send(self.client.post(self.url(path)).json(body), Some(token)).await
+    }
+
+    /// Sends a PUT request with a JSON body signed in with the session `token`
+    pub async fn put_as(&self, token: &str, path: &str, body: &Value) -> (StatusCode, Value) {
+        self.send(self.client.put(self.url(path)).json(body), Some(token)).await
+    }
+
+    /// Sends a PATCH request with a JSON body signed in with the session `token`
+    pub async fn patch_as(&self, token: &str, path: &str, body: &Value) -> (StatusCode, Value) {
+        self.send(self.client.patch(self.url(path)).json(body), Some(token)).await
+    }
+
+    /// Sends a DELETE request signed in with the session `token`
+    pub async fn delete_as(&self, token: &str, path: &str) -> (StatusCode, Value) {
+        self.send(self.client.delete(self.url(path)), Some(token)).await
+    }
+
+    /// Registers `username` and returns their session token
+    pub async fn register(&self, username: &str, password: &str) -> String {
+        let (status, body) = self
+            .post(
+                "/auth/register",
+                &json!({"username": username, "name": username, "password": password}),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED, "Registering {} failed: {}", username, body);
+        body["token"].as_str().expect("No session token").to_string()
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder, token: Option<&str>) -> (StatusCode, Value) {
+        let request = match token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+        json_response(request.send().await.expect("Request failed")).await
+    }
+}
+
+async fn json_response(response: reqwest::Response) -> (StatusCode, Value) {
+    let status = StatusCode::from_u16(response.status().as_u16()).unwrap();
+    let body = response.json().await.unwrap_or(Value::Null);
+    (status, body)
+}
+
+async fn token_handler(
+    State(state): State<Arc<MockState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    let valid = params.get("client_id").map(String::as_str) == Some(CLIENT_ID)
+        && params.get("client_secret").map(String::as_str) == Some(CLIENT_SECRET)
+        && params.get("grant_type").map(String::as_str) == Some("client_credentials");
+    if !valid {
+        return (StatusCode::BAD_REQUEST, Json(json!({"status": 400, "message": "invalid client"})))
+            .into_response();
+    }
+
+    *state.token_requests.lock().unwrap() += 1;
+    Json(json!({
+        "access_token": ACCESS_TOKEN,
+        "expires_in": 5_000_000,
+        "token_type": "bearer",
+    }))
+    .into_response()
+}
+
+async fn igdb_handler(
+    State(state): State<Arc<MockState>>,
+    Path(endpoint): Path<String>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    if header("Client-ID") != Some(CLIENT_ID) || header("Authorization") != Some(&format!("Bearer {}", ACCESS_TOKEN)) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    state.requests.lock().unwrap().push(RecordedRequest {
+        endpoint: endpoint.clone(),
+        body: body.clone(),
+    });
+
+    let records = match endpoint.as_str() {
+        "games" => games(),
+        "platforms" => platforms(),
+        "genres" => genres(),
+        "external_games" => external_games(),
+        _ => return StatusCode::NOT_FOUND.into_response(),
+    };
+    Json(apply_query(records, &body)).into_response()
+}
+
+/// Filters, then pages `records` the way IGDB would for the Apicalypse `body`
+fn apply_query(records: Vec<Value>, body: &str) -> Vec<Value> {
+    let mut records = records;
+    if let Some(term) = search_term(body) {
+        let term = term.to_lowercase();
+        records.retain(|record| record["name"].as_str().unwrap_or_default().to_lowercase().contains(&term));
+    }
+    if let Some(ids) = where_ids(body) {
+        records.retain(|record| record["id"].as_u64().is_some_and(|id| ids.contains(&id)));
+    }
+    if let Some(uids) = where_uids(body) {
+        records.retain(|record| record["uid"].as_str().is_some_and(|uid| uids.iter().any(|wanted| wanted == uid)));
+    }
+    let offset = clause(body, "offset").and_then(|value| value.parse().ok()).unwrap_or(0);
+    let limit = clause(body, "limit").and_then(|value| value.parse().ok()).unwrap_or(10);
+    records.into_iter().skip(offset).take(limit).collect()
+}
+
+/// Returns the value of the `keyword value;` clause
+fn clause<'a>(body: &'a str, keyword: &str) -> Option<&'a str> {
+    body.split(';')
+        .map(str::trim)
+        .find_map(|clause| clause.strip_prefix(keyword)?.strip_prefix(' '))
+}
+
+/// Returns the unescaped term of the `search "term";` clause
+fn search_term(body: &str) -> Option<String> {
+    let quoted = body.split_once("search \"")?.1;
+    let mut term = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => term.extend(chars.next()),
+            '"' => return Some(term),
+            c => term.push(c),
+        }
+    }
+    None
+}
+
+/// Returns the IDs of a `where id = (1,2,3)` condition
+fn where_ids(body: &str) -> Option<Vec<u64>> {
+    let ids = body.split_once(" id = (")?.1.split_once(')')?.0;
+    Some(ids.split(',').filter_map(|id| id.trim().parse().ok()).collect())
+}
+
+/// Returns the unquoted values of a `where uid = ("1","2")` condition
+fn where_uids(body: &str) -> Option<Vec<String>> {
+    let uids = body.split_once(" uid = (")?.1.split_once(')')?.0;
+    Some(uids.split(',').map(|uid| uid.trim().trim_matches('"').to_string()).collect())
+}
+
+fn games() -> Vec<Value> {
+    vec![
+        json!({
+            "id": 7346,
+            "name": "The Legend of Zelda: Breath of the Wild",
+            "platforms": [130, 41],
+            "first_release_date": 1488499200,
+            "genres": [12, 31],
+            "cover": {"id": 172453, "image_id": "co3p2d"},
+            "screenshots": [{"id": 11234, "image_id": "sc5yr2"}],
+            "release_dates": [
+                {"id": 1, "date": 1488499200, "category": 0, "platform": 41, "region": 8},
+                {"id": 2, "category": 7, "platform": 130, "region": 6},
+                {"id": 3, "date": 1488499200, "category": 0, "platform": 130, "region": 8},
+            ],
+            "summary": "Step into a world of discovery, exploration, and adventure.",
+            "storyline": "Link awakens from a hundred-year slumber.",
+            "involved_companies": [
+                {"id": 1, "company": {"id": 70, "name": "Nintendo EPD"}, "developer": true, "publisher": false},
+                {"id": 2, "company": {"id": 71, "name": "Nintendo"}, "developer": false, "publisher": true},
+            ],
+            "franchises": [{"id": 596, "name": "The Legend of Zelda"}],
+            "game_modes": [{"id": 1, "name": "Single player"}],
+            "themes": [{"id": 1, "name": "Action"}, {"id": 38, "name": "Open world"}],
+            "total_rating": 94.6,
+            "total_rating_count": 2100,
+            "websites": [
+                {"id": 1, "url": "https://zelda.nintendo.com/breath-of-the-wild/", "category": 1},
+                {"id": 2, "url": "https://en.wikipedia.org/wiki/Breath_of_the_Wild", "category": 3},
+            ],
+            "similar_games": [{"id": 1025, "name": "Zelda II: The Adventure of Link", "cover": {"id": 1, "image_id": "co1uii"}}],
+        }),
+        json!({
+            "id": 1025,
+            "name": "Zelda II: The Adventure of Link",
+            "platforms": [18],
+            "first_release_date": 538358400,
+            "genres": [12],
+            "release_dates": [{"id": 4, "date": 538358400, "category": 1, "platform": 18, "region": 5}],
+        }),
+        json!({
+            "id": 1942,
+            "name": "The Witcher 3: Wild Hunt",
+            "platforms": [6, 48],
+            "first_release_date": 1431993600,
+            "genres": [12, 31],
+            "cover": {"id": 89386, "image_id": "co1wyy"},
+        }),
+        json!({
+            "id": 72,
+            "name": "Portal 2",
+            "platforms": [6],
+            "first_release_date": 1303171200,
+            "genres": [9],
+        }),
+    ]
+}
+
+fn platforms() -> Vec<Value> {
+    vec![
+        json!({"id": 6, "name": "PC (Microsoft Windows)"}),
+        json!({"id": 18, "name": "Nintendo Entertainment System"}),
+        json!({"id": 41, "name": "Wii U"}),
+        json!({"id": 48, "name": "PlayStation 4"}),
+        json!({"id": 130, "name": "Nintendo Switch"}),
+    ]
+}
+
+fn genres() -> Vec<Value> {
+    vec![
+        json!({"id": 9, "name": "Puzzle"}),
+        json!({"id": 12, "name": "Role-playing (RPG)"}),
+        json!({"id": 31, "name": "Adventure"}),
+    ]
+}
+
+/// Steam (source 1) and GOG (source 5) IDs of the games above
+fn external_games() -> Vec<Value> {
+    vec![
+        json!({"id": 1, "game": 72, "uid": "620", "external_game_source": 1}),
+        json!({"id": 2, "game": 1942, "uid": "292030", "external_game_source": 1}),
+        json!({"id": 3, "game": 1942, "uid": "1207664643", "external_game_source": 5}),
+    ]
+}
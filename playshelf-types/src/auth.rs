@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+use crate::users::UserResponse;
+
+/// Body of `POST /auth/register`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RegisterRequest {
+    pub username: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub password: String,
+}
+
+/// Body of `POST /auth/login`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+/// A signed-in session, returned by `POST /auth/register` and `POST /auth/login`
+///
+/// `token` is sent back as `Authorization: Bearer <token>` on requests that
+/// need the caller's identity.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SessionResponse {
+    pub token: String,
+    /// Unix timestamp in seconds after which the token is rejected
+    pub expires_at: u64,
+    pub user: UserResponse,
+}
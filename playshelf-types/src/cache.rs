@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// Hit/miss counters and current size of a cache
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+/// Hit/miss counters of the IGDB response caches, returned by `GET /cache/stats`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct IGDBCacheStats {
+    pub platforms: CacheStats,
+    pub genres: CacheStats,
+    pub games: CacheStats,
+    pub game_details: CacheStats,
+}
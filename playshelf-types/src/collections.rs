@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shelf::ShelfEntry;
+
+/// A named, manually ordered list of games from a user's shelf
+///
+/// A game can be in any number of collections. Collections hold game IDs, so
+/// a game must be on the shelf to be collected and leaves every collection
+/// when it is removed from the shelf.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Collection {
+    /// Unique among the user's collections
+    pub id: u64,
+    pub name: String,
+    /// In the order the user arranged them
+    #[serde(default)]
+    pub game_ids: Vec<u64>,
+}
+
+/// A collection with its shelf entries, returned by `GET /users/{username}/collections/{id}`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CollectionDetail {
+    pub id: u64,
+    pub name: String,
+    pub games: Vec<ShelfEntry>,
+}
+
+/// A user's collections in their chosen order, returned by `GET /users/{username}/collections`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CollectionsResponse {
+    pub collections: Vec<Collection>,
+}
+
+/// Body of `POST /users/{username}/collections` and `PATCH /users/{username}/collections/{id}`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CollectionNameRequest {
+    pub name: String,
+}
+
+/// Body of `POST /users/{username}/collections/{id}/games`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct CollectGameRequest {
+    pub game_id: u64,
+    /// Index to insert the game at; appended if left out or past the end
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+}
+
+/// Body of `PUT /users/{username}/collections/order` and
+/// `PUT /users/{username}/collections/{id}/order`
+///
+/// Lists every collection or game exactly once, in the new order.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct OrderRequest {
+    pub ids: Vec<u64>,
+}
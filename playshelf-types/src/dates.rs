@@ -0,0 +1,210 @@
+use chrono::{DateTime, Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How much of a release date is actually known
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DatePrecision {
+    Day,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl DatePrecision {
+    /// Maps IGDB's `release_dates.category`; `None` for "TBD" and unknown values
+    pub fn from_igdb_category(category: u64) -> Option<Self> {
+        match category {
+            0 => Some(DatePrecision::Day),
+            1 => Some(DatePrecision::Month),
+            2 => Some(DatePrecision::Year),
+            3..=6 => Some(DatePrecision::Quarter),
+            _ => None,
+        }
+    }
+}
+
+/// A release date that may only be known to the month, quarter or year
+///
+/// `date` is the first day IGDB reports for the period; only the parts
+/// covered by `precision` are meaningful.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ReleaseDate {
+    pub date: NaiveDate,
+    pub precision: DatePrecision,
+}
+
+impl ReleaseDate {
+    pub fn new(date: NaiveDate, precision: DatePrecision) -> Self {
+        Self { date, precision }
+    }
+
+    /// Converts an IGDB Unix timestamp in seconds, treating 0 as "no date"
+    pub fn from_timestamp(timestamp: i64, precision: DatePrecision) -> Option<Self> {
+        if timestamp == 0 {
+            return None;
+        }
+        let date = DateTime::from_timestamp(timestamp, 0)?.date_naive();
+        Some(Self::new(date, precision))
+    }
+
+    pub fn year(&self) -> i32 {
+        self.date.year()
+    }
+
+    /// Formats the date to its precision, e.g. "March 3, 2017", "Q1 2017" or "2017"
+    pub fn label(&self) -> String {
+        match self.precision {
+            DatePrecision::Day => self.date.format("%B %-d, %Y").to_string(),
+            DatePrecision::Month => self.date.format("%B %Y").to_string(),
+            DatePrecision::Quarter => format!("Q{} {}", self.date.month0() / 3 + 1, self.year()),
+            DatePrecision::Year => self.year().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ReleaseDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.label())
+    }
+}
+
+/// Region a release date applies to, from IGDB's `release_dates.region`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ReleaseRegion {
+    Europe,
+    NorthAmerica,
+    Australia,
+    NewZealand,
+    Japan,
+    China,
+    Asia,
+    Worldwide,
+    Korea,
+    Brazil,
+}
+
+impl ReleaseRegion {
+    /// Maps IGDB's `release_dates.region`; `None` for unknown values
+    pub fn from_igdb_region(region: u64) -> Option<Self> {
+        Some(match region {
+            1 => ReleaseRegion::Europe,
+            2 => ReleaseRegion::NorthAmerica,
+            3 => ReleaseRegion::Australia,
+            4 => ReleaseRegion::NewZealand,
+            5 => ReleaseRegion::Japan,
+            6 => ReleaseRegion::China,
+            7 => ReleaseRegion::Asia,
+            8 => ReleaseRegion::Worldwide,
+            9 => ReleaseRegion::Korea,
+            10 => ReleaseRegion::Brazil,
+            _ => return None,
+        })
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReleaseRegion::Europe => "Europe",
+            ReleaseRegion::NorthAmerica => "North America",
+            ReleaseRegion::Australia => "Australia",
+            ReleaseRegion::NewZealand => "New Zealand",
+            ReleaseRegion::Japan => "Japan",
+            ReleaseRegion::China => "China",
+            ReleaseRegion::Asia => "Asia",
+            ReleaseRegion::Worldwide => "Worldwide",
+            ReleaseRegion::Korea => "Korea",
+            ReleaseRegion::Brazil => "Brazil",
+        }
+    }
+}
+
+/// When a game came out on one platform in one region
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PlatformReleaseDate {
+    pub platform: String,
+    pub region: Option<ReleaseRegion>,
+    /// `None` while the date is still to be announced
+    pub date: Option<ReleaseDate>,
+}
+
+/// Deserializes `first_release_date` as written by any version of PlayShelf
+///
+/// Libraries stored before release dates were typed hold the Unix timestamp
+/// as a string (`"1488499200"`, with `"0"` or `""` for unknown); those are
+/// read as dates with day precision.
+pub mod legacy_release_date {
+    use super::{DatePrecision, ReleaseDate};
+    use serde::{de, Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<ReleaseDate>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Typed(ReleaseDate),
+            Timestamp(i64),
+            TimestampString(String),
+        }
+
+        let timestamp = match Option::<Stored>::deserialize(deserializer)? {
+            None => return Ok(None),
+            Some(Stored::Typed(date)) => return Ok(Some(date)),
+            Some(Stored::Timestamp(timestamp)) => timestamp,
+            Some(Stored::TimestampString(text)) if text.trim().is_empty() => return Ok(None),
+            Some(Stored::TimestampString(text)) => text
+                .trim()
+                .parse()
+                .map_err(|_| de::Error::custom(format!("invalid release date timestamp '{}'", text)))?,
+        };
+        Ok(ReleaseDate::from_timestamp(timestamp, DatePrecision::Day))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Stored {
+        #[serde(default, deserialize_with = "legacy_release_date::deserialize")]
+        first_release_date: Option<ReleaseDate>,
+    }
+
+    fn parse(json: &str) -> Option<ReleaseDate> {
+        serde_json::from_str::<Stored>(json).expect("Failed to parse").first_release_date
+    }
+
+    #[test]
+    fn test_legacy_timestamps() {
+        let breath_of_the_wild = Some(ReleaseDate::new(
+            NaiveDate::from_ymd_opt(2017, 3, 3).unwrap(),
+            DatePrecision::Day,
+        ));
+        assert_eq!(parse(r#"{"first_release_date": "1488499200"}"#), breath_of_the_wild);
+        assert_eq!(parse(r#"{"first_release_date": 1488499200}"#), breath_of_the_wild);
+        assert_eq!(parse(r#"{"first_release_date": "0"}"#), None);
+        assert_eq!(parse(r#"{"first_release_date": ""}"#), None);
+        assert_eq!(parse(r#"{"first_release_date": null}"#), None);
+        assert_eq!(parse("{}"), None);
+        assert!(serde_json::from_str::<Stored>(r#"{"first_release_date": "soon"}"#).is_err());
+    }
+
+    #[test]
+    fn test_typed_dates() {
+        let date = parse(r#"{"first_release_date": {"date": "2017-01-01", "precision": "quarter"}}"#)
+            .expect("Date should be set");
+        assert_eq!(date.precision, DatePrecision::Quarter);
+        assert_eq!(date.label(), "Q1 2017");
+    }
+
+    #[test]
+    fn test_labels_follow_precision() {
+        let date = NaiveDate::from_ymd_opt(2016, 9, 15).unwrap();
+        assert_eq!(ReleaseDate::new(date, DatePrecision::Day).label(), "September 15, 2016");
+        assert_eq!(ReleaseDate::new(date, DatePrecision::Month).label(), "September 2016");
+        assert_eq!(ReleaseDate::new(date, DatePrecision::Quarter).label(), "Q3 2016");
+        assert_eq!(ReleaseDate::new(date, DatePrecision::Year).label(), "2016");
+        assert_eq!(DatePrecision::from_igdb_category(7), None);
+    }
+}
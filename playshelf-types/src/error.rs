@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// Body of every error response
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ErrorResponse {
+    pub error: String,
+    /// Problems with individual request fields, set on 409 and 422 responses
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+}
+
+impl ErrorResponse {
+    pub fn new(error: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            fields: Vec::new(),
+        }
+    }
+}
+
+/// Why a request field was rejected
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationCode {
+    Required,
+    TooShort,
+    TooLong,
+    InvalidCharacters,
+    Reserved,
+    Taken,
+    /// A number or time outside the allowed range
+    OutOfRange,
+}
+
+/// A rejected request field with a message that can be shown next to it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: String,
+    pub code: ValidationCode,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, code: ValidationCode, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            code,
+            message: message.into(),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::users::UserResponse;
+
+/// Version written to new JSON exports; bumped when the format changes in a
+/// way older readers cannot follow
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Header row of CSV exports, one row per shelf entry after it
+///
+/// List columns (platforms, genres, collections) are joined with "; ".
+/// Sessions are summarised as a count and total minutes; the JSON export
+/// holds each one. Text starting with `=`, `+`, `-`, `@`, a tab or a carriage
+/// return gets a leading `'` so spreadsheets do not run it as a formula.
+pub const CSV_COLUMNS: [&str; 17] = [
+    "game_id",
+    "title",
+    "platforms",
+    "genres",
+    "release_date",
+    "status",
+    "added_on",
+    "started_on",
+    "finished_on",
+    "rating",
+    "review",
+    "reviewed_on",
+    "sessions",
+    "minutes_played",
+    "first_played_at",
+    "last_played_at",
+    "collections",
+];
+
+/// A user's whole library as downloaded from `GET /users/{username}/export`
+///
+/// Shaped like `sample_users.json`, a `{"users": [...]}` document, so either
+/// can be read as the other. Documents without a version, such as
+/// `sample_users.json` itself, are version 1.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LibraryExport {
+    #[serde(default = "first_version")]
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_at: Option<DateTime<Utc>>,
+    pub users: Vec<UserResponse>,
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+/// File format of a library export
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Query parameters of `GET /users/{username}/export`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_users_is_an_export() {
+        let sample = std::fs::read_to_string("../sample_users.json").expect("Failed to read sample_users.json");
+        let export: LibraryExport = serde_json::from_str(&sample).expect("Failed to parse sample_users.json");
+        assert_eq!(export.version, 1);
+        assert_eq!(export.exported_at, None);
+        assert_eq!(export.users[0].username, "hyunjaemoon");
+
+        let value = serde_json::to_value(&export).unwrap();
+        assert_eq!(value["version"], EXPORT_VERSION);
+        assert_eq!(serde_json::from_value::<LibraryExport>(value).unwrap(), export);
+    }
+}
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use crate::dates::{legacy_release_date, PlatformReleaseDate, ReleaseDate};
+
+/// Base URL for IGDB images; the size and image ID are appended
+pub const IGDB_IMAGE_URL: &str = "https://images.igdb.com/igdb/image/upload";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GameData {
+    pub id: u64,
+    pub name: String,
+    pub platforms: Vec<String>,
+    /// Earliest release on any platform, `None` if IGDB has no date yet
+    #[serde(default, deserialize_with = "legacy_release_date::deserialize")]
+    pub first_release_date: Option<ReleaseDate>,
+    pub genres: Vec<String>,
+    /// Release dates per platform and region
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub release_dates: Vec<PlatformReleaseDate>,
+    /// Cover art, if IGDB has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover: Option<GameImage>,
+    /// In-game screenshots
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub screenshots: Vec<GameImage>,
+}
+
+/// An IGDB image with ready-to-use URLs at several sizes
+///
+/// The URLs are rebuilt from `image_id` when an image is read, so stores can
+/// save the `image_id` alone.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(from = "StoredImage")]
+pub struct GameImage {
+    pub image_id: String,
+    /// 90x90, cropped
+    pub thumb: String,
+    /// 90x128
+    pub cover_small: String,
+    /// 264x374
+    pub cover_big: String,
+    /// 569x320
+    pub screenshot_med: String,
+    /// 889x500
+    pub screenshot_big: String,
+    /// 1280x720
+    pub hd: String,
+    /// 1920x1080
+    pub full_hd: String,
+}
+
+impl GameImage {
+    /// Builds the image URLs for an IGDB `image_id`
+    pub fn from_image_id(image_id: &str) -> Self {
+        let url = |size: &str| format!("{}/t_{}/{}.jpg", IGDB_IMAGE_URL, size, image_id);
+        Self {
+            image_id: image_id.to_string(),
+            thumb: url("thumb"),
+            cover_small: url("cover_small"),
+            cover_big: url("cover_big"),
+            screenshot_med: url("screenshot_med"),
+            screenshot_big: url("screenshot_big"),
+            hd: url("720p"),
+            full_hd: url("1080p"),
+        }
+    }
+}
+
+/// The part of a `GameImage` read back
+#[derive(Deserialize)]
+struct StoredImage {
+    image_id: String,
+}
+
+impl From<StoredImage> for GameImage {
+    fn from(stored: StoredImage) -> Self {
+        GameImage::from_image_id(&stored.image_id)
+    }
+}
+
+/// Extended record for a single game, returned by `GET /games/{id}`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GameDetail {
+    #[serde(flatten)]
+    pub game: GameData,
+    pub summary: Option<String>,
+    pub storyline: Option<String>,
+    #[serde(default)]
+    pub involved_companies: Vec<CompanyCredit>,
+    #[serde(default)]
+    pub franchises: Vec<String>,
+    #[serde(default)]
+    pub game_modes: Vec<String>,
+    #[serde(default)]
+    pub themes: Vec<String>,
+    /// Average of critic and user ratings, from 0 to 100
+    pub rating: Option<f64>,
+    pub rating_count: Option<u64>,
+    #[serde(default)]
+    pub websites: Vec<GameWebsite>,
+    #[serde(default)]
+    pub similar_games: Vec<SimilarGame>,
+}
+
+/// A company involved in making a game
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CompanyCredit {
+    pub name: String,
+    pub developer: bool,
+    pub publisher: bool,
+}
+
+/// A website related to a game
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GameWebsite {
+    pub url: String,
+    /// Kind of website, e.g. "official", "steam" or "wikipedia"
+    pub category: String,
+}
+
+/// A game IGDB considers similar, with just enough data to link to it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SimilarGame {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub cover: Option<GameImage>,
+}
+
+/// Pagination parameters of `GET /games`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PageQuery {
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// Parameters of `GET /games/search`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub query: String,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// One page of games, returned by `GET /games` and `GET /games/search`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GamesResponse {
+    pub count: usize,
+    pub games: Vec<GameData>,
+    pub limit: u32,
+    pub offset: u32,
+    pub has_more: bool,
+    /// Offset of the next page, set when `has_more` is true
+    pub next_offset: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_image_urls() {
+        let image = GameImage::from_image_id("co1r76");
+        assert_eq!(image.image_id, "co1r76");
+        assert_eq!(image.cover_big, "https://images.igdb.com/igdb/image/upload/t_cover_big/co1r76.jpg");
+        assert_eq!(image.hd, "https://images.igdb.com/igdb/image/upload/t_720p/co1r76.jpg");
+    }
+
+    #[test]
+    fn test_game_image_urls_are_rebuilt() {
+        let image = GameImage::from_image_id("co1r76");
+        let json = serde_json::to_value(&image).expect("Failed to serialize image");
+        assert_eq!(json["cover_big"], image.cover_big);
+        assert_eq!(serde_json::from_value::<GameImage>(json).unwrap(), image);
+
+        // Stored images hold only their ID, and stale URLs are ignored
+        let stored = serde_json::json!({ "image_id": "co1r76", "cover_big": "https://example.com/stale.jpg" });
+        let parsed: GameImage = serde_json::from_value(stored).expect("Failed to parse image");
+        assert_eq!(parsed, image);
+    }
+
+    #[test]
+    fn test_game_detail_flattens_game_data() {
+        let json = serde_json::json!({
+            "id": 1942,
+            "name": "The Witcher 3: Wild Hunt",
+            "platforms": ["PC (Microsoft Windows)"],
+            "first_release_date": "1431993600",
+            "genres": ["Adventure"],
+            "summary": null,
+            "storyline": null,
+            "rating": null,
+            "rating_count": null,
+        });
+        let detail: GameDetail = serde_json::from_value(json).expect("Failed to parse game detail");
+        assert_eq!(detail.game.name, "The Witcher 3: Wild Hunt");
+        assert!(detail.game.cover.is_none());
+        assert!(detail.similar_games.is_empty());
+    }
+}
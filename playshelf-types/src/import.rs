@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+use crate::games::GameData;
+use crate::reviews::Rating;
+use crate::shelf::ShelfStatus;
+use crate::users::UserResponse;
+
+/// Body of `POST /users/{username}/import/preview`
+///
+/// `csv` needs a header row with a `title` column; `platform`, `status` and
+/// `rating` columns are optional. The `platforms` column of a CSV export is
+/// read as `platform`. A row with an IGDB ID in an `id`, `igdb_id` or
+/// `game_id` column is matched to that game exactly, without a search.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ImportPreviewRequest {
+    pub csv: String,
+}
+
+/// How sure the import is about the best candidate for a row
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchConfidence {
+    /// The best candidate is a close match and well ahead of the others
+    Confident,
+    /// Several candidates are similarly close, or the best is not close
+    Ambiguous,
+    /// IGDB returned nothing resembling the title
+    Unmatched,
+}
+
+/// An IGDB game that may be the one a row means
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImportCandidate {
+    pub game: GameData,
+    /// From 0 (unrelated) to 1 (same title, on the row's platform if it has one)
+    pub score: f64,
+}
+
+/// One CSV row and the games it may match
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImportRow {
+    /// Line of the row in the CSV, counting the header as line 1
+    pub line: usize,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    /// Backlog if the row leaves it out
+    pub status: ShelfStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<Rating>,
+    pub confidence: MatchConfidence,
+    /// Best first, at most `MAX_IMPORT_CANDIDATES`
+    pub candidates: Vec<ImportCandidate>,
+    /// Values of the row that could not be read and were left out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// ID of the best candidate if it is already on the user's shelf
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_shelf: Option<u64>,
+}
+
+/// Returned by `POST /users/{username}/import/preview`; nothing is saved yet
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImportPreview {
+    pub rows: Vec<ImportRow>,
+}
+
+/// A confirmed match to add to the shelf; the server looks the game up by ID
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImportItem {
+    pub game_id: u64,
+    #[serde(default)]
+    pub status: ShelfStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<Rating>,
+}
+
+/// Body of `POST /users/{username}/import`, with at most `MAX_IMPORT_ROWS` items
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImportRequest {
+    pub items: Vec<ImportItem>,
+}
+
+/// Returned by `POST /users/{username}/import`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImportResponse {
+    /// IDs of the games added to the shelf
+    pub imported: Vec<u64>,
+    /// IDs of the games left out because they were already on the shelf
+    pub skipped: Vec<u64>,
+    /// IDs IGDB has no game for, which were left out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub not_found: Vec<u64>,
+    pub user: UserResponse,
+}
+
+/// Most candidates listed per row
+pub const MAX_IMPORT_CANDIDATES: usize = 5;
+/// Most rows read from one CSV or imported at once
+///
+/// Every distinct title in a preview is searched on IGDB, a few at a time and
+/// within IGDB's rate limit, so a full preview takes several seconds.
+pub const MAX_IMPORT_ROWS: usize = 100;
@@ -0,0 +1,46 @@
+//! Request and response types of the PlayShelf HTTP API
+//!
+//! Shared by `playshelf-server` and `playshelf-client` so that a change to the
+//! wire format on one side fails to compile on the other.
+
+mod auth;
+mod cache;
+mod collections;
+mod dates;
+mod error;
+mod export;
+mod games;
+mod import;
+mod reviews;
+mod sessions;
+mod shelf;
+mod stats;
+mod steam;
+mod users;
+pub mod validation;
+
+pub use auth::{LoginRequest, RegisterRequest, SessionResponse};
+pub use cache::{CacheStats, IGDBCacheStats};
+pub use collections::{
+    CollectGameRequest, Collection, CollectionDetail, CollectionNameRequest, CollectionsResponse, OrderRequest,
+};
+pub use dates::{legacy_release_date, DatePrecision, PlatformReleaseDate, ReleaseDate, ReleaseRegion};
+pub use error::{ErrorResponse, FieldError, ValidationCode};
+pub use export::{ExportFormat, ExportQuery, LibraryExport, CSV_COLUMNS, EXPORT_VERSION};
+pub use games::{
+    CompanyCredit, GameData, GameDetail, GameImage, GameWebsite, GamesResponse, PageQuery,
+    SearchQuery, SimilarGame, IGDB_IMAGE_URL,
+};
+pub use import::{
+    ImportCandidate, ImportItem, ImportPreview, ImportPreviewRequest, ImportRequest, ImportResponse, ImportRow,
+    MatchConfidence, MAX_IMPORT_CANDIDATES, MAX_IMPORT_ROWS,
+};
+pub use reviews::{GameReview, GameReviewsResponse, Rating, ReviewRequest};
+pub use sessions::{
+    format_minutes, GamePlaytime, GameSessionsResponse, ImportedPlaytime, LogSessionRequest, MonthPlaytime,
+    PlatformPlaytime, PlaySession, PlaytimeResponse, PlaytimeSource, PlaytimeTotals,
+};
+pub use shelf::{AddGameRequest, ShelfEntry, ShelfQuery, ShelfStatus, StatusChangeRequest};
+pub use stats::{LabelCount, LibraryStats, ReleaseYearCount, StatusCount, YearTrend};
+pub use steam::{SteamImportResponse, SteamOwnedGame, SteamOwnedGames, SteamOwnedGamesList, MAX_STEAM_GAMES};
+pub use users::{user_id, UpdateProfileRequest, UserGamesResponse, UserResponse, UsersResponse};
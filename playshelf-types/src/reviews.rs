@@ -0,0 +1,131 @@
+use chrono::NaiveDate;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+use crate::shelf::ShelfStatus;
+
+/// A rating from half a star to five stars in half-star steps
+///
+/// Stored as a count of half stars and written to JSON as the number of stars,
+/// e.g. `3.5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rating(u8);
+
+impl Rating {
+    pub const MIN_HALF_STARS: u8 = 1;
+    pub const MAX_HALF_STARS: u8 = 10;
+
+    /// `None` unless `half_stars` is between 1 and 10
+    pub fn from_half_stars(half_stars: u8) -> Option<Self> {
+        (Self::MIN_HALF_STARS..=Self::MAX_HALF_STARS)
+            .contains(&half_stars)
+            .then_some(Self(half_stars))
+    }
+
+    /// `None` unless `stars` is a multiple of 0.5 between 0.5 and 5
+    pub fn from_stars(stars: f64) -> Option<Self> {
+        let half_stars = stars * 2.0;
+        if half_stars.fract() != 0.0 || !(1.0..=10.0).contains(&half_stars) {
+            return None;
+        }
+        Self::from_half_stars(half_stars as u8)
+    }
+
+    pub fn half_stars(&self) -> u8 {
+        self.0
+    }
+
+    pub fn stars(&self) -> f64 {
+        f64::from(self.0) / 2.0
+    }
+
+    /// Draws the rating with full, half and empty stars, e.g. "★★★½☆"
+    pub fn star_label(&self) -> String {
+        let full = usize::from(self.0 / 2);
+        let half = self.0 % 2 == 1;
+        let empty = 5 - full - usize::from(half);
+        format!("{}{}{}", "★".repeat(full), if half { "½" } else { "" }, "☆".repeat(empty))
+    }
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.stars())
+    }
+}
+
+impl Serialize for Rating {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.stars())
+    }
+}
+
+impl<'de> Deserialize<'de> for Rating {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let stars = f64::deserialize(deserializer)?;
+        Rating::from_stars(stars).ok_or_else(|| {
+            de::Error::custom(format!("rating {} is not between 0.5 and 5 in half-star steps", stars))
+        })
+    }
+}
+
+/// Body of `PUT /users/{username}/games/{game_id}/review`
+///
+/// Replaces both the rating and the review; leaving both out removes them.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ReviewRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<Rating>,
+    /// Review text in Markdown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review: Option<String>,
+}
+
+/// One user's opinion of a game
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GameReview {
+    pub username: String,
+    pub name: String,
+    pub status: ShelfStatus,
+    pub rating: Option<Rating>,
+    /// Review text in Markdown
+    pub review: Option<String>,
+    pub reviewed_on: Option<NaiveDate>,
+}
+
+/// A page of every user's ratings and reviews of a game, returned by
+/// `GET /games/{id}/reviews`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GameReviewsResponse {
+    pub game_id: u64,
+    /// Mean of all ratings in stars, `None` if nobody rated the game
+    pub average_rating: Option<f64>,
+    pub rating_count: usize,
+    /// Number of users who rated or reviewed the game, across all pages
+    pub review_count: usize,
+    /// Newest first
+    pub reviews: Vec<GameReview>,
+    /// Offset of the next page, `None` on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ratings_use_half_stars() {
+        let rating: Rating = serde_json::from_str("3.5").expect("Failed to parse rating");
+        assert_eq!(rating.half_stars(), 7);
+        assert_eq!(serde_json::to_string(&rating).unwrap(), "3.5");
+        assert_eq!(rating.star_label(), "★★★½☆");
+        assert_eq!(Rating::from_half_stars(10).unwrap().star_label(), "★★★★★");
+
+        assert!(serde_json::from_str::<Rating>("5").is_ok());
+        assert!(serde_json::from_str::<Rating>("0").is_err());
+        assert!(serde_json::from_str::<Rating>("3.25").is_err());
+        assert!(serde_json::from_str::<Rating>("5.5").is_err());
+        assert_eq!(Rating::from_half_stars(11), None);
+    }
+}
@@ -0,0 +1,241 @@
+use chrono::{DateTime, Datelike, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// One sitting with a game on a user's shelf
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PlaySession {
+    /// Unique among the sessions of the same shelf entry
+    pub id: u64,
+    pub started_at: DateTime<Utc>,
+    pub duration_minutes: u32,
+    /// Platform played on, one of the game's platforms or free text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl PlaySession {
+    pub fn ended_at(&self) -> DateTime<Utc> {
+        self.started_at + Duration::minutes(i64::from(self.duration_minutes))
+    }
+}
+
+/// Body of `POST /users/{username}/games/{game_id}/sessions`
+///
+/// Exactly one of `ended_at` and `duration_minutes` must be set.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LogSessionRequest {
+    pub started_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_minutes: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl LogSessionRequest {
+    /// Length of the session in whole minutes, from whichever of `ended_at`
+    /// and `duration_minutes` is set; `None` if neither or both are, or the
+    /// session ends before it starts
+    pub fn duration_minutes(&self) -> Option<u32> {
+        match (self.ended_at, self.duration_minutes) {
+            (Some(ended_at), None) => u32::try_from((ended_at - self.started_at).num_minutes()).ok(),
+            (None, Some(minutes)) => Some(minutes),
+            _ => None,
+        }
+    }
+}
+
+/// Where playtime that was not logged as sessions came from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaytimeSource {
+    Steam,
+}
+
+impl PlaytimeSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlaytimeSource::Steam => "Steam",
+        }
+    }
+}
+
+/// A game's total playtime as recorded by another service
+///
+/// Services such as Steam only keep a total, so it has no dates or platform
+/// and counts as unattributed time.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ImportedPlaytime {
+    pub minutes: u64,
+    pub source: PlaytimeSource,
+}
+
+/// Minutes played on one platform
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PlatformPlaytime {
+    /// `None` for sessions logged without a platform
+    pub platform: Option<String>,
+    pub minutes: u64,
+}
+
+/// Minutes played in one calendar month (UTC)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct MonthPlaytime {
+    /// Formatted as `YYYY-MM`
+    pub month: String,
+    pub minutes: u64,
+}
+
+/// Time played across a set of sessions
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct PlaytimeTotals {
+    pub minutes: u64,
+    pub sessions: usize,
+    /// Part of `minutes` imported as totals, which counts towards no platform or month
+    #[serde(default)]
+    pub unattributed_minutes: u64,
+    /// Most played first
+    pub by_platform: Vec<PlatformPlaytime>,
+    /// Oldest first; a session counts towards the month it started in
+    pub by_month: Vec<MonthPlaytime>,
+}
+
+impl PlaytimeTotals {
+    pub fn from_sessions<'a>(sessions: impl IntoIterator<Item = &'a PlaySession>) -> Self {
+        let mut totals = PlaytimeTotals::default();
+        let mut by_platform: BTreeMap<Option<&str>, u64> = BTreeMap::new();
+        let mut by_month: BTreeMap<(i32, u32), u64> = BTreeMap::new();
+        for session in sessions {
+            let minutes = u64::from(session.duration_minutes);
+            totals.minutes += minutes;
+            totals.sessions += 1;
+            *by_platform.entry(session.platform.as_deref()).or_default() += minutes;
+            let started = session.started_at.date_naive();
+            *by_month.entry((started.year(), started.month())).or_default() += minutes;
+        }
+
+        totals.by_platform = by_platform
+            .into_iter()
+            .map(|(platform, minutes)| PlatformPlaytime {
+                platform: platform.map(str::to_string),
+                minutes,
+            })
+            .collect();
+        // Stable, so platforms with the same time stay in name order
+        totals.by_platform.sort_by_key(|platform| std::cmp::Reverse(platform.minutes));
+        totals.by_month = by_month
+            .into_iter()
+            .map(|((year, month), minutes)| MonthPlaytime {
+                month: format!("{:04}-{:02}", year, month),
+                minutes,
+            })
+            .collect();
+        totals
+    }
+
+    /// Adds imported playtime, which has no sessions, platform or month
+    pub fn add_unattributed(&mut self, minutes: u64) {
+        self.minutes += minutes;
+        self.unattributed_minutes += minutes;
+    }
+}
+
+/// A game's sessions and their totals, returned by `GET /users/{username}/games/{game_id}/sessions`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct GameSessionsResponse {
+    pub game_id: u64,
+    /// Newest first
+    pub sessions: Vec<PlaySession>,
+    pub totals: PlaytimeTotals,
+}
+
+/// Minutes played on one game
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct GamePlaytime {
+    pub game_id: u64,
+    pub name: String,
+    pub minutes: u64,
+}
+
+/// Time played across a user's whole shelf, returned by `GET /users/{username}/playtime`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PlaytimeResponse {
+    pub totals: PlaytimeTotals,
+    /// Most played first, leaving out games never played
+    pub by_game: Vec<GamePlaytime>,
+}
+
+/// Formats minutes as hours and minutes, e.g. "2h 05m" or "45m"
+pub fn format_minutes(minutes: u64) -> String {
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: u64, started_at: &str, duration_minutes: u32, platform: Option<&str>) -> PlaySession {
+        PlaySession {
+            id,
+            started_at: started_at.parse().unwrap(),
+            duration_minutes,
+            platform: platform.map(str::to_string),
+            note: None,
+        }
+    }
+
+    #[test]
+    fn test_session_duration_from_request() {
+        let mut request = LogSessionRequest {
+            started_at: "2024-05-01T20:00:00Z".parse().unwrap(),
+            ended_at: Some("2024-05-01T21:30:59Z".parse().unwrap()),
+            duration_minutes: None,
+            platform: None,
+            note: None,
+        };
+        assert_eq!(request.duration_minutes(), Some(90));
+        request.duration_minutes = Some(30);
+        assert_eq!(request.duration_minutes(), None, "Both an end and a duration is ambiguous");
+        request.ended_at = None;
+        assert_eq!(request.duration_minutes(), Some(30));
+
+        request.ended_at = Some("2024-05-01T19:00:00Z".parse().unwrap());
+        request.duration_minutes = None;
+        assert_eq!(request.duration_minutes(), None);
+    }
+
+    #[test]
+    fn test_playtime_totals() {
+        let sessions = [
+            session(1, "2024-04-30T23:00:00Z", 120, Some("PC")),
+            session(2, "2024-05-02T18:00:00Z", 45, Some("Nintendo Switch")),
+            session(3, "2024-05-03T18:00:00Z", 45, Some("PC")),
+            session(4, "2024-06-01T10:00:00Z", 10, None),
+        ];
+        let totals = PlaytimeTotals::from_sessions(&sessions);
+        assert_eq!((totals.minutes, totals.sessions), (220, 4));
+        let platforms: Vec<(Option<&str>, u64)> = totals
+            .by_platform
+            .iter()
+            .map(|platform| (platform.platform.as_deref(), platform.minutes))
+            .collect();
+        assert_eq!(platforms, vec![(Some("PC"), 165), (Some("Nintendo Switch"), 45), (None, 10)]);
+        let months: Vec<(&str, u64)> =
+            totals.by_month.iter().map(|month| (month.month.as_str(), month.minutes)).collect();
+        assert_eq!(months, vec![("2024-04", 120), ("2024-05", 90), ("2024-06", 10)]);
+
+        assert_eq!(sessions[0].ended_at().to_rfc3339(), "2024-05-01T01:00:00+00:00");
+        assert_eq!(format_minutes(45), "45m");
+        assert_eq!(format_minutes(125), "2h 05m");
+    }
+}
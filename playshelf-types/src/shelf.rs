@@ -0,0 +1,360 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::games::GameData;
+use crate::reviews::Rating;
+use crate::sessions::{ImportedPlaytime, PlaySession, PlaytimeTotals};
+
+/// Where a game stands for the user who shelved it
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ShelfStatus {
+    /// Not owned yet
+    Wishlist,
+    /// Owned but not started
+    #[default]
+    Backlog,
+    Playing,
+    Completed,
+    Abandoned,
+}
+
+impl ShelfStatus {
+    pub const ALL: [ShelfStatus; 5] = [
+        ShelfStatus::Wishlist,
+        ShelfStatus::Backlog,
+        ShelfStatus::Playing,
+        ShelfStatus::Completed,
+        ShelfStatus::Abandoned,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ShelfStatus::Wishlist => "Wishlist",
+            ShelfStatus::Backlog => "Backlog",
+            ShelfStatus::Playing => "Playing",
+            ShelfStatus::Completed => "Completed",
+            ShelfStatus::Abandoned => "Abandoned",
+        }
+    }
+
+    /// Whether the game has been put down, finished or not
+    pub fn is_finished(&self) -> bool {
+        matches!(self, ShelfStatus::Completed | ShelfStatus::Abandoned)
+    }
+}
+
+/// A game on a user's shelf and the user's progress with it
+///
+/// Libraries stored before entries existed hold bare `GameData` objects;
+/// those are read as backlog entries with no dates.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ShelfEntry {
+    pub game: GameData,
+    pub status: ShelfStatus,
+    /// `None` for games shelved before the date was recorded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub added_on: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_on: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_on: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating: Option<Rating>,
+    /// Review text in Markdown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review: Option<String>,
+    /// When the rating or review last changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reviewed_on: Option<NaiveDate>,
+    /// Logged play sessions, oldest first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sessions: Vec<PlaySession>,
+    /// Highest session ID handed out so far, so IDs of deleted sessions are not reused
+    #[serde(skip_serializing_if = "is_zero")]
+    pub last_session_id: u64,
+    /// Playtime imported as a total from another service, on top of `sessions`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub imported_playtime: Option<ImportedPlaytime>,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+impl ShelfEntry {
+    /// A backlog entry for `game` added on `today`
+    pub fn new(game: GameData, today: NaiveDate) -> Self {
+        Self {
+            game,
+            status: ShelfStatus::Backlog,
+            added_on: Some(today),
+            started_on: None,
+            finished_on: None,
+            rating: None,
+            review: None,
+            reviewed_on: None,
+            sessions: Vec::new(),
+            last_session_id: 0,
+            imported_playtime: None,
+        }
+    }
+
+    /// Minutes of the logged sessions and the imported playtime together
+    pub fn minutes_played(&self) -> u64 {
+        self.playtime_totals().minutes
+    }
+
+    /// Totals of the logged sessions, with the imported playtime as unattributed time
+    pub fn playtime_totals(&self) -> PlaytimeTotals {
+        let mut totals = PlaytimeTotals::from_sessions(&self.sessions);
+        if let Some(imported) = self.imported_playtime {
+            totals.add_unattributed(imported.minutes);
+        }
+        totals
+    }
+
+    /// Replaces the rating and review, recording `date` as the review date
+    /// unless both were removed
+    pub fn set_review(&mut self, rating: Option<Rating>, review: Option<String>, date: NaiveDate) {
+        // Blank reviews are treated as no review
+        self.review = review.filter(|review| !review.trim().is_empty());
+        self.rating = rating;
+        self.reviewed_on = (self.rating.is_some() || self.review.is_some()).then_some(date);
+    }
+
+    /// Whether the user rated or reviewed the game
+    pub fn is_reviewed(&self) -> bool {
+        self.rating.is_some() || self.review.is_some()
+    }
+
+    /// Moves the entry to `status` on `date`, keeping the dates consistent
+    ///
+    /// Starting a game records when it was started; finishing or abandoning it
+    /// records when it ended, and when it started if that was never recorded.
+    /// Going back to the wishlist or backlog clears both dates. Setting the
+    /// current status again changes nothing.
+    pub fn set_status(&mut self, status: ShelfStatus, date: NaiveDate) {
+        if status == self.status {
+            return;
+        }
+        match status {
+            ShelfStatus::Wishlist | ShelfStatus::Backlog => {
+                self.started_on = None;
+                self.finished_on = None;
+            }
+            ShelfStatus::Playing => {
+                // Picking a finished game up again is a new playthrough
+                if self.status.is_finished() || self.started_on.is_none() {
+                    self.started_on = Some(date);
+                }
+                self.finished_on = None;
+            }
+            ShelfStatus::Completed | ShelfStatus::Abandoned => {
+                self.started_on.get_or_insert(date);
+                self.finished_on = Some(date);
+            }
+        }
+        self.status = status;
+    }
+}
+
+impl<'de> Deserialize<'de> for ShelfEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Entry {
+            game: GameData,
+            #[serde(default)]
+            status: ShelfStatus,
+            #[serde(default)]
+            added_on: Option<NaiveDate>,
+            #[serde(default)]
+            started_on: Option<NaiveDate>,
+            #[serde(default)]
+            finished_on: Option<NaiveDate>,
+            #[serde(default)]
+            rating: Option<Rating>,
+            #[serde(default)]
+            review: Option<String>,
+            #[serde(default)]
+            reviewed_on: Option<NaiveDate>,
+            #[serde(default)]
+            sessions: Vec<PlaySession>,
+            #[serde(default)]
+            last_session_id: u64,
+            #[serde(default)]
+            imported_playtime: Option<ImportedPlaytime>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Entry(Entry),
+            Bare(GameData),
+        }
+
+        Ok(match Stored::deserialize(deserializer)? {
+            Stored::Entry(entry) => ShelfEntry {
+                game: entry.game,
+                status: entry.status,
+                added_on: entry.added_on,
+                started_on: entry.started_on,
+                finished_on: entry.finished_on,
+                rating: entry.rating,
+                review: entry.review,
+                reviewed_on: entry.reviewed_on,
+                sessions: entry.sessions,
+                last_session_id: entry.last_session_id,
+                imported_playtime: entry.imported_playtime,
+            },
+            Stored::Bare(game) => ShelfEntry {
+                game,
+                status: ShelfStatus::default(),
+                added_on: None,
+                started_on: None,
+                finished_on: None,
+                rating: None,
+                review: None,
+                reviewed_on: None,
+                sessions: Vec::new(),
+                last_session_id: 0,
+                imported_playtime: None,
+            },
+        })
+    }
+}
+
+/// Body of `POST /users/{username}/games`
+///
+/// Either a game with the status to shelve it under, or a bare game, which goes
+/// to the backlog. The server fills in the rest of the entry, so dates, reviews
+/// and sessions sent along are ignored.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct AddGameRequest {
+    pub game: GameData,
+    pub status: ShelfStatus,
+}
+
+impl<'de> Deserialize<'de> for AddGameRequest {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Request {
+            game: GameData,
+            #[serde(default)]
+            status: ShelfStatus,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Body {
+            Request(Request),
+            Bare(GameData),
+        }
+
+        Ok(match Body::deserialize(deserializer)? {
+            Body::Request(request) => AddGameRequest {
+                game: request.game,
+                status: request.status,
+            },
+            Body::Bare(game) => AddGameRequest {
+                game,
+                status: ShelfStatus::default(),
+            },
+        })
+    }
+}
+
+/// Body of `PUT /users/{username}/games/{game_id}/status`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct StatusChangeRequest {
+    pub status: ShelfStatus,
+    /// Day the change happened; the server's current date if left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<NaiveDate>,
+}
+
+/// Query parameters of `GET /users/{username}/games`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShelfQuery {
+    /// Only return entries with this status
+    pub status: Option<ShelfStatus>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+    }
+
+    fn celeste() -> GameData {
+        serde_json::from_str(r#"{"id": 42, "name": "Celeste", "platforms": [], "genres": []}"#).unwrap()
+    }
+
+    #[test]
+    fn test_bare_games_are_backlog_entries() {
+        let json = r#"{"id": 42, "name": "Celeste", "first_release_date": "1516665600", "platforms": [], "genres": []}"#;
+        let entry: ShelfEntry = serde_json::from_str(json).expect("Failed to parse bare game");
+        assert_eq!(entry.game.name, "Celeste");
+        assert_eq!(entry.status, ShelfStatus::Backlog);
+        assert_eq!(entry.added_on, None);
+
+        let value = serde_json::to_value(&entry).unwrap();
+        assert_eq!(value["status"], "backlog");
+        assert!(value.get("added_on").is_none());
+        assert_eq!(serde_json::from_value::<ShelfEntry>(value).unwrap(), entry);
+    }
+
+    #[test]
+    fn test_add_game_request_ignores_entry_fields() {
+        let request: AddGameRequest = serde_json::from_value(serde_json::json!({
+            "game": celeste(),
+            "status": "completed",
+            "finished_on": "2000-01-01",
+            "rating": 10,
+        }))
+        .expect("Failed to parse add game request");
+        assert_eq!(request.game, celeste());
+        assert_eq!(request.status, ShelfStatus::Completed);
+
+        let bare: AddGameRequest = serde_json::from_value(serde_json::to_value(celeste()).unwrap()).unwrap();
+        assert_eq!(bare.status, ShelfStatus::Backlog);
+    }
+
+    #[test]
+    fn test_status_transitions_keep_dates() {
+        let mut entry = ShelfEntry::new(celeste(), day(1));
+        entry.set_status(ShelfStatus::Playing, day(2));
+        assert_eq!((entry.started_on, entry.finished_on), (Some(day(2)), None));
+        entry.set_status(ShelfStatus::Playing, day(3));
+        assert_eq!(entry.started_on, Some(day(2)), "Setting the same status changes nothing");
+
+        entry.set_status(ShelfStatus::Completed, day(4));
+        assert_eq!((entry.started_on, entry.finished_on), (Some(day(2)), Some(day(4))));
+        entry.set_status(ShelfStatus::Playing, day(5));
+        assert_eq!((entry.started_on, entry.finished_on), (Some(day(5)), None));
+
+        entry.set_status(ShelfStatus::Backlog, day(6));
+        assert_eq!((entry.started_on, entry.finished_on), (None, None));
+
+        // Abandoning a game that was never started counts as starting it that day
+        entry.set_status(ShelfStatus::Abandoned, day(7));
+        assert_eq!((entry.started_on, entry.finished_on), (Some(day(7)), Some(day(7))));
+        assert_eq!(entry.added_on, Some(day(1)));
+    }
+
+    #[test]
+    fn test_reviews() {
+        let mut entry = ShelfEntry::new(celeste(), day(1));
+        entry.set_review(Rating::from_half_stars(9), Some("**Tight** controls".to_string()), day(2));
+        assert_eq!(entry.reviewed_on, Some(day(2)));
+        let value = serde_json::to_value(&entry).unwrap();
+        assert_eq!(value["rating"], 4.5);
+        assert_eq!(serde_json::from_value::<ShelfEntry>(value).unwrap(), entry);
+
+        entry.set_review(None, Some("  ".to_string()), day(3));
+        assert!(!entry.is_reviewed());
+        assert_eq!(entry.reviewed_on, None);
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shelf::ShelfStatus;
+
+/// Number of shelved games on one platform or in one genre
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LabelCount {
+    pub label: String,
+    pub count: usize,
+}
+
+/// Number of shelved games with one status
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct StatusCount {
+    pub status: ShelfStatus,
+    pub count: usize,
+}
+
+/// Number of shelved games first released in one year
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ReleaseYearCount {
+    pub year: i32,
+    pub count: usize,
+}
+
+/// What happened on a user's shelf in one calendar year
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct YearTrend {
+    pub year: i32,
+    /// Games added to the shelf
+    pub added: usize,
+    /// Games whose current playthrough started this year
+    pub started: usize,
+    pub completed: usize,
+    pub abandoned: usize,
+    /// Minutes of play sessions started this year
+    pub minutes_played: u64,
+}
+
+/// Breakdowns of a user's shelf, returned by `GET /users/{username}/stats`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LibraryStats {
+    pub username: String,
+    pub total_games: usize,
+    /// Every status in `ShelfStatus::ALL` order, including empty ones
+    pub by_status: Vec<StatusCount>,
+    /// Most games first; a game counts towards each platform it was released on
+    pub by_platform: Vec<LabelCount>,
+    /// Most games first; a game counts towards each of its genres
+    pub by_genre: Vec<LabelCount>,
+    /// Oldest first
+    pub by_release_year: Vec<ReleaseYearCount>,
+    /// Games without a known release date
+    pub unknown_release_year: usize,
+    /// Share of owned games, i.e. all but the wishlist, that were completed,
+    /// from 0 to 1; `None` if nothing is owned
+    pub completion_rate: Option<f64>,
+    /// Owned games not started yet
+    pub backlog_size: usize,
+    /// Oldest first, only years in which something happened
+    pub yearly_trends: Vec<YearTrend>,
+    /// Minutes of imported playtime, which has no dates and so counts towards
+    /// no year of `yearly_trends`
+    #[serde(default)]
+    pub unattributed_minutes_played: u64,
+}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+use crate::users::UserResponse;
+
+/// Body of `POST /users/{username}/import/steam`: a saved response of the
+/// Steam Web API's `IPlayerService/GetOwnedGames`
+///
+/// Fetched with `include_appinfo=1`, games carry their names, which are
+/// used to report the ones that could not be matched. At most
+/// `MAX_STEAM_GAMES` games are accepted.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SteamOwnedGames {
+    pub response: SteamOwnedGamesList,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct SteamOwnedGamesList {
+    /// Left out by Steam when the profile's game details are private
+    #[serde(default)]
+    pub games: Vec<SteamOwnedGame>,
+}
+
+/// One game of a Steam library; fields not listed here are ignored
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SteamOwnedGame {
+    pub appid: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Minutes played on every platform
+    #[serde(default)]
+    pub playtime_forever: u32,
+    /// Unix time the game was last played, 0 if never or not recorded
+    #[serde(default)]
+    pub rtime_last_played: i64,
+}
+
+impl SteamOwnedGame {
+    /// The game's name, or its app ID if the file has no names
+    pub fn label(&self) -> String {
+        self.name.clone().unwrap_or_else(|| format!("Steam app {}", self.appid))
+    }
+}
+
+/// Returned by `POST /users/{username}/import/steam`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SteamImportResponse {
+    /// IDs of the games added to the shelf
+    pub imported: Vec<u64>,
+    /// IDs of games already on the shelf that had no playtime and were given
+    /// their Steam playtime
+    pub playtime_added: Vec<u64>,
+    /// IDs of games already on the shelf and left as they were
+    pub skipped: Vec<u64>,
+    /// Steam games IGDB has no record of
+    pub unmatched: Vec<SteamOwnedGame>,
+    pub user: UserResponse,
+}
+
+/// Most games read from one Steam upload
+///
+/// A user's whole shelf is stored as one DynamoDB item, which can be at most
+/// 400 KB, so larger libraries have to be trimmed to the games worth tracking.
+pub const MAX_STEAM_GAMES: usize = 250;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_owned_games() {
+        let body = r#"{"response": {"game_count": 2, "games": [
+            {"appid": 620, "name": "Portal 2", "playtime_forever": 754, "img_icon_url": "2e47",
+             "playtime_windows_forever": 754, "rtime_last_played": 1700000000},
+            {"appid": 292030, "playtime_forever": 0}
+        ]}}"#;
+        let owned: SteamOwnedGames = serde_json::from_str(body).unwrap();
+        let games = &owned.response.games;
+        assert_eq!(games[0].label(), "Portal 2");
+        assert_eq!((games[0].playtime_forever, games[0].rtime_last_played), (754, 1_700_000_000));
+        assert_eq!(games[1].label(), "Steam app 292030");
+
+        let private: SteamOwnedGames = serde_json::from_str(r#"{"response": {}}"#).unwrap();
+        assert!(private.response.games.is_empty());
+    }
+}
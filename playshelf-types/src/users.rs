@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use crate::collections::Collection;
+use crate::shelf::ShelfEntry;
+
+/// A user and their library
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UserResponse {
+    #[serde(with = "user_id")]
+    pub id: u128,
+    pub username: String,
+    pub name: String,
+    pub description: String,
+    pub games: Vec<ShelfEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collections: Vec<Collection>,
+}
+
+/// Body of `PATCH /users/{username}`; fields left out are not changed
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct UpdateProfileRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// All users, returned by `GET /users` in the same shape as `sample_users.json`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UsersResponse {
+    pub users: Vec<UserResponse>,
+}
+
+/// The entries on a user's shelf, returned by `GET /users/{username}/games`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UserGamesResponse {
+    pub count: usize,
+    pub games: Vec<ShelfEntry>,
+}
+
+/// Serializes user IDs as JSON numbers when they fit in a `u64` and as decimal
+/// strings otherwise, since JSON values cannot hold 128-bit numbers portably
+///
+/// Random UUID IDs almost never fit in a `u64`, so clients mostly see IDs as
+/// strings such as `"250439578302442914426497513337113262371"`. Both forms are
+/// read back, but a bare number above `u64::MAX` is rejected rather than
+/// rounded, so larger IDs must be sent as strings.
+pub mod user_id {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        match u64::try_from(*id) {
+            Ok(id) => serializer.serialize_u64(id),
+            Err(_) => serializer.serialize_str(&id.to_string()),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Id {
+            Number(u64),
+            String(String),
+        }
+
+        match Id::deserialize(deserializer)? {
+            Id::Number(id) => Ok(id as u128),
+            Id::String(id) => id.parse().map_err(de::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Id(#[serde(with = "user_id")] u128);
+
+    #[test]
+    fn test_user_id_wire_format() {
+        assert_eq!(serde_json::to_value(Id(42)).unwrap(), serde_json::json!(42));
+        assert_eq!(
+            serde_json::to_value(Id(u128::MAX)).unwrap(),
+            serde_json::json!(u128::MAX.to_string())
+        );
+
+        let parsed: Id = serde_json::from_value(serde_json::json!(42)).unwrap();
+        assert_eq!(parsed, Id(42));
+        let parsed: Id = serde_json::from_value(serde_json::json!("42")).unwrap();
+        assert_eq!(parsed, Id(42));
+        let parsed: Id = serde_json::from_value(serde_json::json!(u128::MAX.to_string())).unwrap();
+        assert_eq!(parsed, Id(u128::MAX));
+
+        let number = serde_json::from_str::<Id>(&u128::MAX.to_string());
+        assert!(number.is_err(), "Bare numbers above u64::MAX are rejected");
+    }
+}
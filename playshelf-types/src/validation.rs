@@ -0,0 +1,329 @@
+//! Rules for usernames, profile fields, passwords and other user input
+//!
+//! The server enforces these on every write; the client uses the same
+//! functions to point out problems before a form is submitted.
+
+use chrono::NaiveDate;
+
+use crate::error::{FieldError, ValidationCode};
+use crate::sessions::LogSessionRequest;
+use crate::shelf::{ShelfEntry, ShelfStatus};
+
+pub const MIN_USERNAME_LENGTH: usize = 3;
+pub const MAX_USERNAME_LENGTH: usize = 32;
+pub const MAX_NAME_LENGTH: usize = 64;
+pub const MAX_DESCRIPTION_LENGTH: usize = 500;
+pub const MAX_REVIEW_LENGTH: usize = 10_000;
+pub const MAX_COLLECTION_NAME_LENGTH: usize = 64;
+pub const MAX_PLATFORM_LENGTH: usize = 64;
+pub const MAX_SESSION_NOTE_LENGTH: usize = 1_000;
+/// A logged session may last at most a day
+pub const MAX_SESSION_MINUTES: u32 = 24 * 60;
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+pub const MAX_PASSWORD_LENGTH: usize = 128;
+
+/// Usernames that could be mistaken for the service itself or clash with routes
+pub const RESERVED_USERNAMES: &[&str] = &[
+    "about", "admin", "administrator", "api", "assets", "auth", "cache", "games", "help", "login",
+    "logout", "me", "moderator", "null", "playshelf", "register", "root", "settings", "signin",
+    "signup", "static", "stats", "support", "system", "undefined", "users",
+];
+
+/// Checks that `username` is 3 to 32 lowercase ASCII letters, digits, `_` or
+/// `-`, starts with a letter and is not reserved
+pub fn validate_username(username: &str) -> Result<(), FieldError> {
+    let error = |code, message: String| Err(FieldError::new("username", code, message));
+    let length = username.chars().count();
+    if length == 0 {
+        return error(ValidationCode::Required, "Username is required".to_string());
+    }
+    if length < MIN_USERNAME_LENGTH {
+        return error(
+            ValidationCode::TooShort,
+            format!("Username must be at least {} characters", MIN_USERNAME_LENGTH),
+        );
+    }
+    if length > MAX_USERNAME_LENGTH {
+        return error(
+            ValidationCode::TooLong,
+            format!("Username must be at most {} characters", MAX_USERNAME_LENGTH),
+        );
+    }
+    let allowed = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-';
+    if !username.chars().all(allowed) || !username.starts_with(|c: char| c.is_ascii_lowercase()) {
+        return error(
+            ValidationCode::InvalidCharacters,
+            "Username must start with a letter and contain only lowercase letters, digits, '_' and '-'"
+                .to_string(),
+        );
+    }
+    if RESERVED_USERNAMES.contains(&username) {
+        return error(ValidationCode::Reserved, format!("'{}' is reserved", username));
+    }
+    Ok(())
+}
+
+/// Checks that the display name is 1 to 64 printable characters
+pub fn validate_name(name: &str) -> Result<(), FieldError> {
+    let error = |code, message: String| Err(FieldError::new("name", code, message));
+    if name.trim().is_empty() {
+        return error(ValidationCode::Required, "Name is required".to_string());
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return error(
+            ValidationCode::TooLong,
+            format!("Name must be at most {} characters", MAX_NAME_LENGTH),
+        );
+    }
+    if name.chars().any(char::is_control) {
+        return error(
+            ValidationCode::InvalidCharacters,
+            "Name must not contain control characters".to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Checks that the description is at most 500 characters, allowing line breaks and tabs
+pub fn validate_description(description: &str) -> Result<(), FieldError> {
+    let error = |code, message: String| Err(FieldError::new("description", code, message));
+    if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        return error(
+            ValidationCode::TooLong,
+            format!("Description must be at most {} characters", MAX_DESCRIPTION_LENGTH),
+        );
+    }
+    if description.chars().any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t')) {
+        return error(
+            ValidationCode::InvalidCharacters,
+            "Description must not contain control characters".to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Checks that a collection name is 1 to 64 printable characters
+pub fn validate_collection_name(name: &str) -> Result<(), FieldError> {
+    let error = |code, message: String| Err(FieldError::new("name", code, message));
+    if name.trim().is_empty() {
+        return error(ValidationCode::Required, "Collection name is required".to_string());
+    }
+    if name.chars().count() > MAX_COLLECTION_NAME_LENGTH {
+        return error(
+            ValidationCode::TooLong,
+            format!("Collection name must be at most {} characters", MAX_COLLECTION_NAME_LENGTH),
+        );
+    }
+    if name.chars().any(char::is_control) {
+        return error(
+            ValidationCode::InvalidCharacters,
+            "Collection name must not contain control characters".to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Checks that a Markdown review is at most 10,000 characters, allowing line breaks and tabs
+pub fn validate_review(review: &str) -> Result<(), FieldError> {
+    let error = |code, message: String| Err(FieldError::new("review", code, message));
+    if review.chars().count() > MAX_REVIEW_LENGTH {
+        return error(
+            ValidationCode::TooLong,
+            format!("Review must be at most {} characters", MAX_REVIEW_LENGTH),
+        );
+    }
+    if review.chars().any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t')) {
+        return error(
+            ValidationCode::InvalidCharacters,
+            "Review must not contain control characters".to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Checks that moving `entry` to `status` on `date` keeps its dates in order:
+/// not in the future, not before the game was added and, when finishing it,
+/// not before it was started
+pub fn validate_status_date(
+    entry: &ShelfEntry,
+    status: ShelfStatus,
+    date: NaiveDate,
+    today: NaiveDate,
+) -> Result<(), FieldError> {
+    let error = |message: String| Err(FieldError::new("date", ValidationCode::OutOfRange, message));
+    if date > today {
+        return error("Date cannot be in the future".to_string());
+    }
+    if let Some(added_on) = entry.added_on.filter(|added_on| date < *added_on) {
+        return error(format!("Date cannot be before the game was added on {}", added_on));
+    }
+    if status.is_finished() {
+        if let Some(started_on) = entry.started_on.filter(|started_on| date < *started_on) {
+            return error(format!("Date cannot be before the game was started on {}", started_on));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a session has either an end after its start or a duration,
+/// and lasts 1 minute to 24 hours
+pub fn validate_session_length(request: &LogSessionRequest) -> Result<(), FieldError> {
+    let error = |code, message: String| Err(FieldError::new("duration_minutes", code, message));
+    if request.ended_at.is_some() == request.duration_minutes.is_some() {
+        return error(
+            ValidationCode::Required,
+            "Give either an end time or a duration".to_string(),
+        );
+    }
+    match request.duration_minutes() {
+        Some(minutes) if (1..=MAX_SESSION_MINUTES).contains(&minutes) => Ok(()),
+        _ => error(
+            ValidationCode::OutOfRange,
+            format!("A session must last between 1 minute and {} hours", MAX_SESSION_MINUTES / 60),
+        ),
+    }
+}
+
+/// Checks that the platform a session was played on is 1 to 64 printable characters
+pub fn validate_session_platform(platform: &str) -> Result<(), FieldError> {
+    let error = |code, message: String| Err(FieldError::new("platform", code, message));
+    if platform.trim().is_empty() {
+        return error(ValidationCode::Required, "Platform must not be blank".to_string());
+    }
+    if platform.chars().count() > MAX_PLATFORM_LENGTH {
+        return error(
+            ValidationCode::TooLong,
+            format!("Platform must be at most {} characters", MAX_PLATFORM_LENGTH),
+        );
+    }
+    if platform.chars().any(char::is_control) {
+        return error(
+            ValidationCode::InvalidCharacters,
+            "Platform must not contain control characters".to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Checks that a session note is at most 1000 characters, allowing line breaks and tabs
+pub fn validate_session_note(note: &str) -> Result<(), FieldError> {
+    let error = |code, message: String| Err(FieldError::new("note", code, message));
+    if note.chars().count() > MAX_SESSION_NOTE_LENGTH {
+        return error(
+            ValidationCode::TooLong,
+            format!("Note must be at most {} characters", MAX_SESSION_NOTE_LENGTH),
+        );
+    }
+    if note.chars().any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t')) {
+        return error(
+            ValidationCode::InvalidCharacters,
+            "Note must not contain control characters".to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Checks that the password is 8 to 128 characters
+pub fn validate_password(password: &str) -> Result<(), FieldError> {
+    let length = password.chars().count();
+    if length < MIN_PASSWORD_LENGTH {
+        return Err(FieldError::new(
+            "password",
+            ValidationCode::TooShort,
+            format!("Password must be at least {} characters", MIN_PASSWORD_LENGTH),
+        ));
+    }
+    if length > MAX_PASSWORD_LENGTH {
+        return Err(FieldError::new(
+            "password",
+            ValidationCode::TooLong,
+            format!("Password must be at most {} characters", MAX_PASSWORD_LENGTH),
+        ));
+    }
+    Ok(())
+}
+
+/// Collects the errors of every check, so all problems are reported at once
+pub fn collect_errors(checks: impl IntoIterator<Item = Result<(), FieldError>>) -> Vec<FieldError> {
+    checks.into_iter().filter_map(Result::err).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn username_code(username: &str) -> Option<ValidationCode> {
+        validate_username(username).err().map(|error| error.code)
+    }
+
+    #[test]
+    fn test_username_rules() {
+        assert_eq!(username_code("link"), None);
+        assert_eq!(username_code("hero_of-time2"), None);
+        assert_eq!(username_code(""), Some(ValidationCode::Required));
+        assert_eq!(username_code("li"), Some(ValidationCode::TooShort));
+        assert_eq!(username_code(&"a".repeat(33)), Some(ValidationCode::TooLong));
+        assert_eq!(username_code("Link"), Some(ValidationCode::InvalidCharacters));
+        assert_eq!(username_code("2link"), Some(ValidationCode::InvalidCharacters));
+        assert_eq!(username_code("link zelda"), Some(ValidationCode::InvalidCharacters));
+        assert_eq!(username_code("lïnk"), Some(ValidationCode::InvalidCharacters));
+        assert_eq!(username_code("admin"), Some(ValidationCode::Reserved));
+    }
+
+    #[test]
+    fn test_profile_rules() {
+        assert!(validate_name("Hyun Jae Moon's Library").is_ok());
+        assert_eq!(validate_name("  ").unwrap_err().code, ValidationCode::Required);
+        assert_eq!(validate_name(&"x".repeat(65)).unwrap_err().code, ValidationCode::TooLong);
+        assert_eq!(validate_name("Link\u{0}").unwrap_err().code, ValidationCode::InvalidCharacters);
+
+        assert!(validate_description("").is_ok());
+        assert!(validate_description("Line one\nLine two").is_ok());
+        assert_eq!(validate_description(&"x".repeat(501)).unwrap_err().code, ValidationCode::TooLong);
+        assert_eq!(validate_description("\u{7}").unwrap_err().code, ValidationCode::InvalidCharacters);
+        assert!(validate_review("# Great\n\n- tight controls").is_ok());
+        assert_eq!(validate_review(&"x".repeat(10_001)).unwrap_err().code, ValidationCode::TooLong);
+
+        let errors = collect_errors([validate_username("x"), validate_name("ok"), validate_password("short")]);
+        let fields: Vec<&str> = errors.iter().map(|error| error.field.as_str()).collect();
+        assert_eq!(fields, vec!["username", "password"]);
+    }
+
+    #[test]
+    fn test_status_date_rules() {
+        let day = |day: u32| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+        let game = serde_json::from_str(r#"{"id": 42, "name": "Celeste", "platforms": [], "genres": []}"#).unwrap();
+        let mut entry = ShelfEntry::new(game, day(2));
+        entry.set_status(ShelfStatus::Playing, day(4));
+        let code = |status, date| validate_status_date(&entry, status, date, day(10)).err().map(|error| error.code);
+
+        assert_eq!(code(ShelfStatus::Completed, day(6)), None);
+        assert_eq!(code(ShelfStatus::Completed, day(11)), Some(ValidationCode::OutOfRange));
+        assert_eq!(code(ShelfStatus::Completed, day(3)), Some(ValidationCode::OutOfRange));
+        assert_eq!(code(ShelfStatus::Backlog, day(3)), None);
+        assert_eq!(code(ShelfStatus::Backlog, day(1)), Some(ValidationCode::OutOfRange));
+    }
+
+    #[test]
+    fn test_session_rules() {
+        let mut request = LogSessionRequest {
+            started_at: "2024-05-01T20:00:00Z".parse().unwrap(),
+            ended_at: None,
+            duration_minutes: None,
+            platform: None,
+            note: None,
+        };
+        let code = |request: &LogSessionRequest| validate_session_length(request).err().map(|error| error.code);
+        assert_eq!(code(&request), Some(ValidationCode::Required));
+        request.duration_minutes = Some(0);
+        assert_eq!(code(&request), Some(ValidationCode::OutOfRange));
+        request.duration_minutes = Some(25 * 60);
+        assert_eq!(code(&request), Some(ValidationCode::OutOfRange));
+        request.duration_minutes = Some(90);
+        assert_eq!(code(&request), None);
+
+        assert!(validate_session_platform("Nintendo Switch").is_ok());
+        assert_eq!(validate_session_platform(" ").unwrap_err().code, ValidationCode::Required);
+        assert_eq!(validate_session_note(&"x".repeat(1_001)).unwrap_err().code, ValidationCode::TooLong);
+    }
+}